@@ -1,12 +1,66 @@
+use std::rc::Rc;
+
 use wasm_bindgen::prelude::*;
 
-use js_instrumentation_shared::{InstrumentationInput, InstrumentationOptions};
+use js_instrumentation_shared::{
+    InstrumentationBatchInput, InstrumentationInput, InstrumentationOptions, SourceMapResolver,
+};
+
+/// Resolves external source maps by calling a JS function with the source map's URL and the
+/// id of the file that references it. The function should return the source map's contents,
+/// or undefined if it can't be loaded.
+struct JsSourceMapResolver(js_sys::Function);
+
+impl SourceMapResolver for JsSourceMapResolver {
+    fn resolve(self: &Self, url: &str, id: &str) -> Option<String> {
+        self.0
+            .call2(
+                &JsValue::NULL,
+                &JsValue::from_str(url),
+                &JsValue::from_str(id),
+            )
+            .ok()?
+            .as_string()
+    }
+}
+
+fn options_from_value(
+    options: JsValue,
+    resolve_source_map: Option<js_sys::Function>,
+) -> Result<InstrumentationOptions, JsValue> {
+    let mut options: InstrumentationOptions = serde_wasm_bindgen::from_value(options)?;
+    if let Some(resolve_source_map) = resolve_source_map {
+        options.input.source_map_resolver = Some(Rc::new(JsSourceMapResolver(resolve_source_map)));
+    }
+    Ok(options)
+}
 
 #[wasm_bindgen]
-pub fn transform(input: JsValue, options: JsValue) -> Result<JsValue, JsError> {
+pub fn transform(
+    input: JsValue,
+    options: JsValue,
+    resolve_source_map: Option<js_sys::Function>,
+) -> Result<JsValue, JsValue> {
     let input: InstrumentationInput = serde_wasm_bindgen::from_value(input)?;
-    let options: InstrumentationOptions = serde_wasm_bindgen::from_value(options)?;
-    let transform_output = js_instrumentation_transform::apply_transform(&input, &options);
-    let js_result = transform_output.map_err(|e| JsError::from(&*e))?;
-    Ok(serde_wasm_bindgen::to_value(&js_result)?)
+    let options = options_from_value(options, resolve_source_map)?;
+    match js_instrumentation_transform::apply_transform(&input, &options) {
+        Ok(output) => Ok(serde_wasm_bindgen::to_value(&output)?),
+        // Errors are thrown as plain objects describing the error, so that the JS side can
+        // report them with full detail.
+        Err(err) => Err(serde_wasm_bindgen::to_value(&err)?),
+    }
+}
+
+#[wasm_bindgen]
+pub fn transform_batch(
+    input: JsValue,
+    options: JsValue,
+    resolve_source_map: Option<js_sys::Function>,
+) -> Result<JsValue, JsValue> {
+    let input: InstrumentationBatchInput = serde_wasm_bindgen::from_value(input)?;
+    let options = options_from_value(options, resolve_source_map)?;
+    match js_instrumentation_transform::apply_batch_transform(&input, &options) {
+        Ok(output) => Ok(serde_wasm_bindgen::to_value(&output)?),
+        Err(err) => Err(serde_wasm_bindgen::to_value(&err)?),
+    }
 }
@@ -1,11 +1,15 @@
-use js_instrumentation_shared::{debug_log, InputFile};
+use js_instrumentation_shared::InputFile;
 use swc_common::BytePos;
 use swc_core::base::sourcemap::SourceMap;
 
-use crate::{rewrite::Rewrite, rewrite_content::RewriteContent, rewrite_output::RewriteOutput};
+use crate::{
+    rewrite::Rewrite, rewrite_content::RewriteContent, rewrite_error::RewriteError,
+    rewrite_output::RewriteOutput,
+};
 
 pub struct RewritePlan<Content: RewriteContent> {
     rewrites: Vec<Rewrite<Content>>,
+    skipped_rewrites: Vec<Rewrite<Content>>,
     source_positions_used_in_mappings: Vec<BytePos>,
 }
 
@@ -27,24 +31,33 @@ where
     // Filter out rewrites that overlap with previous rewrites, since they would otherwise
     // conflict.
     let mut prev_hi: Option<BytePos> = None;
-    let filtered_body_rewrites_iterable = body_rewrites.into_iter().filter(|rewrite| {
-        let should_keep = match prev_hi {
-            None => true,
-            Some(prev_hi) if prev_hi > *rewrite.lo() => false,
-            Some(_) => true,
-        };
-        if should_keep {
-            prev_hi = Some(*rewrite.hi());
-        } else {
-            debug_log(&format!("Skipping rewrite due to overlap: {}", rewrite));
-        }
-        should_keep
-    });
+    let (filtered_body_rewrites, skipped_rewrites): (Vec<_>, Vec<_>) =
+        body_rewrites.into_iter().partition(|rewrite| {
+            let should_keep = match prev_hi {
+                None => true,
+                Some(prev_hi) if prev_hi > *rewrite.lo() => false,
+                Some(_) => true,
+            };
+            if should_keep {
+                prev_hi = Some(*rewrite.hi());
+            }
+            should_keep
+        });
 
-    // Combine the header and body rewrites.
-    let rewrites: Vec<Rewrite<Content>> = header_iterable
-        .into_iter()
-        .chain(filtered_body_rewrites_iterable)
+    // Combine the header and body rewrites. The header usually goes at the start of the
+    // file, but documents that embed scripts in markup (e.g. Vue components) may need it
+    // further in, so each header rewrite goes before any body rewrite at or after its position.
+    // Header rewrites at the same position stay in the order they were emitted.
+    let mut header_rewrites: Vec<Rewrite<Content>> = header_iterable.into_iter().collect();
+    header_rewrites.sort_by_key(|rewrite| *rewrite.lo());
+    let mut header_rewrites = header_rewrites.into_iter().peekable();
+    let mut body_rewrites = filtered_body_rewrites.into_iter().peekable();
+    let rewrites: Vec<Rewrite<Content>> =
+        std::iter::from_fn(|| match (header_rewrites.peek(), body_rewrites.peek()) {
+            (Some(header), Some(body)) if header.lo() > body.lo() => body_rewrites.next(),
+            (Some(_), _) => header_rewrites.next(),
+            (None, _) => body_rewrites.next(),
+        })
         .inspect(|rewrite| {
             // Collect positions that we'll need to track to generate source mappings.
             if let Some(pos) = rewrite.content().source_pos() {
@@ -58,17 +71,43 @@ where
     // The result should be a safe rewrite plan.
     RewritePlan {
         rewrites,
+        skipped_rewrites,
         source_positions_used_in_mappings,
     }
 }
 
 impl<Content: RewriteContent> RewritePlan<Content> {
+    /// Returns the rewrites that were left out of the plan because they overlapped with an
+    /// earlier rewrite.
+    pub fn skipped_rewrites(self: &Self) -> &[Rewrite<Content>] {
+        &self.skipped_rewrites
+    }
+
     pub fn apply<'a>(
         self: Self,
         input_file: &mut InputFile<'a>,
         token_positions: Vec<BytePos>,
         embed_code_in_source_map: bool,
-    ) -> (String, SourceMap) {
+    ) -> Result<(String, SourceMap), RewriteError> {
+        // We can only rewrite whole characters of the input.
+        let is_valid_pos = |pos: &BytePos| {
+            *pos >= input_file.start_pos
+                && *pos <= input_file.end_pos
+                && input_file
+                    .code
+                    .is_char_boundary((pos.0 - input_file.start_pos.0) as usize)
+        };
+        if let Some(rewrite) = self
+            .rewrites
+            .iter()
+            .find(|rewrite| !is_valid_pos(rewrite.lo()) || !is_valid_pos(rewrite.hi()))
+        {
+            return Err(RewriteError::new(format!(
+                "{} doesn't start and end on character boundaries in the input",
+                rewrite
+            )));
+        }
+
         let embedded_code = if embed_code_in_source_map {
             Some(vec![Some(String::from(input_file.code).into())])
         } else {
@@ -108,6 +147,6 @@ impl<Content: RewriteContent> RewritePlan<Content> {
             embedded_code,
         );
 
-        return (rewritten_code, source_map);
+        return Ok((rewritten_code, source_map));
     }
 }
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use swc_common::BytePos;
+
+use crate::input_file::InputFile;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiagnosticCode {
+    /// The privacy dictionary exceeded its budget, so some entries were dropped.
+    DictionaryBudgetExceeded,
+    /// The input referenced an external source map, which we can't chain with, so no source
+    /// map was generated.
+    ExternalSourceMap,
+    /// The input contained an inline source map, but an input source map was also provided,
+    /// so the inline source map was ignored.
+    InlineSourceMapIgnored,
+    /// The parser reported an error, but was able to recover from it.
+    RecoveredParseError,
+    /// A rewrite was skipped because it overlapped with another rewrite.
+    RewriteOverlap,
+    /// A string was kept out of the privacy dictionary because it looks like a secret or
+    /// personal information.
+    SensitiveStringRejected,
+    /// The file could only be parsed after changing the JSX or TypeScript settings.
+    SyntaxFallback,
+    /// A rewrite was skipped because its content couldn't be generated.
+    TemplateEvaluationFailed,
+    /// A privacy allowlist exclude-begin or exclude-end directive had no matching directive.
+    UnbalancedExcludeDirective,
+    /// Part of a component was written in a language we can't instrument (e.g. a Pug
+    /// template), or couldn't refer to the dictionary (e.g. the template of a Vue component
+    /// without `<script setup>`), so its strings weren't collected.
+    UnsupportedMarkup,
+}
+
+/// A problem that didn't prevent instrumentation, but that may have affected the output.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub code: DiagnosticCode,
+    /// A human-readable description of the problem.
+    pub message: String,
+    /// The line (1-based) where the problem occurred, if it's associated with a location.
+    pub line: Option<usize>,
+    /// The column (0-based) where the problem occurred, if it's associated with a location.
+    pub column: Option<usize>,
+}
+
+impl Diagnostic {
+    pub fn new(code: DiagnosticCode, message: String) -> Diagnostic {
+        Diagnostic {
+            code,
+            message,
+            line: None,
+            column: None,
+        }
+    }
+
+    pub fn at(
+        code: DiagnosticCode,
+        message: String,
+        input_file: &InputFile,
+        pos: BytePos,
+    ) -> Diagnostic {
+        let (line, column) = input_file.line_and_column(pos);
+        Diagnostic {
+            code,
+            message,
+            line: Some(line),
+            column: Some(column),
+        }
+    }
+}
@@ -28,4 +28,25 @@ pub fn filename_is_explicitly_esm(filename: &str) -> bool {
         || filename.ends_with(".mjsx")
         || filename.ends_with(".mts")
         || filename.ends_with(".mtsx")
+        // Component scripts are always ES modules.
+        || filename_is_vue(filename)
+        || filename_is_svelte(filename)
+        || filename_is_astro(filename)
+        || filename_is_mdx(filename)
+}
+
+pub fn filename_is_vue(filename: &str) -> bool {
+    filename.ends_with(".vue")
+}
+
+pub fn filename_is_svelte(filename: &str) -> bool {
+    filename.ends_with(".svelte")
+}
+
+pub fn filename_is_astro(filename: &str) -> bool {
+    filename.ends_with(".astro")
+}
+
+pub fn filename_is_mdx(filename: &str) -> bool {
+    filename.ends_with(".mdx")
 }
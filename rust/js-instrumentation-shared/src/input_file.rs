@@ -39,6 +39,12 @@ impl<'a> InputFile<'a> {
         }
     }
 
+    /// Returns the line (1-based) and column (0-based) of the given position.
+    pub fn line_and_column(self: &Self, pos: BytePos) -> (usize, usize) {
+        let loc = self.map.lookup_char_pos(pos);
+        (loc.line, loc.col.0)
+    }
+
     pub fn may_follow_keyword(self: &mut Self, pos: BytePos) -> bool {
         if pos == self.start_pos {
             return false;
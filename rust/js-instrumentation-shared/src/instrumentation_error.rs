@@ -0,0 +1,211 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use swc_common::{Span, Spanned};
+use swc_ecma_parser::error::Error as ParseError;
+
+use crate::input_file::InputFile;
+
+/// The number of lines of context to include before and after the error in a code frame.
+const CODE_FRAME_CONTEXT_LINES: usize = 1;
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "type")]
+#[serde(rename_all = "camelCase")]
+#[serde(rename_all_fields = "camelCase")]
+pub enum InstrumentationError {
+    /// The input could not be parsed.
+    Parse {
+        /// The filename or id of the code that failed to parse.
+        id: String,
+        /// A human-readable description of the error.
+        message: String,
+        /// The line (1-based) where the error occurred.
+        line: usize,
+        /// The column (0-based) where the error occurred.
+        column: usize,
+        /// The kind of syntax error reported by the parser (e.g. "UnterminatedStrLit").
+        kind: String,
+        /// An excerpt of the input surrounding the error, with the error location marked.
+        code_frame: String,
+    },
+    /// An input source map could not be parsed, or the output source map could not be
+    /// generated.
+    SourceMap { id: String, message: String },
+    /// The instrumented code could not be generated.
+    Rewrite { id: String, message: String },
+    /// The instrumentation options were invalid.
+    Options { id: String, message: String },
+}
+
+impl InstrumentationError {
+    pub fn parse(input_file: &InputFile, err: &ParseError) -> InstrumentationError {
+        let span = err.span();
+        let (line, column) = input_file.line_and_column(span.lo);
+        InstrumentationError::Parse {
+            id: input_file.name.to_string(),
+            message: err.kind().msg().to_string(),
+            line,
+            column,
+            kind: syntax_error_kind_name(err),
+            code_frame: build_code_frame(input_file, span),
+        }
+    }
+
+    pub fn source_map<E: fmt::Display>(id: &str, err: E) -> InstrumentationError {
+        InstrumentationError::SourceMap {
+            id: id.to_string(),
+            message: err.to_string(),
+        }
+    }
+
+    pub fn rewrite<E: fmt::Display>(id: &str, err: E) -> InstrumentationError {
+        InstrumentationError::Rewrite {
+            id: id.to_string(),
+            message: err.to_string(),
+        }
+    }
+
+    pub fn options<E: fmt::Display>(id: &str, err: E) -> InstrumentationError {
+        InstrumentationError::Options {
+            id: id.to_string(),
+            message: err.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for InstrumentationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InstrumentationError::Parse {
+                id,
+                message,
+                line,
+                column,
+                code_frame,
+                ..
+            } => write!(
+                f,
+                "Parsing failed: {} ({}:{}:{})\n{}",
+                message, id, line, column, code_frame
+            ),
+            InstrumentationError::SourceMap { id, message } => {
+                write!(f, "Source map error: {} ({})", message, id)
+            }
+            InstrumentationError::Rewrite { id, message } => {
+                write!(f, "Rewriting failed: {} ({})", message, id)
+            }
+            InstrumentationError::Options { id, message } => {
+                write!(f, "Invalid options: {} ({})", message, id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InstrumentationError {}
+
+/// Returns the name of the SyntaxError variant, without any of its fields.
+fn syntax_error_kind_name(err: &ParseError) -> String {
+    format!("{:?}", err.kind())
+        .chars()
+        .take_while(|ch| ch.is_alphanumeric() || *ch == '_')
+        .collect()
+}
+
+/// Builds an excerpt of the input around the given span, like:
+///
+/// ```text
+///   1 | const a = 1;
+/// > 2 | const b = ;
+///     |           ^
+///   3 | const c = 3;
+/// ```
+fn build_code_frame(input_file: &InputFile, span: Span) -> String {
+    let loc = input_file.map.lookup_char_pos(span.lo);
+    let line_index = loc.line - 1;
+    let first_line_index = line_index.saturating_sub(CODE_FRAME_CONTEXT_LINES);
+    let last_line_index = line_index + CODE_FRAME_CONTEXT_LINES;
+    let gutter_width = (last_line_index + 1).to_string().len();
+
+    let mut frame = String::new();
+    for index in first_line_index..=last_line_index {
+        let line = match loc.file.get_line(index) {
+            Some(line) => line,
+            None => break,
+        };
+        let marker = if index == line_index { '>' } else { ' ' };
+        frame += &format!(
+            "{} {:>width$} | {}\n",
+            marker,
+            index + 1,
+            line.trim_end(),
+            width = gutter_width
+        );
+        if index == line_index {
+            // Keep the line's tabs, so the caret lines up however wide they're displayed.
+            let padding: String = line
+                .chars()
+                .take(loc.col.0)
+                .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+                .collect();
+            frame += &format!("  {:>width$} | {}^\n", "", padding, width = gutter_width);
+        }
+    }
+
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_common::comments::SingleThreadedComments;
+
+    use crate::build_parser;
+
+    use super::*;
+
+    #[test]
+    fn describes_the_location_of_parse_errors() {
+        let input_file = InputFile::new("input.js", "const a = 1;\nconst b = ;\nconst c = 3;\n");
+        let comments = SingleThreadedComments::default();
+        let mut parser = build_parser(&input_file, &comments, &Default::default());
+        let err = parser.parse_program().unwrap_err();
+
+        match InstrumentationError::parse(&input_file, &err) {
+            InstrumentationError::Parse {
+                id,
+                line,
+                column,
+                kind,
+                code_frame,
+                ..
+            } => {
+                assert_eq!(id, "input.js");
+                assert_eq!((line, column), (2, 10));
+                assert_eq!(kind, "TS1109");
+                assert_eq!(
+                    code_frame,
+                    "  1 | const a = 1;\n> 2 | const b = ;\n    |           ^\n  3 | const c = 3;\n"
+                );
+            }
+            other => panic!("Unexpected error: {}", other),
+        }
+    }
+
+    #[test]
+    fn keeps_tabs_in_code_frame_padding() {
+        let input_file = InputFile::new("input.js", "if (a) {\n\tconst b = ;\n}\n");
+        let comments = SingleThreadedComments::default();
+        let mut parser = build_parser(&input_file, &comments, &Default::default());
+        let err = parser.parse_program().unwrap_err();
+
+        match InstrumentationError::parse(&input_file, &err) {
+            InstrumentationError::Parse { code_frame, .. } => {
+                assert_eq!(
+                    code_frame,
+                    "  1 | if (a) {\n> 2 | \tconst b = ;\n    | \t          ^\n  3 | }\n"
+                );
+            }
+            other => panic!("Unexpected error: {}", other),
+        }
+    }
+}
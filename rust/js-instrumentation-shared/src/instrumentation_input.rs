@@ -9,3 +9,14 @@ pub struct InstrumentationInput {
     /// The source map for the code to instrument, if any.
     pub map: Option<String>,
 }
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstrumentationBatchInput {
+    /// The files to instrument. They'll share a single privacy dictionary, so the files that
+    /// collect any strings must all be the same kind of module as the shared dictionary module.
+    pub inputs: Vec<InstrumentationInput>,
+    /// The filename or id of the shared dictionary module. Instrumented files import the
+    /// dictionary using this id as the module specifier.
+    pub shared_dictionary_id: String,
+}
@@ -1,6 +1,8 @@
+use std::{collections::HashMap, fmt::Display, rc::Rc};
+
 use serde::{Deserialize, Serialize};
 
-use crate::module_kind::ModuleKind;
+use crate::{module_kind::ModuleKind, source_map_resolver::SourceMapResolver};
 
 #[derive(Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -14,6 +16,29 @@ pub struct InputOptions {
 
     /// True: enable TypeScript. False: disable TypeScript. None: guess based on filename.
     pub typescript: Option<bool>,
+
+    /// True: if parsing fails, retry with other combinations of the JSX and TypeScript
+    /// settings before giving up.
+    #[serde(default = "default_syntax_fallback")]
+    pub syntax_fallback: bool,
+
+    /// True: fail if the parser reports any errors, even ones it can recover from.
+    /// False: instrument the file anyway, and report recoverable errors as warnings.
+    #[serde(default)]
+    pub strict_parsing: bool,
+
+    /// The contents of external source maps, keyed by the URL that appears in the
+    /// `sourceMappingURL` comment that references them.
+    #[serde(default)]
+    pub external_source_maps: HashMap<String, String>,
+
+    /// Some: used to load external source maps that don't appear in `external_source_maps`.
+    #[serde(skip)]
+    pub source_map_resolver: Option<Rc<dyn SourceMapResolver>>,
+}
+
+fn default_syntax_fallback() -> bool {
+    true
 }
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -24,6 +49,56 @@ pub struct OutputOptions {
 
     /// True: embed the source code in the source map.
     pub embed_code_in_source_map: bool,
+
+    /// True: include a report listing the privacy dictionary's entries in the output.
+    #[serde(default)]
+    pub privacy_dictionary_report: bool,
+
+    /// What to do with an external `sourceMappingURL` comment once we've chained with the
+    /// source map it references.
+    #[serde(default)]
+    pub external_source_map_comment: ExternalSourceMapComment,
+
+    /// True: if the input source map was an index source map, return an index source map.
+    /// Inline source maps are always regular source maps.
+    #[serde(default)]
+    pub index_source_map: bool,
+
+    /// True: insert the helper import and dictionary declaration after any license comments
+    /// (e.g. `/*! ... */`) that precede the first statement.
+    #[serde(default)]
+    pub insert_header_after_license_comments: bool,
+
+    /// How to declare the privacy dictionary.
+    #[serde(default)]
+    pub dictionary_declaration: DictionaryDeclaration,
+}
+
+#[derive(Clone, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DictionaryDeclaration {
+    /// Declare the dictionary with `const D=...`. This is the most compact option, but if the
+    /// module is part of an import cycle, code that runs before the module body is evaluated
+    /// (e.g. a hoisted function called from another module in the cycle) will throw a
+    /// ReferenceError when it accesses the dictionary.
+    #[default]
+    Const,
+    /// Declare the dictionary as a hoisted function that builds the dictionary the first time
+    /// it's called, and reference entries with `D()[0]`. This is a bit larger, but it's safe
+    /// to access the dictionary at any time, even in an import cycle.
+    Function,
+}
+
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExternalSourceMapComment {
+    /// Remove the comment; it references a source map that doesn't match the output.
+    #[default]
+    Remove,
+    /// Leave the comment as-is.
+    Keep,
+    /// Replace the comment with one that references the given URL.
+    Replace { url: String },
 }
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -38,12 +113,320 @@ pub enum HelperFunctionSource {
         esm_module: String,
         func: String,
     },
+    /// Read the helper from a dot-separated property path on `globalThis`, e.g.
+    /// `DD_PRIVACY.$` for `globalThis.DD_PRIVACY.$`. Unlike an import, this also works in
+    /// classic scripts.
+    Global {
+        path: String,
+    },
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StringFilterAction {
+    /// Add matching strings to the privacy dictionary.
+    Include,
+    /// Don't add matching strings to the privacy dictionary.
+    Exclude,
+}
+
+/// A rule that decides whether strings are added to the privacy dictionary. A rule matches a
+/// string if every condition it specifies matches; conditions which are None are ignored.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StringFilterRule {
+    pub action: StringFilterAction,
+
+    /// Some: the string must match this regular expression.
+    #[serde(default)]
+    pub regex: Option<String>,
+
+    /// Some: the string must be at least this many bytes long.
+    #[serde(default)]
+    pub min_length: Option<usize>,
+
+    /// Some: the string must be at most this many bytes long.
+    #[serde(default)]
+    pub max_length: Option<usize>,
+
+    /// Some: the string must start with this prefix.
+    #[serde(default)]
+    pub prefix: Option<String>,
+
+    /// Some: the string must end with this suffix.
+    #[serde(default)]
+    pub suffix: Option<String>,
+}
+
+/// The built-in rules that decide whether strings are added to the dictionary, when no
+/// user-specified rule matches. Whatever the preset, strings longer than 4096 bytes are never
+/// added to the dictionary, even if a user-specified rule includes them.
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StringFilterPreset {
+    /// Exclude strings that look like URLs, file names, numbers, or code.
+    #[default]
+    Default,
+    /// Don't apply any built-in rules; only the user-specified rules are used.
+    None,
+}
+
+/// A call or constructor expression whose arguments should never be collected.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UncollectedCall {
+    /// The callee, as a bare identifier (e.g. `t`) or a member path (e.g.
+    /// `document.querySelector`). May contain `*` wildcards, e.g. `logger.*`.
+    pub callee: String,
+
+    /// Some: only the arguments at these indices are uncollected. None: no arguments are
+    /// collected.
+    #[serde(default)]
+    pub arguments: Option<Vec<usize>>,
+}
+
+/// A function that creates a UI element, like React's `createElement()` or Preact's `h()`.
+/// Calls to element factories are collected like JSX: the tag is never collected, the props
+/// are collected like JSX attributes, and the remaining arguments like JSX children.
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElementFactory {
+    /// Names that calls to the factory may use, as bare identifiers (e.g. `h`) or member paths
+    /// (e.g. `React.createElement`). Patterns may contain `*` wildcards.
+    #[serde(default)]
+    pub callees: Vec<String>,
+
+    /// Some: the module export that the factory is. Calls through any binding that imports or
+    /// requires it are recognized too, whatever the binding is named.
+    #[serde(default)]
+    pub import: Option<ElementFactoryImport>,
+
+    /// Some: the index of the argument that holds the element's tag or component.
+    #[serde(default)]
+    pub tag_argument: Option<usize>,
+
+    /// Some: the index of the argument that holds the element's props.
+    #[serde(default)]
+    pub props_argument: Option<usize>,
+
+    /// The indices of other arguments that are never collected, e.g. flags or lists of prop
+    /// names.
+    #[serde(default)]
+    pub uncollected_arguments: Vec<usize>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElementFactoryImport {
+    /// The module that exports the factory, e.g. `preact`.
+    pub module: String,
+    /// The name the module exports the factory with, e.g. `h`.
+    pub name: String,
+}
+
+/// A built-in set of element factories for a UI framework.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ElementFactoryPreset {
+    /// `React.createElement()`, and the automatic JSX runtime's `jsx()`, `jsxs()` and
+    /// `jsxDEV()`.
+    React,
+    /// `h()` and `createElement()`, and Preact's JSX runtime.
+    Preact,
+    /// `h()`, and the functions that compiled templates call, like `createElementVNode()` and
+    /// `createTextVNode()`.
+    Vue,
+    /// `template()`. Its argument is an HTML string, which is never collected.
+    Solid,
+    /// `createVNode()`, `createComponentVNode()` and `createTextVNode()`.
+    Inferno,
+}
+
+/// Limits on the size of a file's privacy dictionary. When a limit is exceeded, the least
+/// valuable entries are dropped, and their strings are left untouched. An entry's value is
+/// the number of bytes that replacing its strings with dictionary references saves, minus
+/// the size of the entry itself. Tagged templates are never dropped.
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DictionaryBudget {
+    /// Some: the dictionary may contain at most this many entries.
+    #[serde(default)]
+    pub max_entries: Option<usize>,
+    /// Some: the dictionary's entries may total at most this many bytes.
+    #[serde(default)]
+    pub max_bytes: Option<usize>,
+    /// Some: the dictionary may grow the file by at most this fraction of its original size
+    /// (e.g. 0.1 for 10%), taking into account the bytes saved by dictionary references.
+    #[serde(default)]
+    pub max_growth_ratio: Option<f64>,
+}
+
+/// How strings that look like secrets or personal information are kept out of the privacy
+/// dictionary. Rejected strings are left untouched, and each rejection is reported as a
+/// warning.
+#[derive(Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SensitiveStringDetection {
+    /// How aggressively to look for sensitive strings.
+    #[serde(default)]
+    pub sensitivity: DetectionSensitivity,
+    /// Rules that shouldn't be applied, e.g. `email` for code that's full of example addresses.
+    #[serde(default)]
+    pub disabled_rules: Vec<SensitiveStringRule>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DetectionSensitivity {
+    /// Don't look for sensitive strings.
+    Off,
+    /// Only look for credentials, like API keys and private keys, and only flag very long,
+    /// random-looking tokens as high-entropy.
+    Low,
+    /// Also look for personal information, like email addresses and credit card numbers.
+    #[default]
+    Medium,
+    /// Like `Medium`, but also flag shorter and less random tokens as high-entropy.
+    High,
+}
+
+/// A kind of sensitive value. A string is rejected if it contains one anywhere.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SensitiveStringRule {
+    /// An email address.
+    Email,
+    /// A JSON Web Token.
+    Jwt,
+    /// An API key or access token with a well-known prefix, e.g. `sk_live_` or `ghp_`.
+    ApiKey,
+    /// An AWS access key ID.
+    AwsAccessKey,
+    /// A PEM-encoded private key.
+    PrivateKey,
+    /// A number that's shaped like a credit card number and passes the Luhn check.
+    CreditCard,
+    /// A long, random-looking token, like a hex or base64-encoded secret.
+    HighEntropy,
+}
+
+impl Display for SensitiveStringRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SensitiveStringRule::Email => write!(f, "email"),
+            SensitiveStringRule::Jwt => write!(f, "jwt"),
+            SensitiveStringRule::ApiKey => write!(f, "apiKey"),
+            SensitiveStringRule::AwsAccessKey => write!(f, "awsAccessKey"),
+            SensitiveStringRule::PrivateKey => write!(f, "privateKey"),
+            SensitiveStringRule::CreditCard => write!(f, "creditCard"),
+            SensitiveStringRule::HighEntropy => write!(f, "highEntropy"),
+        }
+    }
+}
+
+/// The rules used to split privacy dictionary strings into words. These must match the rules
+/// used by the version of the SDK that the instrumented code will run with.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WordTokenization {
+    /// A regular expression that matches a single word. Uses Rust regex syntax, which is
+    /// compatible with JavaScript's `u` flag for simple patterns like the default.
+    #[serde(default = "default_word_pattern")]
+    pub pattern: String,
+    /// Words longer than this many UTF-16 code units are discarded.
+    #[serde(default = "default_max_word_length")]
+    pub max_word_length: usize,
+    /// True: convert words to lowercase.
+    #[serde(default = "default_lowercase_words")]
+    pub lowercase: bool,
+}
+
+fn default_word_pattern() -> String {
+    r"\p{Letter}+|\p{Symbol}+".into()
+}
+
+fn default_max_word_length() -> usize {
+    20
+}
+
+fn default_lowercase_words() -> bool {
+    true
+}
+
+impl Default for WordTokenization {
+    fn default() -> Self {
+        WordTokenization {
+            pattern: default_word_pattern(),
+            max_word_length: default_max_word_length(),
+            lowercase: default_lowercase_words(),
+        }
+    }
 }
 
 #[derive(Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PrivacyOptions {
     pub add_to_dictionary_helper: HelperFunctionSource,
+
+    /// Rules that decide whether strings are added to the privacy dictionary. The rules are
+    /// checked in order, and the first matching rule wins. If no rule matches, the preset
+    /// decides.
+    #[serde(default)]
+    pub string_filters: Vec<StringFilterRule>,
+
+    /// The built-in rules to apply to strings that don't match any rule in `string_filters`.
+    #[serde(default)]
+    pub string_filter_preset: StringFilterPreset,
+
+    /// JSX elements whose contents should never be collected, in addition to the built-in
+    /// ones (e.g. `path`). Patterns may contain `*` wildcards, e.g. `Icon*`.
+    #[serde(default)]
+    pub uncollected_jsx_elements: Vec<String>,
+
+    /// JSX attributes whose values should never be collected, in addition to the built-in
+    /// ones (e.g. `className`). Patterns may contain `*` wildcards, e.g. `aria-*`.
+    #[serde(default)]
+    pub uncollected_jsx_attributes: Vec<String>,
+
+    /// Calls whose arguments should never be collected, in addition to the built-in ones
+    /// (e.g. `eval()` or `new RegExp()`). The first matching entry wins.
+    #[serde(default)]
+    pub uncollected_calls: Vec<UncollectedCall>,
+
+    /// The built-in element factories to recognize.
+    #[serde(default = "default_element_factory_presets")]
+    pub element_factory_presets: Vec<ElementFactoryPreset>,
+
+    /// Element factories to recognize in addition to the presets. If a call matches several
+    /// factories, these take precedence.
+    #[serde(default)]
+    pub element_factories: Vec<ElementFactory>,
+    /// Some: split the dictionary's strings into words at build time, and pass the words to
+    /// the add-to-dictionary helper as a second argument, so the helper doesn't need to do it
+    /// at runtime. Strings that no dictionary reference uses are then left out of the
+    /// dictionary, since their words are all the helper needs.
+    /// None: pass the strings to the helper, which splits them into words at runtime.
+    #[serde(default)]
+    pub build_time_tokenization: Option<WordTokenization>,
+    /// Limits on the size of each file's privacy dictionary.
+    #[serde(default)]
+    pub dictionary_budget: DictionaryBudget,
+    /// If true, register the dictionary with one call to the add-to-dictionary helper per
+    /// context category (e.g. JSX text or error messages), passing the category as a third
+    /// argument, so that the helper can apply a different policy to each category. Each
+    /// entry is registered under the category it first appears in. Since each category's
+    /// entries must be contiguous in the dictionary, some dictionary references may be
+    /// longer than they would otherwise be.
+    #[serde(default)]
+    pub register_per_category: bool,
+    /// How strings that look like secrets or personal information are kept out of the
+    /// dictionary.
+    #[serde(default)]
+    pub sensitive_string_detection: SensitiveStringDetection,
+}
+
+fn default_element_factory_presets() -> Vec<ElementFactoryPreset> {
+    vec![ElementFactoryPreset::React]
 }
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -61,10 +444,19 @@ impl Default for InstrumentationOptions {
                 module: None,
                 jsx: Some(true),
                 typescript: Some(true),
+                syntax_fallback: true,
+                strict_parsing: false,
+                external_source_maps: HashMap::new(),
+                source_map_resolver: None,
             },
             output: OutputOptions {
                 inline_source_map: false,
                 embed_code_in_source_map: true,
+                privacy_dictionary_report: false,
+                external_source_map_comment: ExternalSourceMapComment::Remove,
+                index_source_map: false,
+                insert_header_after_license_comments: false,
+                dictionary_declaration: DictionaryDeclaration::Const,
             },
             privacy: PrivacyOptions {
                 add_to_dictionary_helper: HelperFunctionSource::Import {
@@ -72,6 +464,17 @@ impl Default for InstrumentationOptions {
                     esm_module: "datadog:privacy-helpers.mjs".into(),
                     func: "$".into(),
                 },
+                string_filters: vec![],
+                string_filter_preset: StringFilterPreset::Default,
+                uncollected_jsx_elements: vec![],
+                uncollected_jsx_attributes: vec![],
+                uncollected_calls: vec![],
+                element_factory_presets: default_element_factory_presets(),
+                element_factories: vec![],
+                build_time_tokenization: None,
+                dictionary_budget: DictionaryBudget::default(),
+                register_per_category: false,
+                sensitive_string_detection: SensitiveStringDetection::default(),
             },
         }
     }
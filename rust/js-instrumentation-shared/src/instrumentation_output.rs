@@ -1,5 +1,9 @@
+use std::fmt::Display;
+
 use serde::{Deserialize, Serialize};
 
+use crate::{diagnostic::Diagnostic, module_kind::ModuleKindDetection};
+
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct InstrumentationOutput {
@@ -15,4 +19,107 @@ pub struct InstrumentationOutput {
     pub map: Option<String>,
     /// The number of items in the privacy dictionary generated for this file.
     pub privacy_dictionary_size: usize,
+    /// A report listing the entries in the privacy dictionary generated for this file. Only
+    /// generated if requested via `OutputOptions::privacy_dictionary_report`.
+    pub report: Option<PrivacyDictionaryReport>,
+    /// The module kind the instrumented code was generated for, and why it was chosen.
+    pub module_kind: ModuleKindDetection,
+    /// Problems that didn't prevent instrumentation, but that may have affected the output.
+    pub warnings: Vec<Diagnostic>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstrumentationBatchOutput {
+    /// The instrumented files, in the same order as the inputs.
+    pub outputs: Vec<InstrumentationOutput>,
+    /// The module containing the privacy dictionary shared by the instrumented files.
+    pub shared_dictionary: SharedDictionaryOutput,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedDictionaryOutput {
+    /// The filename or id of the shared dictionary module.
+    pub id: String,
+    /// The source code of the shared dictionary module.
+    pub code: String,
+    /// The number of items in the shared privacy dictionary.
+    pub privacy_dictionary_size: usize,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivacyDictionaryReport {
+    /// The entries in the privacy dictionary, in dictionary order.
+    pub entries: Vec<PrivacyDictionaryReportEntry>,
+    /// The entries that were dropped to keep the dictionary within its budget, in the order
+    /// they first appear in the file.
+    pub dropped: Vec<PrivacyDictionaryReportEntry>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrivacyDictionaryReportEntry {
+    /// The value that was added to the dictionary.
+    pub value: PrivacyDictionaryReportValue,
+    /// The number of times the value appears in the file.
+    pub count: usize,
+    /// The line (1-based) where the value first appears in the file.
+    pub line: usize,
+    /// The column (0-based) where the value first appears in the file.
+    pub column: usize,
+    /// The index of the value in the privacy dictionary. None if the value was left out of
+    /// the dictionary because nothing references it, and only its words were registered.
+    pub dictionary_entry: Option<usize>,
+    /// The syntactic contexts the value appears in, in the order they first appear in the
+    /// file. The first one is the category the value is registered under when the dictionary
+    /// is registered one category at a time.
+    pub categories: Vec<ContextCategory>,
+}
+
+/// The syntactic context that a privacy dictionary value was collected from.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum ContextCategory {
+    /// Text inside a JSX element, or a child passed to `React.createElement()`.
+    JsxText,
+    /// A JSX attribute value, or a prop passed to `React.createElement()`.
+    JsxAttribute,
+    /// An object property key.
+    PropertyKey,
+    /// A thrown value, or an argument to an error constructor like `new Error()`.
+    ErrorMessage,
+    /// An argument to a `console` method like `console.log()`.
+    ConsoleArgument,
+    /// A `case` label in a `switch` statement.
+    SwitchCase,
+    /// Any other context.
+    #[default]
+    Other,
+}
+
+impl Display for ContextCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContextCategory::JsxText => write!(f, "jsxText"),
+            ContextCategory::JsxAttribute => write!(f, "jsxAttribute"),
+            ContextCategory::PropertyKey => write!(f, "propertyKey"),
+            ContextCategory::ErrorMessage => write!(f, "errorMessage"),
+            ContextCategory::ConsoleArgument => write!(f, "consoleArgument"),
+            ContextCategory::SwitchCase => write!(f, "switchCase"),
+            ContextCategory::Other => write!(f, "other"),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PrivacyDictionaryReportValue {
+    /// A string, including its surrounding quotes.
+    String(String),
+    /// The quasis of a tagged template.
+    TaggedTemplate(Vec<String>),
+    /// A quasi from an untagged template.
+    TemplateQuasi(String),
 }
@@ -1,3 +1,6 @@
+pub mod diagnostic;
+pub use diagnostic::{Diagnostic, DiagnosticCode};
+
 pub mod filetype;
 pub use filetype::*;
 
@@ -8,19 +11,27 @@ pub mod log;
 pub use log::debug_log;
 
 pub mod module_kind;
-pub use module_kind::{module_kind_for, ModuleKind};
+pub use module_kind::{detect_module_kind, module_kind_for, ModuleKind, ModuleKindDetection};
 
 pub mod parser;
-pub use parser::build_parser;
+pub use parser::{
+    build_parser, expression_len, parse_embedded_program, parse_program, ParsedProgram,
+};
+
+pub mod source_map_resolver;
+pub use source_map_resolver::SourceMapResolver;
 
 pub mod syntax;
 pub use syntax::syntax_for;
 
+pub mod instrumentation_error;
+pub use instrumentation_error::InstrumentationError;
+
 pub mod instrumentation_input;
-pub use instrumentation_input::InstrumentationInput;
+pub use instrumentation_input::{InstrumentationBatchInput, InstrumentationInput};
 
 pub mod instrumentation_options;
 pub use instrumentation_options::InstrumentationOptions;
 
 pub mod instrumentation_output;
-pub use instrumentation_output::InstrumentationOutput;
+pub use instrumentation_output::{InstrumentationBatchOutput, InstrumentationOutput};
@@ -5,45 +5,261 @@ use crate::{
     instrumentation_options::InstrumentationOptions,
 };
 
-#[derive(Clone, Deserialize, PartialEq, Eq, Serialize)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ModuleKind {
     CJS,
     ESM,
+    /// A classic script (e.g. loaded with `<script>` or `importScripts()`), which can't use
+    /// `import` or `require()`, and whose top-level bindings are shared with every other
+    /// script. Never inferred; it must be requested with the `module` input option.
+    ///
+    /// The helper is kept out of the global scope, but the dictionary itself must be a
+    /// top-level binding, since wrapping the script would change the scope of its own
+    /// declarations. It's given a name unique to the file (e.g. `D_1e7p1md` for `app.js`):
+    /// a global lexical binding with `DictionaryDeclaration::Const`, or a property of the
+    /// global object with `DictionaryDeclaration::Function`.
+    Script,
 }
 
-pub struct ModuleKeywordUsage {
-    pub cjs: bool,
-    pub esm: bool,
+/// A piece of evidence about a file's module format, found in its source code.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ModuleSignal {
+    /// An `import` declaration (but not `import x = require()`).
+    ImportDeclaration,
+    /// An `export` declaration (but not `export =`).
+    ExportDeclaration,
+    /// A reference to `import.meta`.
+    ImportMeta,
+    /// An `await` outside of any function.
+    TopLevelAwait,
+    /// The parser found module syntax that isn't covered by a more specific signal.
+    ModuleSyntax,
+    /// A call to `require()`.
+    Require,
+    /// A reference to a free variable named `exports`.
+    Exports,
+    /// A reference to `module.exports`.
+    ModuleExports,
+    /// A TypeScript `import x = require()` declaration.
+    TsImportEquals,
+    /// A TypeScript `export =` assignment.
+    TsExportAssignment,
+    /// A call to the AMD `define()` function with a dependency list or module factory.
+    AmdDefine,
+    /// A UMD wrapper, which checks for `define` before choosing how to export the module.
+    UmdWrapper,
+}
+
+impl ModuleSignal {
+    pub fn module_kind(self: &Self) -> ModuleKind {
+        match self {
+            ModuleSignal::ImportDeclaration
+            | ModuleSignal::ExportDeclaration
+            | ModuleSignal::ImportMeta
+            | ModuleSignal::TopLevelAwait
+            | ModuleSignal::ModuleSyntax => ModuleKind::ESM,
+            // AMD and UMD modules are normally consumed by bundlers (or loaders) that also
+            // understand `require()`, so they're treated as CommonJS.
+            ModuleSignal::Require
+            | ModuleSignal::Exports
+            | ModuleSignal::ModuleExports
+            | ModuleSignal::TsImportEquals
+            | ModuleSignal::TsExportAssignment
+            | ModuleSignal::AmdDefine
+            | ModuleSignal::UmdWrapper => ModuleKind::CJS,
+        }
+    }
+
+    /// True for TypeScript constructs that are only valid when the file is compiled to
+    /// CommonJS; these outweigh any ESM syntax in the same file.
+    fn requires_cjs(self: &Self) -> bool {
+        matches!(
+            self,
+            ModuleSignal::TsImportEquals | ModuleSignal::TsExportAssignment
+        )
+    }
+}
+
+/// The module format signals found in a file, in the order they were first seen.
+#[derive(Default)]
+pub struct ModuleSignals {
+    signals: Vec<ModuleSignal>,
+}
+
+impl ModuleSignals {
+    pub fn add(self: &mut Self, signal: ModuleSignal) {
+        if !self.signals.contains(&signal) {
+            self.signals.push(signal);
+        }
+    }
+
+    pub fn contains(self: &Self, signal: ModuleSignal) -> bool {
+        self.signals.contains(&signal)
+    }
+
+    pub fn contains_kind(self: &Self, kind: ModuleKind) -> bool {
+        self.signals
+            .iter()
+            .any(|signal| signal.module_kind() == kind)
+    }
+
+    fn matching<F: Fn(&ModuleSignal) -> bool>(self: &Self, predicate: F) -> Vec<ModuleSignal> {
+        self.signals
+            .iter()
+            .filter(|signal| predicate(signal))
+            .copied()
+            .collect()
+    }
+}
+
+/// Why a file was treated as a particular kind of module.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ModuleKindReason {
+    /// The module kind was specified in the input options.
+    Configured,
+    /// The file extension (e.g. `.cjs` or `.mjs`) determined the module kind.
+    Filename,
+    /// Signals found in the source code determined the module kind.
+    Signals,
+    /// Nothing determined the module kind, so the default was used.
+    Default,
+}
+
+/// The module kind chosen for a file, and why it was chosen.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModuleKindDetection {
+    pub kind: ModuleKind,
+    pub reason: ModuleKindReason,
+    /// The signals that decided the module kind. Empty unless `reason` is `Signals`.
+    pub signals: Vec<ModuleSignal>,
+}
+
+impl ModuleKindDetection {
+    fn new(kind: ModuleKind, reason: ModuleKindReason) -> ModuleKindDetection {
+        ModuleKindDetection {
+            kind,
+            reason,
+            signals: vec![],
+        }
+    }
 }
 
 pub fn module_kind_for(
     filename: &str,
     options: &InstrumentationOptions,
-    keyword_usage: Option<ModuleKeywordUsage>,
+    signals: Option<&ModuleSignals>,
 ) -> ModuleKind {
-    match (&options.input.module, keyword_usage) {
-        // If the user specified a module kind, use it.
-        (Some(ref kind), _) => kind.clone(),
-
-        // Infer the module kind from the filename if possible.
-        _ if filename_is_explicitly_cjs(filename) => ModuleKind::CJS,
-        _ if filename_is_explicitly_esm(filename) => ModuleKind::ESM,
-
-        // If the file contained `import` or `export`, treat it as ESM.
-        (None, Some(ModuleKeywordUsage { esm: true, .. })) => ModuleKind::ESM,
-
-        // If it contained `require()` or `exports`, but no `import` or `export`, treat it as CJS.
-        (
-            None,
-            Some(ModuleKeywordUsage {
-                esm: false,
-                cjs: true,
-            }),
-        ) => ModuleKind::CJS,
-
-        // Otherwise, default to ESM. It seems that today, overall, this is the slightly
-        // more compatible default.
-        (None, _) => ModuleKind::ESM,
+    detect_module_kind(filename, options, signals).kind
+}
+
+pub fn detect_module_kind(
+    filename: &str,
+    options: &InstrumentationOptions,
+    signals: Option<&ModuleSignals>,
+) -> ModuleKindDetection {
+    // If the user specified a module kind, use it.
+    if let Some(ref kind) = options.input.module {
+        return ModuleKindDetection::new(kind.clone(), ModuleKindReason::Configured);
+    }
+
+    // Infer the module kind from the filename if possible.
+    if filename_is_explicitly_cjs(filename) {
+        return ModuleKindDetection::new(ModuleKind::CJS, ModuleKindReason::Filename);
+    }
+    if filename_is_explicitly_esm(filename) {
+        return ModuleKindDetection::new(ModuleKind::ESM, ModuleKindReason::Filename);
+    }
+
+    if let Some(signals) = signals {
+        let (kind, decisive) = if signals.signals.iter().any(ModuleSignal::requires_cjs) {
+            // TypeScript's `import x = require()` and `export =` force CommonJS output.
+            (
+                ModuleKind::CJS,
+                signals.matching(ModuleSignal::requires_cjs),
+            )
+        } else if signals.contains_kind(ModuleKind::ESM) {
+            // ESM syntax can't appear in CommonJS, but `require` and `exports` can appear in
+            // ESM (e.g. via `createRequire()`), so ESM signals win.
+            (
+                ModuleKind::ESM,
+                signals.matching(|signal| signal.module_kind() == ModuleKind::ESM),
+            )
+        } else {
+            (
+                ModuleKind::CJS,
+                signals.matching(|signal| signal.module_kind() == ModuleKind::CJS),
+            )
+        };
+        if !decisive.is_empty() {
+            return ModuleKindDetection {
+                kind,
+                reason: ModuleKindReason::Signals,
+                signals: decisive,
+            };
+        }
+    }
+
+    // Otherwise, default to ESM. It seems that today, overall, this is the slightly
+    // more compatible default.
+    ModuleKindDetection::new(ModuleKind::ESM, ModuleKindReason::Default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(filename: &str, signals: &[ModuleSignal]) -> ModuleKindDetection {
+        let mut module_signals = ModuleSignals::default();
+        for signal in signals {
+            module_signals.add(*signal);
+        }
+        detect_module_kind(filename, &Default::default(), Some(&module_signals))
+    }
+
+    #[test]
+    fn prefers_esm_syntax_over_require() {
+        let detection = detect(
+            "a.js",
+            &[
+                ModuleSignal::Require,
+                ModuleSignal::ImportMeta,
+                ModuleSignal::Require,
+            ],
+        );
+        assert_eq!(detection.kind, ModuleKind::ESM);
+        assert_eq!(detection.reason, ModuleKindReason::Signals);
+        assert_eq!(detection.signals, vec![ModuleSignal::ImportMeta]);
+    }
+
+    #[test]
+    fn prefers_typescript_cjs_syntax_over_esm_syntax() {
+        let detection = detect(
+            "a.ts",
+            &[
+                ModuleSignal::ImportDeclaration,
+                ModuleSignal::TsExportAssignment,
+            ],
+        );
+        assert_eq!(detection.kind, ModuleKind::CJS);
+        assert_eq!(detection.signals, vec![ModuleSignal::TsExportAssignment]);
+    }
+
+    #[test]
+    fn prefers_filename_over_signals() {
+        let detection = detect("a.cjs", &[ModuleSignal::ImportDeclaration]);
+        assert_eq!(detection.kind, ModuleKind::CJS);
+        assert_eq!(detection.reason, ModuleKindReason::Filename);
+        assert!(detection.signals.is_empty());
+    }
+
+    #[test]
+    fn defaults_to_esm() {
+        let detection = detect("a.js", &[]);
+        assert_eq!(detection.kind, ModuleKind::ESM);
+        assert_eq!(detection.reason, ModuleKindReason::Default);
     }
 }
@@ -1,28 +1,153 @@
-use swc_common::comments::SingleThreadedComments;
-use swc_ecma_ast::EsVersion::EsNext;
-use swc_ecma_parser::{lexer::Lexer, Parser, StringInput};
+use swc_common::{comments::SingleThreadedComments, BytePos, Spanned};
+use swc_ecma_ast::{EsVersion::EsNext, Program};
+use swc_ecma_parser::{error::Error as ParseError, lexer::Lexer, Parser, StringInput, Syntax};
 
-use crate::{input_file::InputFile, instrumentation_options::InstrumentationOptions, syntax_for};
+use crate::{
+    input_file::InputFile,
+    instrumentation_options::InstrumentationOptions,
+    syntax::{build_syntax, fallback_syntaxes_for},
+    syntax_for,
+};
+
+pub struct ParsedProgram {
+    pub program: Program,
+    pub comments: SingleThreadedComments,
+    /// Errors the parser was able to recover from.
+    pub recovered_errors: Vec<ParseError>,
+    /// True if the program could only be parsed with a fallback syntax configuration.
+    pub used_fallback_syntax: bool,
+}
 
 pub fn build_parser<'a>(
     file: &InputFile<'a>,
     comments: &'a SingleThreadedComments,
     options: &InstrumentationOptions,
 ) -> Parser<Lexer<'a>> {
-    let lexer = build_lexer(file, comments, options);
-    Parser::new_from(lexer)
+    build_parser_with_syntax(file, comments, syntax_for(file.name, options))
+}
+
+/// Parse the given file. If parsing fails and syntax fallback is enabled, retry with each
+/// fallback syntax configuration in turn. If every attempt fails, the error from the first
+/// attempt is returned, since the first attempt used the syntax we expected to succeed.
+pub fn parse_program(
+    file: &InputFile,
+    options: &InstrumentationOptions,
+) -> Result<ParsedProgram, ParseError> {
+    parse_embedded_program(file, file.code, options)
+}
+
+/// Parse the given file, reading its code from `code` rather than from the file itself. The
+/// code must have the same length as the file, so that positions in the program refer to the
+/// file; this lets front ends parse the scripts embedded in a component, with everything
+/// outside the scripts replaced by whitespace.
+pub fn parse_embedded_program(
+    file: &InputFile,
+    code: &str,
+    options: &InstrumentationOptions,
+) -> Result<ParsedProgram, ParseError> {
+    let first_error = match try_parse_program(file, code, syntax_for(file.name, options)) {
+        Ok(parsed) => return Ok(parsed),
+        Err(err) => err,
+    };
+
+    if options.input.syntax_fallback {
+        for syntax in fallback_syntaxes_for(file.name, options) {
+            if let Ok(mut parsed) = try_parse_program(file, code, syntax) {
+                parsed.used_fallback_syntax = true;
+                return Ok(parsed);
+            }
+        }
+    }
+
+    Err(first_error)
+}
+
+/// Returns the length of the expression at the start of `code`, or None if `code` doesn't
+/// start with an expression. Front ends use this to find the end of an expression embedded in
+/// markup, which matching braces can't do reliably if the expression contains JSX.
+pub fn expression_len(code: &str) -> Option<usize> {
+    // swc reserves position 0 for dummy spans.
+    let start = BytePos(1);
+    let lexer = Lexer::new(
+        build_syntax(true, true),
+        EsNext,
+        StringInput::new(code, start, start + BytePos(code.len() as u32)),
+        None,
+    );
+    let expr = Parser::new_from(lexer).parse_expr().ok()?;
+    Some((expr.span().hi - start).0 as usize)
+}
+
+fn try_parse_program(
+    file: &InputFile,
+    code: &str,
+    syntax: Syntax,
+) -> Result<ParsedProgram, ParseError> {
+    let comments: SingleThreadedComments = Default::default();
+    let (program, recovered_errors) = {
+        let mut parser = build_parser_with_code(file, code, &comments, syntax);
+        let program = parser.parse_program()?;
+        (program, parser.take_errors())
+    };
+
+    Ok(ParsedProgram {
+        program,
+        comments,
+        recovered_errors,
+        used_fallback_syntax: false,
+    })
 }
 
-fn build_lexer<'a>(
+fn build_parser_with_syntax<'a>(
     file: &InputFile<'a>,
     comments: &'a SingleThreadedComments,
-    options: &InstrumentationOptions,
-) -> Lexer<'a> {
-    let syntax = syntax_for(file.name, options);
-    Lexer::new(
+    syntax: Syntax,
+) -> Parser<Lexer<'a>> {
+    build_parser_with_code(file, file.code, comments, syntax)
+}
+
+fn build_parser_with_code<'a>(
+    file: &InputFile,
+    code: &'a str,
+    comments: &'a SingleThreadedComments,
+    syntax: Syntax,
+) -> Parser<Lexer<'a>> {
+    let lexer = Lexer::new(
         syntax,
         EsNext,
-        StringInput::new(file.code, file.start_pos, file.end_pos),
+        StringInput::new(code, file.start_pos, file.end_pos),
         Some(comments),
-    )
+    );
+    Parser::new_from(lexer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn falls_back_to_alternative_syntax() {
+        let input_file = InputFile::new("input.ts", "const a = <string>b;\n");
+        let parsed = parse_program(&input_file, &Default::default()).unwrap();
+        assert!(parsed.used_fallback_syntax);
+
+        let mut options = InstrumentationOptions::default();
+        options.input.syntax_fallback = false;
+        assert!(parse_program(&input_file, &options).is_err());
+    }
+
+    #[test]
+    fn collects_recovered_errors() {
+        let input_file = InputFile::new("input.js", "const a = 'unterminated;\n");
+        let parsed = parse_program(&input_file, &Default::default()).unwrap();
+        assert!(!parsed.used_fallback_syntax);
+        assert_eq!(parsed.recovered_errors.len(), 1);
+    }
+
+    #[test]
+    fn measures_expressions() {
+        assert_eq!(expression_len("a + b} rest"), Some(5));
+        assert_eq!(expression_len("ok && <p>Don't {panic}</p>}"), Some(26));
+        assert_eq!(expression_len("...props}"), None);
+    }
 }
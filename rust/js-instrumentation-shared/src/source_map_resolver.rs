@@ -0,0 +1,6 @@
+/// Loads the contents of external source maps referenced by `sourceMappingURL` comments.
+pub trait SourceMapResolver {
+    /// Returns the contents of the source map at `url`, as referenced by the file with the
+    /// given id, or None if the source map can't be loaded.
+    fn resolve(self: &Self, url: &str, id: &str) -> Option<String>;
+}
@@ -6,6 +6,24 @@ use crate::{
 };
 
 pub fn syntax_for(filename: &str, options: &InstrumentationOptions) -> Syntax {
+    let (typescript, jsx) = dialect_for(filename, options);
+    build_syntax(typescript, jsx)
+}
+
+/// Returns the syntax configurations to try, in order, if parsing with the configuration
+/// returned by syntax_for() fails. This helps when the dialect we picked was wrong; for
+/// example, `.js` files often contain JSX, and TypeScript type assertions like `<T>value`
+/// can't be parsed when JSX is enabled.
+pub fn fallback_syntaxes_for(filename: &str, options: &InstrumentationOptions) -> Vec<Syntax> {
+    let (typescript, jsx) = dialect_for(filename, options);
+    vec![
+        build_syntax(typescript, !jsx),
+        build_syntax(!typescript, jsx),
+        build_syntax(!typescript, !jsx),
+    ]
+}
+
+fn dialect_for(filename: &str, options: &InstrumentationOptions) -> (bool, bool) {
     let jsx = options
         .input
         .jsx
@@ -14,7 +32,10 @@ pub fn syntax_for(filename: &str, options: &InstrumentationOptions) -> Syntax {
         .input
         .typescript
         .unwrap_or_else(|| filename_is_typescript(filename));
+    (typescript, jsx)
+}
 
+pub(crate) fn build_syntax(typescript: bool, jsx: bool) -> Syntax {
     if typescript {
         Syntax::Typescript(TsSyntax {
             tsx: jsx,
@@ -0,0 +1,287 @@
+use js_instrumentation_rewrite::rewrite::Rewrite;
+use js_instrumentation_shared::instrumentation_options::DictionaryDeclaration;
+use js_instrumentation_shared::instrumentation_output::SharedDictionaryOutput;
+use js_instrumentation_shared::{
+    module_kind_for, InstrumentationBatchInput, InstrumentationBatchOutput, InstrumentationError,
+    InstrumentationOptions, ModuleKind,
+};
+use swc_common::BytePos;
+
+use crate::dictionary::{
+    canonical_string_literal, SharedDictionary, WordTokenizer, DEFAULT_DICTIONARY_IDENTIFIER,
+};
+use crate::instrumentation_transform::{
+    analyze_input, get_default_add_to_dictionary_helper, rewrite_input, DictionarySource,
+};
+use crate::rewrite::{build_dictionary_declaration, build_helper_declaration, TemplateParameters};
+
+/// Instrument several files at once. Rather than declaring its own privacy dictionary, each
+/// file imports a single dictionary shared by every file in the batch, so strings that appear
+/// in several files are only included in the output once.
+pub fn apply_batch_transform(
+    batch: &InstrumentationBatchInput,
+    options: &InstrumentationOptions,
+) -> Result<InstrumentationBatchOutput, InstrumentationError> {
+    let mut analyzed_inputs = batch
+        .inputs
+        .iter()
+        .map(|input| analyze_input(input, options))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Every file that refers to the shared dictionary module must be the same kind of module
+    // as it is; e.g. a CommonJS file can't require() an ES module.
+    let module_kind = module_kind_for(&batch.shared_dictionary_id, options, None);
+    if let Some(analyzed) = analyzed_inputs.iter().find(|analyzed| {
+        !analyzed.strings.is_empty() && analyzed.module_kind(options) != module_kind
+    }) {
+        return Err(InstrumentationError::options(
+            analyzed.id(),
+            format!(
+                "a {:?} file can't refer to the {:?} shared dictionary module '{}'; instrument files of each module kind in a separate batch",
+                analyzed.module_kind(options),
+                module_kind,
+                batch.shared_dictionary_id
+            ),
+        ));
+    }
+
+    let shared_dictionary_identifier = DEFAULT_DICTIONARY_IDENTIFIER.to_string();
+    let SharedDictionary { dictionary, files } = SharedDictionary::build(
+        &shared_dictionary_identifier,
+        analyzed_inputs
+            .iter_mut()
+            .map(|analyzed| {
+                let references = analyzed.entry_references();
+                (std::mem::take(&mut analyzed.strings), references)
+            })
+            .collect(),
+        options.privacy.build_time_tokenization.is_some(),
+        options.privacy.register_per_category,
+    );
+
+    let outputs = analyzed_inputs
+        .into_iter()
+        .zip(files)
+        .map(|(mut analyzed, file_dictionary)| {
+            let dictionary_identifier = analyzed.new_dictionary_identifier();
+            rewrite_input(
+                analyzed,
+                file_dictionary,
+                dictionary_identifier,
+                DictionarySource::Shared(&batch.shared_dictionary_id),
+                options,
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let word_tokenizer = WordTokenizer::new(&options.privacy)
+        .map_err(|err| InstrumentationError::options(&batch.shared_dictionary_id, err))?;
+
+    let privacy_dictionary_size = dictionary.entry_count();
+    let template_parameters = TemplateParameters::new(
+        dictionary,
+        shared_dictionary_identifier,
+        // The shared dictionary module has no imports that could form a cycle, so its
+        // dictionary is always initialized before any other module accesses it.
+        DictionaryDeclaration::Const,
+        &options.privacy.add_to_dictionary_helper,
+        get_default_add_to_dictionary_helper(options).to_string(),
+        module_kind,
+        word_tokenizer.as_ref(),
+    );
+
+    Ok(InstrumentationBatchOutput {
+        outputs,
+        shared_dictionary: SharedDictionaryOutput {
+            id: batch.shared_dictionary_id.clone(),
+            code: build_shared_dictionary_module(&template_parameters, &batch.shared_dictionary_id),
+            privacy_dictionary_size,
+        },
+    })
+}
+
+fn build_shared_dictionary_module(params: &TemplateParameters, id: &str) -> String {
+    let mut code = String::new();
+
+    if params.dictionary.strings.is_empty() {
+        // Export an empty dictionary, so that the module is still valid if it's imported.
+        code += &format!("const {}=[];", params.dictionary_identifier);
+    } else {
+        let declaration = build_helper_declaration(BytePos(0), params)
+            .into_iter()
+            .chain(build_dictionary_declaration(BytePos(0), params));
+        for rewrite in declaration {
+            if let Rewrite::Insert { content, .. } = rewrite {
+                code += &content.to_string();
+            }
+        }
+    }
+
+    match params.module_kind {
+        ModuleKind::CJS => format!(
+            "{}exports.{}={};",
+            code, DEFAULT_DICTIONARY_IDENTIFIER, params.dictionary_identifier
+        ),
+        ModuleKind::ESM => format!("{}export{{{}}};", code, params.dictionary_identifier),
+        // A script can't export anything, so store the dictionary in a global keyed by the
+        // module id instead, and wrap everything in a block so no other bindings leak.
+        ModuleKind::Script => format!(
+            "{{{}globalThis[{}]={};}}",
+            code,
+            canonical_string_literal(id),
+            params.dictionary_identifier
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use js_instrumentation_shared::instrumentation_options::HelperFunctionSource;
+    use js_instrumentation_shared::InstrumentationInput;
+
+    use crate::identifiers::script_dictionary_identifier;
+
+    use super::*;
+
+    fn input(id: &str, code: &str) -> InstrumentationInput {
+        InstrumentationInput {
+            id: id.into(),
+            code: code.into(),
+            map: None,
+        }
+    }
+
+    #[test]
+    fn shares_one_dictionary_between_files() {
+        let output = apply_batch_transform(
+            &InstrumentationBatchInput {
+                inputs: vec![
+                    input("a.mjs", "export const a = 'Hello there';\n"),
+                    input(
+                        "b.mjs",
+                        "const D = 1;\nexport const b = ['Hello there', 'Goodbye'];\n",
+                    ),
+                    input("c.cjs", "exports.c = 1;\n"),
+                ],
+                shared_dictionary_id: "datadog:privacy-dictionary.mjs".into(),
+            },
+            &Default::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            output.shared_dictionary.code,
+            "import{$}from'datadog:privacy-helpers.mjs';const D=$(['Hello there','Goodbye']);export{D};"
+        );
+        assert_eq!(output.shared_dictionary.privacy_dictionary_size, 2);
+
+        let codes: Vec<&str> = output.outputs.iter().map(|o| o.code.as_str()).collect();
+        assert_eq!(
+            codes,
+            vec![
+                "import{D}from'datadog:privacy-dictionary.mjs';export const a = D[0];\n",
+                "import{D as A}from'datadog:privacy-dictionary.mjs';const D = 1;\nexport const b = [A[0], A[1]];\n",
+                "exports.c = 1;\n",
+            ]
+        );
+        assert_eq!(output.outputs[1].privacy_dictionary_size, 2);
+    }
+
+    #[test]
+    fn rejects_files_of_another_module_kind() {
+        let err = apply_batch_transform(
+            &InstrumentationBatchInput {
+                inputs: vec![
+                    input("a.mjs", "export const a = 'Hello there';\n"),
+                    input("b.cjs", "exports.b = 'Hello there';\n"),
+                ],
+                shared_dictionary_id: "datadog:privacy-dictionary.mjs".into(),
+            },
+            &Default::default(),
+        )
+        .err()
+        .unwrap();
+
+        match err {
+            InstrumentationError::Options { id, .. } => assert_eq!(id, "b.cjs"),
+            other => panic!("Unexpected error: {}", other),
+        }
+    }
+
+    #[test]
+    fn looks_up_shared_dictionary_lazily_in_cjs_with_function_declaration() {
+        let mut options = InstrumentationOptions::default();
+        options.output.dictionary_declaration = DictionaryDeclaration::Function;
+        let output = apply_batch_transform(
+            &InstrumentationBatchInput {
+                inputs: vec![
+                    input("a.cjs", "const D = 1;\nexports.a = 'Hello there';\n"),
+                    input("b.cjs", "exports.b = 'Hello there';\n"),
+                ],
+                shared_dictionary_id: "datadog:privacy-dictionary.cjs".into(),
+            },
+            &options,
+        )
+        .unwrap();
+
+        assert_eq!(
+            output.shared_dictionary.code,
+            "const{$}=require('datadog:privacy-helpers.cjs');const D=$(['Hello there']);exports.D=D;"
+        );
+        let codes: Vec<&str> = output.outputs.iter().map(|o| o.code.as_str()).collect();
+        assert_eq!(
+            codes,
+            vec![
+                "function A(){return require('datadog:privacy-dictionary.cjs').D}const D = 1;\nexports.a = A()[0];\n",
+                "function D(){return require('datadog:privacy-dictionary.cjs').D}exports.b = D()[0];\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn escapes_the_shared_dictionary_id() {
+        let output = apply_batch_transform(
+            &InstrumentationBatchInput {
+                inputs: vec![input("a.mjs", "export const a = 'Hello there';\n")],
+                shared_dictionary_id: "./it's\\dictionary.mjs".into(),
+            },
+            &Default::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            output.outputs[0].code,
+            "import{D}from\"./it's\\\\dictionary.mjs\";export const a = D[0];\n"
+        );
+    }
+
+    #[test]
+    fn shares_dictionary_between_scripts_through_a_global() {
+        let mut options = InstrumentationOptions::default();
+        options.input.module = Some(ModuleKind::Script);
+        options.privacy.add_to_dictionary_helper = HelperFunctionSource::Global {
+            path: "DD_PRIVACY.$".into(),
+        };
+        let output = apply_batch_transform(
+            &InstrumentationBatchInput {
+                inputs: vec![input("a.js", "var a = 'Hello there';\n")],
+                shared_dictionary_id: "privacy-dictionary.js".into(),
+            },
+            &options,
+        )
+        .unwrap();
+
+        assert_eq!(
+            output.shared_dictionary.code,
+            "{const D=($=>$(['Hello there']))(globalThis.DD_PRIVACY.$);globalThis['privacy-dictionary.js']=D;}"
+        );
+        let dictionary = script_dictionary_identifier("a.js");
+        assert_eq!(
+            output.outputs[0].code,
+            format!(
+                "const {}=globalThis['privacy-dictionary.js'];var a = {}[0];\n",
+                dictionary, dictionary
+            )
+        );
+    }
+}
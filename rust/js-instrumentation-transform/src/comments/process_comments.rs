@@ -1,5 +1,5 @@
 use data_url::DataUrl;
-use js_instrumentation_shared::InputFile;
+use js_instrumentation_shared::{Diagnostic, DiagnosticCode, InputFile};
 use swc_common::{comments::SingleThreadedComments, BytePos, Span};
 
 use super::{DirectiveSet, SourceMapComment};
@@ -18,7 +18,8 @@ const SOURCE_MAPPING_URL_COMMENT_PREFIX: &'static str = "# sourceMappingURL=";
 pub fn process_comments(
     file: &InputFile,
     comments: &SingleThreadedComments,
-) -> (DirectiveSet, Option<SourceMapComment>) {
+) -> (DirectiveSet, Option<SourceMapComment>, Vec<Diagnostic>) {
+    let mut warnings: Vec<Diagnostic> = Vec::new();
     let mut source_map_comment: Option<SourceMapComment> = None;
     let mut privacy_allowlist_excluded_file = false;
     let mut privacy_allowlist_excluded_spans: Vec<Span> = Vec::new();
@@ -58,6 +59,7 @@ pub fn process_comments(
         &mut privacy_allowlist_excluded_spans,
         exclusion_begin_positions,
         exclusion_end_positions,
+        &mut warnings,
     );
 
     (
@@ -66,6 +68,7 @@ pub fn process_comments(
             privacy_allowlist_excluded_spans,
         },
         source_map_comment,
+        warnings,
     )
 }
 
@@ -79,7 +82,10 @@ fn parse_source_mapping_url_directive(
             let (body, _) = url.decode_to_vec().ok()?;
             Some(SourceMapComment::Inline(body, comment_span.clone()))
         }
-        Some(_) => Some(SourceMapComment::External()),
+        Some(url_str) => Some(SourceMapComment::External(
+            url_str.trim().to_string(),
+            comment_span.clone(),
+        )),
         _ => None,
     }
 }
@@ -130,6 +136,7 @@ fn add_spans_for_exclusion_begin_and_end_positions(
     privacy_allowlist_excluded_spans: &mut Vec<Span>,
     mut exclusion_begin_positions: Vec<BytePos>,
     mut exclusion_end_positions: Vec<BytePos>,
+    warnings: &mut Vec<Diagnostic>,
 ) {
     exclusion_begin_positions.sort_unstable();
     exclusion_end_positions.sort_unstable();
@@ -141,7 +148,18 @@ fn add_spans_for_exclusion_begin_and_end_positions(
         // If there are other begin directives between a begin directive and the next end
         // directive, ignore them.
         match &last_end_pos {
-            Some(last_end_pos) if begin_pos < *last_end_pos => continue,
+            Some(last_end_pos) if begin_pos < *last_end_pos => {
+                warnings.push(Diagnostic::at(
+                    DiagnosticCode::UnbalancedExcludeDirective,
+                    format!(
+                        "Ignoring {} directive nested inside another excluded region.",
+                        PRIVACY_ALLOWLIST_EXCLUDE_BEGIN_COMMENT
+                    ),
+                    file,
+                    begin_pos,
+                ));
+                continue;
+            }
             _ => {}
         }
 
@@ -150,7 +168,10 @@ fn add_spans_for_exclusion_begin_and_end_positions(
         loop {
             end_pos = exclusion_end_iter.next();
             match end_pos {
-                Some(end_pos) if end_pos <= begin_pos => continue,
+                Some(end_pos) if end_pos <= begin_pos => {
+                    push_unmatched_end_warning(file, end_pos, warnings);
+                    continue;
+                }
                 _ => break,
             }
         }
@@ -167,6 +188,15 @@ fn add_spans_for_exclusion_begin_and_end_positions(
                 });
             }
             None => {
+                warnings.push(Diagnostic::at(
+                    DiagnosticCode::UnbalancedExcludeDirective,
+                    format!(
+                        "{} directive has no matching {} directive; excluding until the end of the file.",
+                        PRIVACY_ALLOWLIST_EXCLUDE_BEGIN_COMMENT, PRIVACY_ALLOWLIST_EXCLUDE_END_COMMENT
+                    ),
+                    file,
+                    begin_pos,
+                ));
                 last_end_pos = Some(file.end_pos);
                 privacy_allowlist_excluded_spans.push(Span {
                     lo: begin_pos,
@@ -176,9 +206,26 @@ fn add_spans_for_exclusion_begin_and_end_positions(
         }
     }
 
+    // Any end directives we haven't consumed don't follow a begin directive.
+    for end_pos in exclusion_end_iter {
+        push_unmatched_end_warning(file, end_pos, warnings);
+    }
+
     privacy_allowlist_excluded_spans.sort_unstable();
 }
 
+fn push_unmatched_end_warning(file: &InputFile, end_pos: BytePos, warnings: &mut Vec<Diagnostic>) {
+    warnings.push(Diagnostic::at(
+        DiagnosticCode::UnbalancedExcludeDirective,
+        format!(
+            "Ignoring {} directive with no matching {} directive.",
+            PRIVACY_ALLOWLIST_EXCLUDE_END_COMMENT, PRIVACY_ALLOWLIST_EXCLUDE_BEGIN_COMMENT
+        ),
+        file,
+        end_pos,
+    ));
+}
+
 fn bounds_of_lines_intersecting_span(span: &Span, file: &InputFile) -> Option<Span> {
     let lo_span = bounds_of_line_containing_pos(span.lo, file);
     let hi_span = bounds_of_line_containing_pos(span.hi, file);
@@ -220,3 +267,45 @@ fn bounds_of_line_after_pos(pos: BytePos, file: &InputFile) -> Option<Span> {
         hi: line_bounds.1,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use js_instrumentation_shared::parse_program;
+
+    use super::*;
+
+    fn directive_warnings(code: &str) -> Vec<(usize, String)> {
+        let input_file = InputFile::new("input.js", code);
+        let parsed = parse_program(&input_file, &Default::default()).unwrap();
+        let (_, _, warnings) = process_comments(&input_file, &parsed.comments);
+        warnings
+            .into_iter()
+            .map(|warning| {
+                assert_eq!(warning.code, DiagnosticCode::UnbalancedExcludeDirective);
+                (warning.line.unwrap(), warning.message)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn accepts_balanced_exclude_directives() {
+        let warnings = directive_warnings(
+            "// datadog-privacy-allowlist-exclude-begin\nconst a = 'a';\n// datadog-privacy-allowlist-exclude-end\n",
+        );
+        assert_eq!(warnings, vec![]);
+    }
+
+    #[test]
+    fn reports_unbalanced_exclude_directives() {
+        let warnings = directive_warnings(
+            "// datadog-privacy-allowlist-exclude-end\n// datadog-privacy-allowlist-exclude-begin\n// datadog-privacy-allowlist-exclude-begin\nconst a = 'a';\n",
+        );
+        let lines: Vec<usize> = warnings.iter().map(|(line, _)| *line).collect();
+        assert_eq!(lines, vec![1, 2, 3]);
+        assert!(warnings[0]
+            .1
+            .starts_with("Ignoring datadog-privacy-allowlist-exclude-end"));
+        assert!(warnings[1].1.contains("until the end of the file"));
+        assert!(warnings[2].1.contains("nested"));
+    }
+}
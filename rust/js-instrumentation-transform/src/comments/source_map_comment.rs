@@ -2,5 +2,5 @@ use swc_common::Span;
 
 pub enum SourceMapComment {
     Inline(Vec<u8>, Span),
-    External(),
+    External(String, Span),
 }
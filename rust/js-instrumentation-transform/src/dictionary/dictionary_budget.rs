@@ -0,0 +1,271 @@
+use std::fmt::Display;
+
+use js_instrumentation_shared::instrumentation_options::DictionaryBudget;
+
+use super::{
+    dictionary_optimizer::entry_benefits, Dictionary, DictionaryEntryStats, EntryReference,
+};
+
+/// A limit in a `DictionaryBudget`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BudgetLimit {
+    Entries,
+    Bytes,
+    GrowthRatio,
+}
+
+impl Display for BudgetLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BudgetLimit::Entries => write!(f, "maxEntries"),
+            BudgetLimit::Bytes => write!(f, "maxBytes"),
+            BudgetLimit::GrowthRatio => write!(f, "maxGrowthRatio"),
+        }
+    }
+}
+
+/// The result of fitting a dictionary within its budget.
+pub struct BudgetedDictionary {
+    /// The entries that were kept, in their original order.
+    pub strings: Dictionary,
+    /// The entries that were dropped, in their original order.
+    pub dropped: Dictionary,
+    /// For each index in the original dictionary, the entry's index in `strings`, or None if
+    /// it was dropped.
+    pub index_map: Vec<Option<usize>>,
+    /// The limit that caused entries to be dropped, or that the mandatory entries exceeded on
+    /// their own, if any.
+    pub exceeded: Option<BudgetLimit>,
+}
+
+/// Drops the least valuable entries from the dictionary until it fits within the budget.
+/// Entries are ranked by `entry_benefits()`, and the most valuable entries that fit are kept;
+/// once an entry doesn't fit, it and every less valuable entry are dropped. Entries with
+/// mandatory references can't be dropped, but they still count against the budget.
+pub fn apply_dictionary_budget(
+    budget: &DictionaryBudget,
+    input_len: usize,
+    dictionary_reference_len: usize,
+    strings: Dictionary,
+    references: &[EntryReference],
+) -> BudgetedDictionary {
+    let benefits = entry_benefits(dictionary_reference_len, &strings, references);
+    let mut mandatory = vec![false; strings.len()];
+    for reference in references {
+        if !reference.only_if_smaller {
+            if let Some(mandatory) = mandatory.get_mut(reference.index) {
+                *mandatory = true;
+            }
+        }
+    }
+
+    let mut ranking: Vec<usize> = (0..strings.len()).collect();
+    ranking.sort_by_key(|index| {
+        (
+            !mandatory[*index],
+            std::cmp::Reverse(benefits[*index]),
+            *index,
+        )
+    });
+
+    let max_growth = budget
+        .max_growth_ratio
+        .map(|ratio| (ratio * input_len as f64).floor() as i64);
+    let mut keep = vec![false; strings.len()];
+    let mut entries = 0;
+    let mut bytes = 0;
+    let mut growth: i64 = 0;
+    let mut exceeded = None;
+    for index in ranking {
+        let Some((entry, _)) = strings.get_index(index) else {
+            continue;
+        };
+        // The entry itself, followed by a comma.
+        let entry_bytes = entry.declaration_len() + 1;
+        let entry_growth = if mandatory[index] {
+            entry_bytes as i64
+        } else {
+            -benefits[index]
+        };
+
+        if !mandatory[index] {
+            exceeded = if budget.max_entries.is_some_and(|max| entries + 1 > max) {
+                Some(BudgetLimit::Entries)
+            } else if budget
+                .max_bytes
+                .is_some_and(|max| bytes + entry_bytes > max)
+            {
+                Some(BudgetLimit::Bytes)
+            } else if max_growth.is_some_and(|max| growth + entry_growth > max) {
+                Some(BudgetLimit::GrowthRatio)
+            } else {
+                None
+            };
+            if exceeded.is_some() {
+                break;
+            }
+        }
+
+        keep[index] = true;
+        entries += 1;
+        bytes += entry_bytes;
+        growth += entry_growth;
+    }
+
+    // Entries with mandatory references are kept even if they don't fit, but the budget was
+    // still exceeded.
+    if exceeded.is_none() {
+        exceeded = if budget.max_entries.is_some_and(|max| entries > max) {
+            Some(BudgetLimit::Entries)
+        } else if budget.max_bytes.is_some_and(|max| bytes > max) {
+            Some(BudgetLimit::Bytes)
+        } else if max_growth.is_some_and(|max| growth > max) {
+            Some(BudgetLimit::GrowthRatio)
+        } else {
+            None
+        };
+    }
+
+    let mut kept = Dictionary::default();
+    let mut dropped = Dictionary::default();
+    let mut index_map = vec![None; strings.len()];
+    for (index, (entry, stats)) in strings.into_iter().enumerate() {
+        let target = if keep[index] { &mut kept } else { &mut dropped };
+        let new_index = target.len();
+        target.insert(
+            entry,
+            DictionaryEntryStats {
+                index: new_index,
+                ..stats
+            },
+        );
+        if keep[index] {
+            index_map[index] = Some(new_index);
+        }
+    }
+
+    BudgetedDictionary {
+        strings: kept,
+        dropped,
+        index_map,
+        exceeded,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_common::BytePos;
+
+    use crate::dictionary::DictionaryEntry;
+
+    use super::*;
+
+    fn dictionary_with_references(strings: &[(&str, usize)]) -> (Dictionary, Vec<EntryReference>) {
+        let mut dictionary = Dictionary::default();
+        let mut references = vec![];
+        for (index, (string, count)) in strings.iter().enumerate() {
+            references.extend((0..*count).map(|_| EntryReference {
+                index,
+                replaced_len: string.len(),
+                fixed_len: 2,
+                only_if_smaller: true,
+            }));
+            dictionary.insert(
+                DictionaryEntry::String((*string).into()),
+                DictionaryEntryStats {
+                    count: *count,
+                    dictionary_entry: None,
+                    first_pos: BytePos(1),
+                    index,
+                    categories: vec![],
+                },
+            );
+        }
+        (dictionary, references)
+    }
+
+    fn kept_strings(budgeted: &BudgetedDictionary) -> Vec<String> {
+        budgeted
+            .strings
+            .keys()
+            .map(|entry| match entry {
+                DictionaryEntry::String(string) => string.to_string(),
+                _ => String::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn keeps_everything_without_a_budget() {
+        let (strings, references) = dictionary_with_references(&[("'a'", 1), ("'bb'", 1)]);
+        let budgeted =
+            apply_dictionary_budget(&DictionaryBudget::default(), 100, 1, strings, &references);
+        assert_eq!(budgeted.strings.len(), 2);
+        assert!(budgeted.dropped.is_empty());
+        assert_eq!(budgeted.exceeded, None);
+    }
+
+    #[test]
+    fn keeps_the_most_valuable_entries() {
+        let (strings, references) = dictionary_with_references(&[
+            ("'used once'", 1),
+            ("'used very often'", 5),
+            ("'used twice'", 2),
+        ]);
+        let budget = DictionaryBudget {
+            max_entries: Some(2),
+            ..Default::default()
+        };
+        let budgeted = apply_dictionary_budget(&budget, 100, 1, strings, &references);
+
+        // The kept entries stay in their original order.
+        assert_eq!(
+            kept_strings(&budgeted),
+            vec!["'used very often'", "'used twice'"]
+        );
+        assert_eq!(budgeted.dropped.len(), 1);
+        assert_eq!(budgeted.index_map, vec![None, Some(0), Some(1)]);
+        assert_eq!(budgeted.exceeded, Some(BudgetLimit::Entries));
+    }
+
+    #[test]
+    fn limits_bytes_and_growth() {
+        let (strings, references) =
+            dictionary_with_references(&[("'aaaaaaaa'", 3), ("'bbbbbbbb'", 1)]);
+        let budget = DictionaryBudget {
+            max_bytes: Some(15),
+            ..Default::default()
+        };
+        let budgeted = apply_dictionary_budget(&budget, 100, 1, strings, &references);
+        assert_eq!(kept_strings(&budgeted), vec!["'aaaaaaaa'"]);
+        assert_eq!(budgeted.exceeded, Some(BudgetLimit::Bytes));
+
+        // The first entry saves a byte, but the second one costs more than it saves.
+        let (strings, references) =
+            dictionary_with_references(&[("'aaaaaaaa'", 2), ("'bbbbbbbb'", 1)]);
+        let budget = DictionaryBudget {
+            max_growth_ratio: Some(0.0),
+            ..Default::default()
+        };
+        let budgeted = apply_dictionary_budget(&budget, 100, 1, strings, &references);
+        assert_eq!(kept_strings(&budgeted), vec!["'aaaaaaaa'"]);
+        assert_eq!(budgeted.exceeded, Some(BudgetLimit::GrowthRatio));
+    }
+
+    #[test]
+    fn reports_mandatory_entries_that_exceed_the_budget() {
+        let (strings, mut references) =
+            dictionary_with_references(&[("'aaaaaaaa'", 1), ("'bbbbbbbb'", 1)]);
+        for reference in &mut references {
+            reference.only_if_smaller = false;
+        }
+        let budget = DictionaryBudget {
+            max_entries: Some(1),
+            ..Default::default()
+        };
+        let budgeted = apply_dictionary_budget(&budget, 100, 1, strings, &references);
+        assert_eq!(kept_strings(&budgeted), vec!["'aaaaaaaa'", "'bbbbbbbb'"]);
+        assert!(budgeted.dropped.is_empty());
+        assert_eq!(budgeted.exceeded, Some(BudgetLimit::Entries));
+    }
+}
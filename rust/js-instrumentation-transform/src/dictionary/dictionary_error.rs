@@ -1,7 +1,10 @@
 use std::fmt::Display;
 
+#[derive(Debug)]
 pub enum DictionaryError {
     InvalidIndex(usize),
+    /// The entry was left out of the dictionary, so it can't be referenced.
+    OmittedEntry(usize),
 }
 
 impl Display for DictionaryError {
@@ -10,6 +13,13 @@ impl Display for DictionaryError {
             DictionaryError::InvalidIndex(index) => {
                 write!(f, "Invalid dictionary index {}", index)
             }
+            DictionaryError::OmittedEntry(index) => {
+                write!(
+                    f,
+                    "Dictionary index {} was omitted from the dictionary",
+                    index
+                )
+            }
         }
     }
 }
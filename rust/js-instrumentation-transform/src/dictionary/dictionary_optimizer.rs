@@ -1,69 +1,129 @@
-use std::cmp::Ordering;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 
-use super::{dictionary_error::DictionaryError, Dictionary};
+use super::{dictionary_error::DictionaryError, Dictionary, EntryReference};
 
 pub struct OptimizedDictionary {
     pub indices: Vec<usize>,
     pub strings: Dictionary,
+    /// True if each context category's entries are contiguous in the dictionary; see
+    /// `group_by_category()`.
+    pub grouped_by_category: bool,
 }
 
 impl OptimizedDictionary {
-    pub fn build(dictionary_identifier: &String, mut strings: Dictionary) -> OptimizedDictionary {
-        let index = Self::optimize(dictionary_identifier, &mut strings);
+    /// Orders the dictionary to minimize the size of the output. If `omit_unreferenced` is
+    /// true, entries are left out of the dictionary entirely when that's smaller than
+    /// replacing their strings with dictionary references.
+    pub fn build(
+        dictionary_reference: &String,
+        mut strings: Dictionary,
+        references: &[EntryReference],
+        omit_unreferenced: bool,
+    ) -> OptimizedDictionary {
+        let index = Self::optimize(
+            dictionary_reference,
+            &mut strings,
+            references,
+            omit_unreferenced,
+        );
         OptimizedDictionary {
             indices: index,
             strings,
+            grouped_by_category: false,
         }
     }
 
+    /// Reorders the dictionary so that the entries in each context category are contiguous,
+    /// with the categories in a fixed order, so that each category can be registered with a
+    /// separate call to the add-to-dictionary helper. Within a category, entries keep their
+    /// relative order, but the assignment of entry numbers is no longer optimal.
+    pub fn group_by_category(self: &mut Self) {
+        let strings = &self.strings;
+        self.indices
+            .sort_by_key(|index| strings.get_index(*index).map(|(_, stats)| stats.category()));
+
+        for (dictionary_entry, index) in self.indices.iter().enumerate() {
+            if let Some((_, stats)) = self.strings.get_index_mut(*index) {
+                stats.dictionary_entry = Some(dictionary_entry);
+            }
+        }
+        self.grouped_by_category = true;
+    }
+
     pub fn entry_for_index(self: &Self, dictionary_index: usize) -> Result<usize, DictionaryError> {
         match self.strings.get_index(dictionary_index) {
-            Some((_, stats)) => Ok(stats.dictionary_entry),
+            Some((_, stats)) => stats
+                .dictionary_entry
+                .ok_or(DictionaryError::OmittedEntry(dictionary_index)),
             None => Err(DictionaryError::InvalidIndex(dictionary_index)),
         }
     }
 
-    fn optimize(dictionary_identifier: &String, strings: &mut Dictionary) -> Vec<usize> {
-        let mut index: Vec<usize> = (0..strings.len()).into_iter().collect();
-        index.sort_by(|a, b| {
-            match (strings.get_index(*a), strings.get_index(*b)) {
-                (Some((ref a_key, ref a_val)), Some((ref b_key, ref b_val))) => {
-                    // Earlier entries in the dictionary have lower, and thus shorter, indices,
-                    // which means that dictionary references to those entries will be shorter.
-                    // (e.g., D[1] is shorter than D[1000]). We never replace a string with a
-                    // dictionary reference unless the reference is shorter than the original
-                    // string, so the order of the dictionary entries affects which strings we'll
-                    // replace with dictionary references, and how many characters those dictionary
-                    // references require, and this in turn affects the overall size of the output.
-                    // This means we have an optimization problem to solve: for example, it's
-                    // better if strings that appear many times get shorter indices. The current
-                    // solution uses an ordering derived from the maximum possible benefit we could
-                    // see from substituting each dictionary entry, if it received the shortest
-                    // possible index. This is not optimal, but it should be better than a naive
-                    // ordering.
-                    let ordering = b_key
-                        .max_dict_ref_benefit(&dictionary_identifier, b_val.count)
-                        .cmp(&a_key.max_dict_ref_benefit(&dictionary_identifier, a_val.count));
-
-                    // Within each equivalence class of strings, order strings that appear earlier
-                    // before strings that appear later. We do this to ensure that the output is
-                    // stable.
-                    match ordering {
-                        Ordering::Equal => a_val.index.cmp(&b_val.index),
-                        other => other,
-                    }
-                }
-                // If one value is None (which is a bug, but we should still handle it),
-                // sort it at the end of the list.
-                (Some(_), None) => Ordering::Less,
-                (None, Some(_)) => Ordering::Greater,
-                (None, None) => Ordering::Equal,
+    /// Returns the number of entries in the dictionary, not counting those left out of it.
+    pub fn entry_count(self: &Self) -> usize {
+        self.strings
+            .values()
+            .filter(|stats| stats.dictionary_entry.is_some())
+            .count()
+    }
+
+    /// Returns the indices of the entries that were left out of the dictionary, in the order
+    /// they first appeared in the source code.
+    pub fn omitted_indices(self: &Self) -> impl Iterator<Item = usize> + '_ {
+        self.strings
+            .values()
+            .filter(|stats| stats.dictionary_entry.is_none())
+            .map(|stats| stats.index)
+    }
+
+    fn optimize(
+        dictionary_reference: &String,
+        strings: &mut Dictionary,
+        references: &[EntryReference],
+        omit_unreferenced: bool,
+    ) -> Vec<usize> {
+        // Earlier entries in the dictionary have lower, and thus shorter, entry numbers, which
+        // means that dictionary references to those entries will be shorter (e.g., D[1] is
+        // shorter than D[1000]). Since some references are only used when they're shorter than
+        // the code they replace, the order of the dictionary entries determines both which
+        // strings get replaced and how long the replacements are. Only the number of digits in
+        // an entry number matters, though, so rather than ordering the entries one by one, we
+        // assign each entry to a bucket of entry numbers with the same number of digits (0-9,
+        // 10-99, 100-999, ...), choosing the assignment that minimizes the total size of every
+        // reference in the output.
+        let mut capacities = bucket_capacities(strings.len());
+        let mut costs = reference_costs(dictionary_reference.len(), strings, references);
+
+        // Leaving an entry out of the dictionary is modeled as one more bucket, with unlimited
+        // capacity, in which every reference keeps the original source code.
+        let omitted_bucket = capacities.len();
+        if omit_unreferenced {
+            capacities.push(usize::MAX);
+            for (entry_costs, cost) in costs
+                .iter_mut()
+                .zip(omitted_costs(strings.len(), references))
+            {
+                entry_costs.push(cost);
             }
-        });
+        }
+
+        let buckets = assign_buckets(&costs, &capacities);
 
+        // Within a bucket, the order doesn't affect the output size. Order entries by their
+        // position in the dictionary, which is the order they first appeared in the source
+        // code, to keep the output stable and readable.
+        let mut index: Vec<usize> = (0..strings.len())
+            .filter(|index| buckets[*index] != omitted_bucket)
+            .collect();
+        index.sort_by_key(|index| (buckets[*index], *index));
+
+        for (_, val) in strings.iter_mut() {
+            val.dictionary_entry = None;
+        }
         for (dictionary_entry, index) in index.iter().enumerate() {
             match strings.get_index_mut(*index) {
-                Some((_, val)) => val.dictionary_entry = dictionary_entry,
+                Some((_, val)) => val.dictionary_entry = Some(dictionary_entry),
                 None => {}
             }
         }
@@ -71,3 +131,359 @@ impl OptimizedDictionary {
         index
     }
 }
+
+/// Returns, for each dictionary entry, the number of bytes that including it in the dictionary
+/// saves, assuming it gets a one-digit entry number. This is negative if the entry costs more
+/// bytes than its references save. Entries that must be in the dictionary, like tagged
+/// templates, have an effectively infinite benefit.
+pub fn entry_benefits(
+    dictionary_reference_len: usize,
+    strings: &Dictionary,
+    references: &[EntryReference],
+) -> Vec<i64> {
+    reference_costs(dictionary_reference_len, strings, references)
+        .iter()
+        .zip(omitted_costs(strings.len(), references))
+        .map(|(costs, omitted_cost)| omitted_cost - costs.first().copied().unwrap_or(0))
+        .collect()
+}
+
+/// The cost of an impossible assignment. It's large enough to never be chosen, but small
+/// enough that sums of costs can't overflow.
+const UNAVAILABLE: i64 = 1 << 48;
+
+/// Returns the number of entries a dictionary of the given size places in each bucket of
+/// entry numbers. Bucket `b` holds the entry numbers with `b + 1` digits.
+fn bucket_capacities(len: usize) -> Vec<usize> {
+    let mut capacities = vec![];
+    let mut remaining = len;
+    let mut capacity = 10;
+    while remaining > 0 {
+        let bucket_len = remaining.min(capacity);
+        capacities.push(bucket_len);
+        remaining -= bucket_len;
+        capacity = if capacities.len() == 1 {
+            90
+        } else {
+            capacity * 10
+        };
+    }
+    capacities
+}
+
+/// Returns, for each dictionary entry, the size of the entry and every reference to it in the
+/// output, if the entry were placed in each bucket.
+fn reference_costs(
+    dictionary_reference_len: usize,
+    strings: &Dictionary,
+    references: &[EntryReference],
+) -> Vec<Vec<i64>> {
+    let bucket_count = bucket_capacities(strings.len()).len();
+    let mut costs: Vec<Vec<i64>> = strings
+        .keys()
+        // The entry itself, followed by a comma.
+        .map(|entry| vec![entry.declaration_len() as i64 + 1; bucket_count])
+        .collect();
+    for reference in references {
+        let Some(entry_costs) = costs.get_mut(reference.index) else {
+            continue;
+        };
+        for (bucket, cost) in entry_costs.iter_mut().enumerate() {
+            *cost += reference.output_len(dictionary_reference_len, bucket + 1) as i64;
+        }
+    }
+    costs
+}
+
+/// Returns, for each dictionary entry, the size of the source code its references would
+/// replace if the entry were left out of the dictionary. That's only possible if every
+/// reference is optional.
+fn omitted_costs(len: usize, references: &[EntryReference]) -> Vec<i64> {
+    let mut costs = vec![0; len];
+    for reference in references {
+        let Some(cost) = costs.get_mut(reference.index) else {
+            continue;
+        };
+        *cost += if reference.only_if_smaller {
+            reference.replaced_len as i64
+        } else {
+            UNAVAILABLE
+        };
+    }
+    costs
+}
+
+/// Entries that could move from one bucket to another, ordered by the cost of the move.
+type MoveQueue = BinaryHeap<Reverse<(i64, usize)>>;
+
+/// Assigns each entry to a bucket, minimizing the total cost while respecting each bucket's
+/// capacity. This is a small transportation problem, which we solve exactly with successive
+/// shortest paths: entries are added one at a time, and each new entry either goes into a
+/// bucket with room to spare, or displaces a chain of entries from full buckets into other
+/// buckets, whichever is cheapest. Since there are only a handful of buckets, each step is
+/// cheap, and the result is deterministic.
+fn assign_buckets(costs: &[Vec<i64>], capacities: &[usize]) -> Vec<usize> {
+    let bucket_count = capacities.len();
+    let mut buckets = vec![0; costs.len()];
+    let mut sizes = vec![0; bucket_count];
+
+    // For each pair of buckets (from, to), a queue of the entries in `from`, ordered by how
+    // much it would cost to move them to `to`. Entries that have since moved elsewhere are
+    // discarded lazily when they reach the front of the queue.
+    let mut moves: Vec<Vec<MoveQueue>> = vec![vec![BinaryHeap::new(); bucket_count]; bucket_count];
+    let enqueue_moves = |moves: &mut Vec<Vec<MoveQueue>>, entry: usize, from: usize| {
+        for to in 0..bucket_count {
+            if to != from {
+                let delta = costs[entry][to] - costs[entry][from];
+                moves[from][to].push(Reverse((delta, entry)));
+            }
+        }
+    };
+
+    for entry in 0..costs.len() {
+        // Find the cheapest move from each bucket to each other bucket.
+        let mut cheapest_moves = vec![vec![None; bucket_count]; bucket_count];
+        for from in 0..bucket_count {
+            for to in 0..bucket_count {
+                let queue = &mut moves[from][to];
+                while let Some(Reverse((_, moved))) = queue.peek() {
+                    if buckets[*moved] == from {
+                        break;
+                    }
+                    queue.pop();
+                }
+                cheapest_moves[from][to] = queue.peek().map(|Reverse(m)| *m);
+            }
+        }
+
+        // Compute the cheapest way to make room for the new entry in each bucket, using the
+        // Bellman-Ford algorithm. The current assignment is optimal, so there are no negative
+        // cycles.
+        let mut distances: Vec<i64> = costs[entry].clone();
+        let mut predecessors: Vec<Option<(usize, usize)>> = vec![None; bucket_count];
+        for _ in 0..bucket_count {
+            let mut changed = false;
+            for from in 0..bucket_count {
+                for to in 0..bucket_count {
+                    if let Some((delta, moved)) = cheapest_moves[from][to] {
+                        if distances[from] + delta < distances[to] {
+                            distances[to] = distances[from] + delta;
+                            predecessors[to] = Some((from, moved));
+                            changed = true;
+                        }
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let Some(mut bucket) = (0..bucket_count)
+            .filter(|bucket| sizes[*bucket] < capacities[*bucket])
+            .min_by_key(|bucket| (distances[*bucket], *bucket))
+        else {
+            break;
+        };
+
+        // Walk the path backwards, moving each displaced entry into the bucket that was made
+        // for it, and finally placing the new entry at the start of the path.
+        sizes[bucket] += 1;
+        while let Some((from, moved)) = predecessors[bucket] {
+            buckets[moved] = bucket;
+            enqueue_moves(&mut moves, moved, bucket);
+            bucket = from;
+        }
+        buckets[entry] = bucket;
+        enqueue_moves(&mut moves, entry, bucket);
+    }
+
+    buckets
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_common::BytePos;
+
+    use crate::dictionary::{DictionaryEntry, DictionaryEntryStats};
+
+    use super::*;
+
+    fn dictionary_of(strings: &[String]) -> Dictionary {
+        let mut dictionary = Dictionary::default();
+        for (index, string) in strings.iter().enumerate() {
+            dictionary.insert(
+                DictionaryEntry::String(string.as_str().into()),
+                DictionaryEntryStats {
+                    count: 1,
+                    dictionary_entry: None,
+                    first_pos: BytePos(1),
+                    index,
+                    categories: vec![],
+                },
+            );
+        }
+        dictionary
+    }
+
+    fn total_cost(costs: &[Vec<i64>], buckets: &[usize]) -> i64 {
+        buckets
+            .iter()
+            .enumerate()
+            .map(|(entry, bucket)| costs[entry][*bucket])
+            .sum()
+    }
+
+    #[test]
+    fn computes_bucket_capacities() {
+        assert_eq!(bucket_capacities(0), Vec::<usize>::new());
+        assert_eq!(bucket_capacities(10), vec![10]);
+        assert_eq!(bucket_capacities(11), vec![10, 1]);
+        assert_eq!(bucket_capacities(1001), vec![10, 90, 900, 1]);
+    }
+
+    #[test]
+    fn prefers_short_entry_numbers_for_frequently_used_short_strings() {
+        let mut strings = vec![];
+        let mut references = vec![];
+        for index in 0..11 {
+            // The first ten strings are long, but used only once. The last string is short,
+            // and is only worth replacing with a one-digit reference, but it's used often.
+            let (string, count) = if index < 10 {
+                (format!("'a long string, number {}'", index), 1)
+            } else {
+                ("'abc'".to_string(), 10)
+            };
+            for _ in 0..count {
+                references.push(EntryReference {
+                    index,
+                    replaced_len: string.len(),
+                    fixed_len: 2,
+                    only_if_smaller: true,
+                });
+            }
+            strings.push(string);
+        }
+
+        let dictionary = OptimizedDictionary::build(
+            &"D".to_string(),
+            dictionary_of(&strings),
+            &references,
+            false,
+        );
+
+        assert!(dictionary.entry_for_index(10).unwrap() < 10);
+    }
+
+    #[test]
+    fn omits_entries_only_when_requested() {
+        let strings = vec![
+            "'Hello'".to_string(),
+            "'ab'".to_string(),
+            "'cd'".to_string(),
+        ];
+        let mut references = vec![
+            // Not worth replacing.
+            EntryReference {
+                index: 1,
+                replaced_len: 4,
+                fixed_len: 2,
+                only_if_smaller: true,
+            },
+            // Not worth replacing, but the reference is mandatory.
+            EntryReference {
+                index: 2,
+                replaced_len: 0,
+                fixed_len: 3,
+                only_if_smaller: false,
+            },
+        ];
+        // A string that's used once is smaller in place than in the dictionary, but it's worth
+        // replacing if it's used several times.
+        references.extend((0..3).map(|_| EntryReference {
+            index: 0,
+            replaced_len: 7,
+            fixed_len: 2,
+            only_if_smaller: true,
+        }));
+
+        let dictionary = OptimizedDictionary::build(
+            &"D".to_string(),
+            dictionary_of(&strings),
+            &references,
+            true,
+        );
+        assert_eq!(dictionary.indices, vec![0, 2]);
+        assert_eq!(dictionary.entry_for_index(0).unwrap(), 0);
+        assert!(matches!(
+            dictionary.entry_for_index(1),
+            Err(DictionaryError::OmittedEntry(1))
+        ));
+        assert_eq!(dictionary.entry_for_index(2).unwrap(), 1);
+
+        let dictionary = OptimizedDictionary::build(
+            &"D".to_string(),
+            dictionary_of(&strings),
+            &references,
+            false,
+        );
+        assert_eq!(dictionary.indices, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn finds_optimal_assignment_across_several_buckets() {
+        // A pseudo-random mix of entries, compared against a greedy assignment that's known
+        // to be suboptimal in general.
+        let mut seed: u64 = 12345;
+        let mut next = || {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as usize
+        };
+        let mut references = vec![];
+        for index in 0..150 {
+            for _ in 0..(next() % 4) {
+                references.push(EntryReference {
+                    index,
+                    replaced_len: 3 + next() % 6,
+                    fixed_len: 2 + next() % 4,
+                    only_if_smaller: next() % 5 != 0,
+                });
+            }
+        }
+        let strings: Vec<String> = (0..150).map(|index| format!("'{}'", index)).collect();
+        let costs = reference_costs(1, &dictionary_of(&strings), &references);
+        let buckets = assign_buckets(&costs, &bucket_capacities(150));
+
+        let capacities = bucket_capacities(150);
+        for (bucket, capacity) in capacities.iter().enumerate() {
+            assert_eq!(buckets.iter().filter(|b| **b == bucket).count(), *capacity);
+        }
+
+        // No single swap of two entries between buckets can reduce the total cost.
+        let optimal = total_cost(&costs, &buckets);
+        for a in 0..150 {
+            for b in (a + 1)..150 {
+                let mut swapped = buckets.clone();
+                swapped.swap(a, b);
+                assert!(total_cost(&costs, &swapped) >= optimal);
+            }
+        }
+
+        // Greedily placing entries by their one-digit savings can't do better.
+        let mut greedy: Vec<usize> = (0..150).collect();
+        greedy.sort_by_key(|entry| (Reverse(costs[*entry][2] - costs[*entry][0]), *entry));
+        let mut greedy_buckets = vec![0; 150];
+        for (position, entry) in greedy.iter().enumerate() {
+            greedy_buckets[*entry] = if position < 10 {
+                0
+            } else if position < 100 {
+                1
+            } else {
+                2
+            };
+        }
+        assert!(total_cost(&costs, &greedy_buckets) >= optimal);
+    }
+}
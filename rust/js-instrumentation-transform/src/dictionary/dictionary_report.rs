@@ -0,0 +1,157 @@
+use js_instrumentation_shared::instrumentation_output::{
+    PrivacyDictionaryReport, PrivacyDictionaryReportEntry, PrivacyDictionaryReportValue,
+};
+use js_instrumentation_shared::InputFile;
+
+use super::{Dictionary, DictionaryEntry, DictionaryEntryStats, OptimizedDictionary};
+
+pub fn build_dictionary_report(
+    dictionary: &OptimizedDictionary,
+    dropped: &Dictionary,
+    input_file: &InputFile,
+) -> PrivacyDictionaryReport {
+    // List the entries in dictionary order, followed by any entries that were left out of the
+    // dictionary.
+    let entries = dictionary
+        .indices
+        .iter()
+        .copied()
+        .chain(dictionary.omitted_indices())
+        .filter_map(|index| dictionary.strings.get_index(index))
+        .map(|(entry, stats)| report_entry(entry, stats, input_file))
+        .collect();
+    let dropped = dropped
+        .iter()
+        .map(|(entry, stats)| report_entry(entry, stats, input_file))
+        .collect();
+
+    PrivacyDictionaryReport { entries, dropped }
+}
+
+fn report_entry(
+    entry: &DictionaryEntry,
+    stats: &DictionaryEntryStats,
+    input_file: &InputFile,
+) -> PrivacyDictionaryReportEntry {
+    let (line, column) = input_file.line_and_column(stats.first_pos);
+    PrivacyDictionaryReportEntry {
+        value: match entry {
+            DictionaryEntry::String(string) => {
+                PrivacyDictionaryReportValue::String(string.to_string())
+            }
+            DictionaryEntry::TaggedTemplate(quasis) => {
+                PrivacyDictionaryReportValue::TaggedTemplate(
+                    quasis.iter().map(|quasi| quasi.to_string()).collect(),
+                )
+            }
+            DictionaryEntry::TemplateQuasi(quasi) => {
+                PrivacyDictionaryReportValue::TemplateQuasi(quasi.to_string())
+            }
+        },
+        count: stats.count,
+        line,
+        column,
+        dictionary_entry: stats.dictionary_entry,
+        categories: stats.categories.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use js_instrumentation_shared::instrumentation_output::ContextCategory;
+    use js_instrumentation_shared::{InstrumentationInput, InstrumentationOptions};
+
+    use crate::apply_transform;
+
+    use super::*;
+
+    #[test]
+    fn reports_dictionary_entries_in_dictionary_order() {
+        let mut options = InstrumentationOptions::default();
+        options.output.privacy_dictionary_report = true;
+
+        let output = apply_transform(
+            &InstrumentationInput {
+                id: "input.js".into(),
+                code: "const a = 'hi';\nconst b = `Welcome, ${name}`;\nconst c = 'hi';\n".into(),
+                map: None,
+            },
+            &options,
+        )
+        .unwrap();
+
+        let entries: Vec<(String, usize, usize, usize, Option<usize>)> = output
+            .report
+            .unwrap()
+            .entries
+            .into_iter()
+            .map(|entry| {
+                let value = match entry.value {
+                    PrivacyDictionaryReportValue::String(string) => string,
+                    PrivacyDictionaryReportValue::TaggedTemplate(quasis) => quasis.join("${}"),
+                    PrivacyDictionaryReportValue::TemplateQuasi(quasi) => quasi,
+                };
+                (
+                    value,
+                    entry.count,
+                    entry.line,
+                    entry.column,
+                    entry.dictionary_entry,
+                )
+            })
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("'hi'".into(), 2, 1, 10, Some(0)),
+                ("'Welcome, '".into(), 1, 2, 11, Some(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_the_contexts_each_entry_appears_in() {
+        let mut options = InstrumentationOptions::default();
+        options.output.privacy_dictionary_report = true;
+
+        let output = apply_transform(
+            &InstrumentationInput {
+                id: "input.jsx".into(),
+                code: "console.log('Saved');\nconst a = <p title='Saved'>Saved</p>;\n".into(),
+                map: None,
+            },
+            &options,
+        )
+        .unwrap();
+
+        let entries: Vec<Vec<ContextCategory>> = output
+            .report
+            .unwrap()
+            .entries
+            .into_iter()
+            .map(|entry| entry.categories)
+            .collect();
+        assert_eq!(
+            entries,
+            vec![vec![
+                ContextCategory::ConsoleArgument,
+                ContextCategory::JsxAttribute,
+                ContextCategory::JsxText,
+            ]]
+        );
+    }
+
+    #[test]
+    fn omits_report_unless_requested() {
+        let output = apply_transform(
+            &InstrumentationInput {
+                id: "input.js".into(),
+                code: "const a = 'hi';".into(),
+                map: None,
+            },
+            &Default::default(),
+        )
+        .unwrap();
+        assert!(output.report.is_none());
+    }
+}
@@ -1,4 +1,6 @@
 use html_escape::decode_html_entities;
+use js_instrumentation_shared::instrumentation_options::SensitiveStringRule;
+use js_instrumentation_shared::instrumentation_output::ContextCategory;
 use js_instrumentation_shared::log::debug_log;
 use lazy_static::lazy_static;
 use ordermap::OrderMap;
@@ -8,62 +10,81 @@ use swc_common::{BytePos, Span};
 
 use crate::comments::DirectiveSet;
 
-pub type Dictionary = OrderMap<DictionaryEntry, DictionaryEntryStats>;
+use super::string_literal::{
+    canonical_string_literal, has_surrogate_escape, unescape_string_contents,
+};
+use super::{SensitiveStringDetector, StringFilter};
 
-const MAX_STRING_LENGTH: usize = 4096;
+pub type Dictionary = OrderMap<DictionaryEntry, DictionaryEntryStats>;
 
 lazy_static! {
     static ref JSX_INITIAL_WHITESPACE_REGEX: Regex = Regex::new(r"^\n\s+").unwrap();
     static ref JSX_INTERNAL_WHITESPACE_REGEX: Regex = Regex::new(r"\n\s+").unwrap();
     static ref JSX_TERMINAL_WHITESPACE_REGEX: Regex = Regex::new(r"\n\s+$").unwrap();
-
-    static ref JSX_DOUBLE_QUOTE_ATTR_ESCAPED_CHARACTERS_REGEX: Regex = Regex::new(r#"[\\"]"#).unwrap();
-    static ref JSX_SINGLE_QUOTE_ATTR_ESCAPED_CHARACTERS_REGEX: Regex = Regex::new(r#"[\\']"#).unwrap();
-    static ref JSX_TEXT_ESCAPED_CHARACTERS_REGEX: Regex = Regex::new(r#"[\\"]"#).unwrap();
-
-    /// Matches strings that look like URLs.
-    static ref URL_STRINGS_REGEX: Regex =
-        Regex::new(r"^(?:http:|https:|data:|url\(|\/\/)").unwrap();
-
-    /// Matches strings that look like file names.
-    static ref FILE_NAME_REGEX: Regex =
-        Regex::new(r"\.(png|jpe?g|gif|svg|webp|js|cjs|mjs|ts|cts|mts)$").unwrap();
-
-    /// Matches strings that consist only of numbers, or of numbers in [brackets], or of groups of
-    /// numbers separated by spaces or dashes or commas or periods.
-    static ref NUMERIC_STRINGS_REGEX: Regex = Regex::new(r"^(?:\[?[0-9]+\]?(?:\s|-|,|.)*)+$").unwrap();
-
-    /// Matches strings that look like programming code.
-    static ref CODE_STRINGS_REGEX: Regex = Regex::new(r#""use strict""#).unwrap();
 }
 
 #[derive(Debug)]
 pub struct DictionaryEntryStats {
     pub count: usize,
-    pub dictionary_entry: usize,
+    /// The entry's position in the dictionary, once it's been ordered. None if the entry was
+    /// left out of the dictionary.
+    pub dictionary_entry: Option<usize>,
     pub first_pos: BytePos,
     pub index: usize,
+    /// The contexts the entry was collected from, without duplicates, in the order they first
+    /// appeared.
+    pub categories: Vec<ContextCategory>,
+}
+
+impl DictionaryEntryStats {
+    /// Returns the category the entry is registered under: the context it first appeared in.
+    pub fn category(self: &Self) -> ContextCategory {
+        self.categories.first().copied().unwrap_or_default()
+    }
+
+    /// Records that the entry was collected from the given context.
+    pub fn add_category(self: &mut Self, category: ContextCategory) {
+        if !self.categories.contains(&category) {
+            self.categories.push(category);
+        }
+    }
 }
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum DictionaryEntry {
+    /// A string literal, normally in the canonical form produced by
+    /// `canonical_string_literal()`.
     String(Atom),
     TaggedTemplate(Vec<Atom>),
     TemplateQuasi(Atom),
 }
 
 pub struct DictionaryTracker {
+    category: ContextCategory,
     directive_set: DirectiveSet,
     in_uncollected_scopes: usize,
+    sensitive_string_detector: SensitiveStringDetector,
+    string_filter: StringFilter,
     pub strings: Dictionary,
+    /// The strings that were kept out of the dictionary because they look sensitive: the rule
+    /// each one matched, and where it appeared.
+    pub rejected_strings: Vec<(SensitiveStringRule, BytePos)>,
 }
 
 impl DictionaryTracker {
-    pub fn new(directive_set: DirectiveSet) -> DictionaryTracker {
+    pub fn new(
+        directive_set: DirectiveSet,
+        string_filter: StringFilter,
+        sensitive_string_detector: SensitiveStringDetector,
+    ) -> DictionaryTracker {
         DictionaryTracker {
+            category: ContextCategory::Other,
             directive_set,
             in_uncollected_scopes: 0,
+            sensitive_string_detector,
+            string_filter,
             strings: Dictionary::default(),
+            rejected_strings: Vec::new(),
         }
     }
 
@@ -79,54 +100,37 @@ impl DictionaryTracker {
         self.in_uncollected_scopes -= 1;
     }
 
+    /// Starts collecting strings from the given context. Returns the previous context, which
+    /// should be passed to `exit_category()`.
+    pub fn enter_category(self: &mut Self, category: ContextCategory) -> ContextCategory {
+        std::mem::replace(&mut self.category, category)
+    }
+
+    pub fn exit_category(self: &mut Self, previous: ContextCategory) {
+        self.category = previous;
+    }
+
     pub fn maybe_add_jsx_attribute(
         self: &mut Self,
         raw: &Option<Atom>,
         value: &Atom,
         span: &Span,
     ) -> Option<usize> {
-        if self.should_skip_string(value, span) {
+        if raw.is_none() {
             return None;
         }
 
-        let raw_value = match raw {
-            Some(raw) => raw,
-            None => {
-                return None;
-            }
-        };
-
-        // Escape any real newlines that may be present. JSX attribute values can contain real
-        // newlines, but ordinary, non-template JS strings cannot.
-        let string = value.replace("\n", "\\n");
-
-        // Decode any HTML entities that appear in the string. Note that it's important that we do
-        // this before escaping quotes, since decoding HTML entities can produce new double quotes!
-        let string = decode_html_entities(&string);
-
-        if raw_value.starts_with("'") {
-            let string =
-                JSX_SINGLE_QUOTE_ATTR_ESCAPED_CHARACTERS_REGEX.replace_all(&string, "\\$0");
-            let string = format!(r#"'{}'"#, string);
-            self.try_add_string(&Some(string.into()), span)
-        } else {
-            let string =
-                JSX_DOUBLE_QUOTE_ATTR_ESCAPED_CHARACTERS_REGEX.replace_all(&string, "\\$0");
-            let string = format!(r#""{}""#, string);
-            self.try_add_string(&Some(string.into()), span)
-        }
-    }
-
-    pub fn maybe_add_jsx_text(
-        self: &mut Self,
-        raw: &Atom,
-        value: &Atom,
-        span: &Span,
-    ) -> Option<usize> {
-        if self.should_skip_string(value, span) {
+        // Decode any HTML entities that appear in the string. The decoded string is what ends
+        // up in the dictionary, so that's what we check.
+        let string = decode_html_entities(value);
+        if self.should_skip_string(&string, span) {
             return None;
         }
 
+        Some(self.add_string_value(&string, span, ContextCategory::JsxAttribute))
+    }
+
+    pub fn maybe_add_jsx_text(self: &mut Self, raw: &Atom, span: &Span) -> Option<usize> {
         // Collapse whitespace after newlines, consistent with JSX rules.
         let string = JSX_INITIAL_WHITESPACE_REGEX.replace(raw, "");
         let string = JSX_TERMINAL_WHITESPACE_REGEX.replace(&string, "");
@@ -135,18 +139,40 @@ impl DictionaryTracker {
         // Remove any newlines that remain.
         let string = string.replace("\n", "");
 
-        // Decode any HTML entities that appear in the string. Note that it's important that we do
-        // this before escaping quotes, since decoding HTML entities can produce new double quotes!
+        // Decode any HTML entities that appear in the string. The decoded string is what ends
+        // up in the dictionary, so that's what we check.
         let string = decode_html_entities(&string);
+        if self.should_skip_string(&string, span) {
+            return None;
+        }
+
+        Some(self.add_string_value(&string, span, ContextCategory::JsxText))
+    }
 
-        // Escape special characters, including double quotes, so that we can safely wrap the
-        // string in double quotes.
-        let string = JSX_TEXT_ESCAPED_CHARACTERS_REGEX.replace_all(&string, "\\$0");
+    /// Adds text or an attribute value from a component's markup (e.g. a Vue template).
+    /// Whitespace is collapsed and HTML entities are decoded, as the markup's compiler would.
+    pub fn maybe_add_markup_string(
+        self: &mut Self,
+        raw: &str,
+        span: &Span,
+        category: ContextCategory,
+    ) -> Option<usize> {
+        let string = raw.split_whitespace().collect::<Vec<&str>>().join(" ");
+        let string = decode_html_entities(&string);
+        if self.should_skip_string(&string, span) {
+            return None;
+        }
 
-        // Wrap the string in double quotes.
-        let string = format!(r#""{}""#, string);
+        Some(self.add_string_value(&string, span, category))
+    }
 
-        Some(self.add_atom(DictionaryEntry::String(string.into()), span))
+    pub fn maybe_add_property_key(
+        self: &mut Self,
+        raw: &Option<Atom>,
+        value: &Atom,
+        span: &Span,
+    ) -> Option<usize> {
+        self.maybe_add_string_in(raw, value, span, ContextCategory::PropertyKey)
     }
 
     pub fn maybe_add_string(
@@ -154,49 +180,100 @@ impl DictionaryTracker {
         raw: &Option<Atom>,
         value: &Atom,
         span: &Span,
+    ) -> Option<usize> {
+        self.maybe_add_string_in(raw, value, span, self.category)
+    }
+
+    fn maybe_add_string_in(
+        self: &mut Self,
+        raw: &Option<Atom>,
+        value: &Atom,
+        span: &Span,
+        category: ContextCategory,
     ) -> Option<usize> {
         if self.should_skip_string(value, span) {
             return None;
         }
 
-        self.try_add_string(raw, span)
-    }
-
-    fn try_add_string(self: &mut Self, raw: &Option<Atom>, span: &Span) -> Option<usize> {
         match raw {
-            Some(raw_value) => {
-                return Some(self.add_atom(DictionaryEntry::String(raw_value.clone()), span));
-            }
-            None => {
-                return None;
+            // The parser can't represent every string value exactly; keep the original
+            // literal if its value may not be accurate.
+            Some(raw_value) if has_surrogate_escape(raw_value) => {
+                Some(self.add_atom(DictionaryEntry::String(raw_value.clone()), span, category))
             }
+            Some(_) => Some(self.add_string_value(value, span, category)),
+            None => None,
         }
     }
 
+    /// Add a string to the dictionary by its value, rather than the way it was written in the
+    /// source code, so that equivalent strings share a single entry.
+    fn add_string_value(
+        self: &mut Self,
+        value: &str,
+        span: &Span,
+        category: ContextCategory,
+    ) -> usize {
+        let literal = canonical_string_literal(value);
+        self.add_atom(DictionaryEntry::String(literal.into()), span, category)
+    }
+
     pub fn maybe_add_tagged_template(
         self: &mut Self,
         quasis: &Vec<Atom>,
         span: &Span,
     ) -> Option<usize> {
-        if self.should_skip_tagged_template(span) {
+        if self.should_skip_tagged_template(quasis, span) {
             return None;
         } else {
-            return Some(self.add_atom(DictionaryEntry::TaggedTemplate(quasis.clone()), span));
+            return Some(self.add_atom(
+                DictionaryEntry::TaggedTemplate(quasis.clone()),
+                span,
+                self.category,
+            ));
         }
     }
 
-    pub fn maybe_add_template_quasi(self: &mut Self, raw: &Atom, span: &Span) -> Option<usize> {
-        if self.should_skip_string(raw, span) {
-            None
-        } else {
-            Some(self.add_atom(DictionaryEntry::TemplateQuasi(raw.clone()), span))
+    pub fn maybe_add_template_quasi(
+        self: &mut Self,
+        raw: &Atom,
+        cooked: &Option<Atom>,
+        span: &Span,
+    ) -> Option<usize> {
+        match cooked {
+            // A quasi is just a string, so store it as one; that way, it can share an entry
+            // with identical string literals.
+            Some(cooked) if !has_surrogate_escape(raw) => {
+                if self.should_skip_string(cooked, span) {
+                    None
+                } else {
+                    Some(self.add_string_value(cooked, span, self.category))
+                }
+            }
+            _ => {
+                if self.should_skip_string(raw, span) {
+                    None
+                } else {
+                    Some(self.add_atom(
+                        DictionaryEntry::TemplateQuasi(raw.clone()),
+                        span,
+                        self.category,
+                    ))
+                }
+            }
         }
     }
 
-    fn add_atom(self: &mut Self, atom: DictionaryEntry, span: &Span) -> usize {
+    fn add_atom(
+        self: &mut Self,
+        atom: DictionaryEntry,
+        span: &Span,
+        category: ContextCategory,
+    ) -> usize {
         match self.strings.get_mut(&atom) {
             Some(stats) => {
                 stats.count += 1;
+                stats.add_category(category);
                 stats.index
             }
             None => {
@@ -205,9 +282,10 @@ impl DictionaryTracker {
                     atom,
                     DictionaryEntryStats {
                         count: 1,
-                        dictionary_entry: 0,
+                        dictionary_entry: None,
                         first_pos: span.lo,
                         index,
+                        categories: vec![category],
                     },
                 );
                 index
@@ -218,13 +296,10 @@ impl DictionaryTracker {
     // Ignore empty or otherwise unwanted strings. It's important to use 'value' for this check to
     // ensure that we ignore the surrounding quotes when calculating the length of the string;
     // don't pass 'raw' to this function.
-    fn should_skip_string(self: &Self, value: &str, span: &Span) -> bool {
+    fn should_skip_string(self: &mut Self, value: &str, span: &Span) -> bool {
         if self.in_uncollected_scopes > 0 {
             return true;
         }
-        if value.len() > MAX_STRING_LENGTH {
-            return true;
-        }
         if value.trim().len() == 0 {
             return true;
         }
@@ -234,22 +309,16 @@ impl DictionaryTracker {
         {
             return true;
         }
-        if URL_STRINGS_REGEX.is_match(value) {
-            return true;
-        }
-        if FILE_NAME_REGEX.is_match(value) {
+        if self.string_filter.excludes(value) {
             return true;
         }
-        if NUMERIC_STRINGS_REGEX.is_match(value) {
-            return true;
-        }
-        if CODE_STRINGS_REGEX.is_match(value) {
+        if self.is_sensitive(value, span) {
             return true;
         }
         return false;
     }
 
-    fn should_skip_tagged_template(self: &Self, span: &Span) -> bool {
+    fn should_skip_tagged_template(self: &mut Self, quasis: &[Atom], span: &Span) -> bool {
         if self.in_uncollected_scopes > 0 {
             return true;
         }
@@ -259,62 +328,54 @@ impl DictionaryTracker {
         {
             return true;
         }
+        if quasis.iter().any(|quasi| self.is_sensitive(quasi, span)) {
+            return true;
+        }
         return false;
     }
-}
 
-const ZERO_BENEFIT: usize = 0;
-const SINGLE_USE_TAGGED_TEMPLATE_BENEFIT: usize = 1;
-const MULTI_USE_TAGGED_TEMPLATE_BENEFIT: usize = 2;
-const NON_TAGGED_TEMPLATE_BASE_BENEFIT: usize = 3;
+    /// Returns true if the given string looks like a secret or personal information, and
+    /// records the rejection.
+    fn is_sensitive(self: &mut Self, value: &str, span: &Span) -> bool {
+        match self.sensitive_string_detector.detect(value) {
+            Some(rule) => {
+                self.rejected_strings.push((rule, span.lo));
+                true
+            }
+            None => false,
+        }
+    }
+}
 
 impl DictionaryEntry {
-    pub fn max_dict_ref_benefit(self: &Self, dictionary_identifier: &str, count: usize) -> usize {
+    /// Returns the strings that this entry adds to the dictionary at runtime.
+    pub fn values(self: &Self) -> Vec<String> {
         match self {
-            DictionaryEntry::String(string) => {
-                // Dictionary reference: D[<entry>]
-                let min_dict_ref_len = dictionary_identifier.len() + /*[*/ 1 + /*_*/ 1 + /*]*/ 1;
-
-                // Original: "<string>"
-                let original_len = string.len();
-
-                if min_dict_ref_len < original_len {
-                    NON_TAGGED_TEMPLATE_BASE_BENEFIT + (original_len - min_dict_ref_len) * count
-                } else {
-                    ZERO_BENEFIT
-                }
-            }
-            DictionaryEntry::TaggedTemplate(_) => {
-                // Dictionary reference: <tag>(D[<entry>],expr1,expr2)
-                // Original: <tag>`quasi1${expr1}quasi2${expr2}`
-                // Note that because quasis are totally excluded and expressions require
-                // only one separator character instead of three, it's actually quite
-                // hard to lose with a tagged template substitution. If we accurately
-                // calculated their benefit, they'd usually be bumped to the top of
-                // the dictionary, above everything else, but that could be counter-productive
-                // since it might push more marginal strings and quasis down the list.
-                // For now, we use a much simpler approach that greatly underestimates their
-                // benefit.
-                if count > 1 {
-                    MULTI_USE_TAGGED_TEMPLATE_BENEFIT
-                } else {
-                    SINGLE_USE_TAGGED_TEMPLATE_BENEFIT
-                }
+            DictionaryEntry::String(literal) => {
+                // Strip the quotes.
+                let contents = literal.get(1..literal.len().saturating_sub(1));
+                vec![unescape_string_contents(contents.unwrap_or_default())]
             }
-            DictionaryEntry::TemplateQuasi(quasi) => {
-                // Dictionary reference: ${D[<entry>]}
-                let min_dict_ref_len =
-                  /*${*/ 2 + dictionary_identifier.len() + /*[*/ 1 + /*_*/ 1 + /*]}*/ 2;
-
-                // Original: <string>
-                let original_len = quasi.len();
+            DictionaryEntry::TaggedTemplate(quasis) => quasis
+                .iter()
+                .map(|quasi| unescape_string_contents(quasi))
+                .collect(),
+            DictionaryEntry::TemplateQuasi(quasi) => vec![unescape_string_contents(quasi)],
+        }
+    }
 
-                if min_dict_ref_len < original_len {
-                    NON_TAGGED_TEMPLATE_BASE_BENEFIT + (original_len - min_dict_ref_len) * count
-                } else {
-                    ZERO_BENEFIT
-                }
+    /// Returns the length of the entry in the dictionary declaration. For tagged templates,
+    /// this doesn't include the tag.
+    pub fn declaration_len(self: &Self) -> usize {
+        match self {
+            DictionaryEntry::String(literal) => literal.len(),
+            // `quasi1${0}quasi2`
+            DictionaryEntry::TaggedTemplate(quasis) => {
+                let quasis_len: usize = quasis.iter().map(|quasi| quasi.len()).sum();
+                quasis_len + 4 * quasis.len().saturating_sub(1) + 2
             }
+            // `quasi`
+            DictionaryEntry::TemplateQuasi(quasi) => quasi.len() + 2,
         }
     }
 }
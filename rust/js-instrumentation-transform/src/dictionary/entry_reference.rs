@@ -0,0 +1,26 @@
+/// A place where a dictionary reference (e.g. `D[0]`) will replace some source code.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntryReference {
+    /// The index of the referenced entry in the `Dictionary`.
+    pub index: usize,
+    /// The length of the source code that the reference replaces.
+    pub replaced_len: usize,
+    /// The length of the reference, not counting the dictionary reference (e.g. `D`) or the
+    /// entry number. For example, this is 4 for `{D[0]}`: the braces and the square brackets.
+    pub fixed_len: usize,
+    /// True if the source code is only replaced if the reference is shorter.
+    pub only_if_smaller: bool,
+}
+
+impl EntryReference {
+    /// The number of characters in the output for this reference, given the length of the
+    /// dictionary reference and the number of digits in the entry number.
+    pub fn output_len(self: &Self, dictionary_reference_len: usize, digits: usize) -> usize {
+        let reference_len = self.fixed_len + dictionary_reference_len + digits;
+        if self.only_if_smaller {
+            reference_len.min(self.replaced_len)
+        } else {
+            reference_len
+        }
+    }
+}
@@ -1,11 +1,37 @@
+mod dictionary_budget;
+pub use dictionary_budget::{apply_dictionary_budget, BudgetedDictionary};
+
 mod dictionary_error;
 pub use dictionary_error::DictionaryError;
 
 mod dictionary_optimizer;
 pub use dictionary_optimizer::OptimizedDictionary;
 
+mod dictionary_report;
+pub use dictionary_report::build_dictionary_report;
+
 mod dictionary_tracker;
-pub use dictionary_tracker::{Dictionary, DictionaryEntry, DictionaryTracker};
+pub use dictionary_tracker::{
+    Dictionary, DictionaryEntry, DictionaryEntryStats, DictionaryTracker,
+};
+
+mod entry_reference;
+pub use entry_reference::EntryReference;
+
+mod sensitive_string_detector;
+pub use sensitive_string_detector::SensitiveStringDetector;
+
+mod shared_dictionary;
+pub use shared_dictionary::SharedDictionary;
+
+mod string_literal;
+pub use string_literal::{canonical_string_literal, escape_script_end_tags};
+
+mod string_filter;
+pub use string_filter::StringFilter;
+
+mod word_tokenizer;
+pub use word_tokenizer::WordTokenizer;
 
 pub const DEFAULT_ADD_TO_DICTIONARY_FUNCTION: &'static str = "$";
 pub const DEFAULT_DICTIONARY_IDENTIFIER: &'static str = "D";
@@ -0,0 +1,166 @@
+use super::{Dictionary, DictionaryEntryStats, EntryReference, OptimizedDictionary};
+
+/// A privacy dictionary shared by several files.
+pub struct SharedDictionary {
+    /// The merged dictionary, containing the entries from every file.
+    pub dictionary: OptimizedDictionary,
+    /// A view of the merged dictionary for each file, in the same order as the input
+    /// dictionaries. Each view contains only the file's own entries, but their dictionary
+    /// entry numbers refer to the merged dictionary.
+    pub files: Vec<OptimizedDictionary>,
+}
+
+impl SharedDictionary {
+    /// Merges the dictionaries of several files. Each file's dictionary is accompanied by the
+    /// references to its entries, which are used to order the merged dictionary. See
+    /// `OptimizedDictionary::build()` for `omit_unreferenced`, and
+    /// `OptimizedDictionary::group_by_category()` for `group_by_category`.
+    pub fn build(
+        dictionary_reference: &String,
+        dictionaries: Vec<(Dictionary, Vec<EntryReference>)>,
+        omit_unreferenced: bool,
+        group_by_category: bool,
+    ) -> SharedDictionary {
+        let mut merged = Dictionary::default();
+        let mut merged_references = vec![];
+        for (dictionary, references) in &dictionaries {
+            for (entry, stats) in dictionary {
+                match merged.get_mut(entry) {
+                    Some(merged_stats) => {
+                        merged_stats.count += stats.count;
+                        for category in &stats.categories {
+                            merged_stats.add_category(*category);
+                        }
+                    }
+                    None => {
+                        let index = merged.len();
+                        merged.insert(
+                            entry.clone(),
+                            DictionaryEntryStats {
+                                count: stats.count,
+                                dictionary_entry: None,
+                                first_pos: stats.first_pos,
+                                index,
+                                categories: stats.categories.clone(),
+                            },
+                        );
+                    }
+                }
+            }
+
+            // Renumber the references to refer to the merged dictionary's indices.
+            merged_references.extend(references.iter().filter_map(|reference| {
+                let (entry, _) = dictionary.get_index(reference.index)?;
+                Some(EntryReference {
+                    index: merged.get_index_of(entry)?,
+                    ..reference.clone()
+                })
+            }));
+        }
+
+        let mut dictionary = OptimizedDictionary::build(
+            dictionary_reference,
+            merged,
+            &merged_references,
+            omit_unreferenced,
+        );
+        if group_by_category {
+            dictionary.group_by_category();
+        }
+
+        let files = dictionaries
+            .into_iter()
+            .map(|(mut strings, _)| {
+                for (entry, stats) in strings.iter_mut() {
+                    if let Some((_, merged_stats)) = dictionary.strings.get_key_value(entry) {
+                        stats.dictionary_entry = merged_stats.dictionary_entry;
+                    }
+                }
+
+                let mut indices: Vec<usize> = (0..strings.len())
+                    .filter(|index| {
+                        strings
+                            .get_index(*index)
+                            .is_some_and(|(_, stats)| stats.dictionary_entry.is_some())
+                    })
+                    .collect();
+                indices.sort_by_key(|index| {
+                    strings
+                        .get_index(*index)
+                        .and_then(|(_, stats)| stats.dictionary_entry)
+                });
+
+                OptimizedDictionary {
+                    indices,
+                    strings,
+                    grouped_by_category: dictionary.grouped_by_category,
+                }
+            })
+            .collect();
+
+        SharedDictionary { dictionary, files }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swc_common::BytePos;
+
+    use crate::dictionary::DictionaryEntry;
+
+    use super::*;
+
+    fn dictionary_of(strings: &[(&str, usize)]) -> (Dictionary, Vec<EntryReference>) {
+        let mut dictionary = Dictionary::default();
+        let mut references = vec![];
+        for (index, (string, count)) in strings.iter().enumerate() {
+            references.extend((0..*count).map(|_| EntryReference {
+                index,
+                replaced_len: string.len(),
+                fixed_len: 2,
+                only_if_smaller: true,
+            }));
+            dictionary.insert(
+                DictionaryEntry::String((*string).into()),
+                DictionaryEntryStats {
+                    count: *count,
+                    dictionary_entry: None,
+                    first_pos: BytePos(1),
+                    index,
+                    categories: vec![],
+                },
+            );
+        }
+        (dictionary, references)
+    }
+
+    #[test]
+    fn merges_entries_across_files() {
+        let shared = SharedDictionary::build(
+            &"D".to_string(),
+            vec![
+                dictionary_of(&[("'only in first'", 1), ("'everywhere'", 1)]),
+                dictionary_of(&[("'everywhere'", 2)]),
+            ],
+            false,
+            false,
+        );
+
+        assert_eq!(shared.dictionary.strings.len(), 2);
+        let (_, everywhere) = shared
+            .dictionary
+            .strings
+            .get_key_value(&DictionaryEntry::String("'everywhere'".into()))
+            .unwrap();
+        assert_eq!(everywhere.count, 3);
+
+        // Each file's view refers to the merged dictionary's entry numbers.
+        let first_file_entries: Vec<usize> = shared.files[0]
+            .indices
+            .iter()
+            .map(|index| shared.files[0].entry_for_index(*index).unwrap())
+            .collect();
+        assert_eq!(first_file_entries, vec![0, 1]);
+        assert_eq!(shared.files[1].entry_for_index(0).unwrap(), 1);
+    }
+}
@@ -0,0 +1,252 @@
+use js_instrumentation_shared::instrumentation_options::{
+    PrivacyOptions, StringFilterAction, StringFilterPreset, StringFilterRule,
+};
+use lazy_static::lazy_static;
+use regex::Regex;
+
+const MAX_STRING_LENGTH: usize = 4096;
+
+lazy_static! {
+    /// Matches strings that look like URLs.
+    static ref URL_STRINGS_REGEX: Regex =
+        Regex::new(r"^(?:http:|https:|data:|url\(|\/\/)").unwrap();
+
+    /// Matches strings that look like file names.
+    static ref FILE_NAME_REGEX: Regex =
+        Regex::new(r"\.(png|jpe?g|gif|svg|webp|js|cjs|mjs|ts|cts|mts)$").unwrap();
+
+    /// Matches strings that consist only of numbers, or of numbers in [brackets], or of groups of
+    /// numbers separated by spaces or dashes or commas or periods.
+    static ref NUMERIC_STRINGS_REGEX: Regex = Regex::new(r"^(?:\[?[0-9]+\]?(?:\s|-|,|.)*)+$").unwrap();
+
+    /// Matches strings that look like programming code.
+    static ref CODE_STRINGS_REGEX: Regex = Regex::new(r#""use strict""#).unwrap();
+}
+
+struct CompiledStringFilterRule {
+    action: StringFilterAction,
+    regex: Option<Regex>,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    prefix: Option<String>,
+    suffix: Option<String>,
+}
+
+impl CompiledStringFilterRule {
+    fn compile(rule: &StringFilterRule) -> Result<CompiledStringFilterRule, regex::Error> {
+        let regex = match rule.regex {
+            Some(ref regex) => Some(Regex::new(regex)?),
+            None => None,
+        };
+
+        Ok(CompiledStringFilterRule {
+            action: rule.action.clone(),
+            regex,
+            min_length: rule.min_length,
+            max_length: rule.max_length,
+            prefix: rule.prefix.clone(),
+            suffix: rule.suffix.clone(),
+        })
+    }
+
+    fn matches(self: &Self, value: &str) -> bool {
+        if let Some(min_length) = self.min_length {
+            if value.len() < min_length {
+                return false;
+            }
+        }
+        if let Some(max_length) = self.max_length {
+            if value.len() > max_length {
+                return false;
+            }
+        }
+        if let Some(ref prefix) = self.prefix {
+            if !value.starts_with(prefix.as_str()) {
+                return false;
+            }
+        }
+        if let Some(ref suffix) = self.suffix {
+            if !value.ends_with(suffix.as_str()) {
+                return false;
+            }
+        }
+        if let Some(ref regex) = self.regex {
+            if !regex.is_match(value) {
+                return false;
+            }
+        }
+        return true;
+    }
+}
+
+/// Decides which strings are eligible for the privacy dictionary. Very long strings are never
+/// eligible. Otherwise, user-specified rules are compiled once per transform and checked in
+/// order; if none of them match, the preset's built-in rules decide.
+pub struct StringFilter {
+    rules: Vec<CompiledStringFilterRule>,
+    preset: StringFilterPreset,
+}
+
+impl StringFilter {
+    pub fn new(options: &PrivacyOptions) -> Result<StringFilter, regex::Error> {
+        let rules = options
+            .string_filters
+            .iter()
+            .map(CompiledStringFilterRule::compile)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(StringFilter {
+            rules,
+            preset: options.string_filter_preset.clone(),
+        })
+    }
+
+    /// Returns true if the given string should not be added to the dictionary. As with
+    /// DictionaryTracker::should_skip_string(), 'value' should not include surrounding quotes.
+    pub fn excludes(self: &Self, value: &str) -> bool {
+        if value.len() > MAX_STRING_LENGTH {
+            return true;
+        }
+
+        for rule in &self.rules {
+            if rule.matches(value) {
+                return match rule.action {
+                    StringFilterAction::Include => false,
+                    StringFilterAction::Exclude => true,
+                };
+            }
+        }
+
+        match self.preset {
+            StringFilterPreset::Default => Self::default_preset_excludes(value),
+            StringFilterPreset::None => false,
+        }
+    }
+
+    fn default_preset_excludes(value: &str) -> bool {
+        if URL_STRINGS_REGEX.is_match(value) {
+            return true;
+        }
+        if FILE_NAME_REGEX.is_match(value) {
+            return true;
+        }
+        if NUMERIC_STRINGS_REGEX.is_match(value) {
+            return true;
+        }
+        if CODE_STRINGS_REGEX.is_match(value) {
+            return true;
+        }
+        return false;
+    }
+}
+
+impl Default for StringFilter {
+    fn default() -> Self {
+        StringFilter {
+            rules: Vec::new(),
+            preset: StringFilterPreset::Default,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use js_instrumentation_shared::InstrumentationOptions;
+
+    use super::*;
+
+    fn rule(action: StringFilterAction) -> StringFilterRule {
+        StringFilterRule {
+            action,
+            regex: None,
+            min_length: None,
+            max_length: None,
+            prefix: None,
+            suffix: None,
+        }
+    }
+
+    fn build_filter(
+        string_filters: Vec<StringFilterRule>,
+        string_filter_preset: StringFilterPreset,
+    ) -> StringFilter {
+        let mut options = InstrumentationOptions::default().privacy;
+        options.string_filters = string_filters;
+        options.string_filter_preset = string_filter_preset;
+        StringFilter::new(&options).unwrap()
+    }
+
+    #[test]
+    fn applies_the_default_preset() {
+        let filter = StringFilter::default();
+        assert!(filter.excludes("https://example.com"));
+        assert!(filter.excludes("logo.png"));
+        assert!(filter.excludes("123-456"));
+        assert!(!filter.excludes("Hello world"));
+    }
+
+    #[test]
+    fn checks_user_rules_before_the_preset() {
+        let filter = build_filter(
+            vec![
+                StringFilterRule {
+                    prefix: Some("https://docs.".into()),
+                    ..rule(StringFilterAction::Include)
+                },
+                StringFilterRule {
+                    regex: Some("^ff_[a-z_]+$".into()),
+                    ..rule(StringFilterAction::Exclude)
+                },
+                StringFilterRule {
+                    min_length: Some(32),
+                    suffix: Some("==".into()),
+                    ..rule(StringFilterAction::Exclude)
+                },
+            ],
+            StringFilterPreset::Default,
+        );
+        assert!(!filter.excludes("https://docs.example.com"));
+        assert!(filter.excludes("https://example.com"));
+        assert!(filter.excludes("ff_new_checkout"));
+        assert!(filter.excludes("c29tZSBsb25nIGJhc2U2NCBlbmNvZGVkIHZhbHVl=="));
+        assert!(!filter.excludes("short=="));
+        assert!(!filter.excludes("Hello world"));
+    }
+
+    #[test]
+    fn can_disable_the_preset() {
+        let filter = build_filter(
+            vec![StringFilterRule {
+                max_length: Some(2),
+                ..rule(StringFilterAction::Exclude)
+            }],
+            StringFilterPreset::None,
+        );
+        assert!(!filter.excludes("https://example.com"));
+        assert!(!filter.excludes("logo.png"));
+        assert!(filter.excludes("ok"));
+    }
+
+    #[test]
+    fn always_excludes_very_long_strings() {
+        let filter = build_filter(
+            vec![StringFilterRule {
+                prefix: Some("Hello".into()),
+                ..rule(StringFilterAction::Include)
+            }],
+            StringFilterPreset::None,
+        );
+        assert!(!filter.excludes(&format!("Hello{}", "o".repeat(MAX_STRING_LENGTH - 5))));
+        assert!(filter.excludes(&format!("Hello{}", "o".repeat(MAX_STRING_LENGTH))));
+    }
+
+    #[test]
+    fn rejects_invalid_regexes() {
+        let mut options = InstrumentationOptions::default().privacy;
+        options.string_filters = vec![StringFilterRule {
+            regex: Some("(unclosed".into()),
+            ..rule(StringFilterAction::Exclude)
+        }];
+        assert!(StringFilter::new(&options).is_err());
+    }
+}
@@ -0,0 +1,190 @@
+use std::fmt::Write;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+lazy_static! {
+    static ref SURROGATE_ESCAPE_REGEX: Regex =
+        Regex::new(r"\\u(\{0*)?[dD][89a-fA-F][0-9a-fA-F]{2}").unwrap();
+}
+
+/// Returns a string literal for the given string value, written in a canonical form, so that
+/// equivalent literals (e.g. `'a'`, `"a"` and `'\x61'`) produce identical dictionary entries.
+/// The literal uses single quotes, unless the value contains more single quotes than double
+/// quotes, and escapes only the characters that need escaping.
+pub fn canonical_string_literal(value: &str) -> String {
+    let double_quotes = value.matches('"').count();
+    let single_quotes = value.matches('\'').count();
+    let quote = if single_quotes > double_quotes {
+        '"'
+    } else {
+        '\''
+    };
+
+    let mut literal = String::with_capacity(value.len() + 2);
+    literal.push(quote);
+    for c in value.chars() {
+        match c {
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            '\u{2028}' => literal.push_str("\\u2028"),
+            '\u{2029}' => literal.push_str("\\u2029"),
+            c if c == quote => {
+                literal.push('\\');
+                literal.push(c);
+            }
+            c if c.is_control() && (c as u32) < 0x80 => {
+                let _ = write!(&mut literal, "\\x{:02x}", c as u32);
+            }
+            c => literal.push(c),
+        }
+    }
+    literal.push(quote);
+    literal
+}
+
+/// Escapes every `</` in the given string or template literal as `<\/`, which has the same
+/// value, so that the literal can't end the `<script>` element that holds it (e.g. with
+/// `</script>`). Not for tagged templates, whose tag receives the raw text.
+pub fn escape_script_end_tags(literal: &str) -> String {
+    literal.replace("</", "<\\/")
+}
+
+/// Returns true if the raw source text of a string contains an escape sequence for a
+/// surrogate code unit. The parser can't represent lone surrogates in the string's value,
+/// so for these strings, the value can't be trusted, and the raw text must be used instead.
+pub fn has_surrogate_escape(raw: &str) -> bool {
+    SURROGATE_ESCAPE_REGEX.is_match(raw)
+}
+
+/// Returns the value of the contents of a string literal or template, given the raw source
+/// text between its delimiters, by interpreting its escape sequences. Escapes for lone
+/// surrogates, which can't appear in a Rust string, are replaced with the noncharacter
+/// U+FFFF; like a lone surrogate, it isn't part of any word.
+pub fn unescape_string_contents(raw: &str) -> String {
+    let mut value = String::with_capacity(raw.len());
+    let mut chars = raw.chars().peekable();
+    let mut pending_surrogate: Option<u32> = None;
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            flush_surrogate(&mut value, &mut pending_surrogate);
+            value.push(c);
+            continue;
+        }
+        let Some(escaped) = chars.next() else {
+            break;
+        };
+        let code = match escaped {
+            'x' => take_hex(&mut chars, 2),
+            'u' if chars.peek() == Some(&'{') => {
+                chars.next();
+                let code = take_hex(&mut chars, 6);
+                if chars.peek() == Some(&'}') {
+                    chars.next();
+                }
+                code
+            }
+            'u' => take_hex(&mut chars, 4),
+            _ => {
+                flush_surrogate(&mut value, &mut pending_surrogate);
+                match escaped {
+                    'n' => value.push('\n'),
+                    'r' => {
+                        // A line continuation may be written as a backslash followed by CRLF.
+                        if chars.peek() != Some(&'\n') {
+                            value.push('\r');
+                        } else {
+                            chars.next();
+                        }
+                    }
+                    't' => value.push('\t'),
+                    'b' => value.push('\u{8}'),
+                    'f' => value.push('\u{c}'),
+                    'v' => value.push('\u{b}'),
+                    '0' => value.push('\0'),
+                    // A backslash followed by a line terminator is a line continuation.
+                    '\n' | '\u{2028}' | '\u{2029}' => {}
+                    other => value.push(other),
+                }
+                continue;
+            }
+        };
+
+        let Some(code) = code else {
+            flush_surrogate(&mut value, &mut pending_surrogate);
+            continue;
+        };
+        match (pending_surrogate.take(), code) {
+            (Some(high), 0xDC00..=0xDFFF) => {
+                let combined = 0x10000 + ((high - 0xD800) << 10) + (code - 0xDC00);
+                value.push(char::from_u32(combined).unwrap_or('\u{FFFF}'));
+            }
+            (high, _) => {
+                if high.is_some() {
+                    value.push('\u{FFFF}');
+                }
+                match code {
+                    0xD800..=0xDBFF => pending_surrogate = Some(code),
+                    _ => value.push(char::from_u32(code).unwrap_or('\u{FFFF}')),
+                }
+            }
+        }
+    }
+    flush_surrogate(&mut value, &mut pending_surrogate);
+    value
+}
+
+fn flush_surrogate(value: &mut String, pending_surrogate: &mut Option<u32>) {
+    if pending_surrogate.take().is_some() {
+        value.push('\u{FFFF}');
+    }
+}
+
+fn take_hex(chars: &mut std::iter::Peekable<std::str::Chars>, max_digits: usize) -> Option<u32> {
+    let mut digits = String::new();
+    while digits.len() < max_digits {
+        match chars.peek() {
+            Some(c) if c.is_ascii_hexdigit() => {
+                digits.push(*c);
+                chars.next();
+            }
+            _ => break,
+        }
+    }
+    u32::from_str_radix(&digits, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_canonical_string_literals() {
+        assert_eq!(canonical_string_literal("Number Two"), "'Number Two'");
+        assert_eq!(canonical_string_literal(r#"say "hi""#), r#"'say "hi"'"#);
+        assert_eq!(canonical_string_literal("it's"), r#""it's""#);
+        assert_eq!(canonical_string_literal(r#"it's "x""#), r#"'it\'s "x"'"#);
+        assert_eq!(
+            canonical_string_literal("a\\b\n\t\u{0}\u{2028}é"),
+            r"'a\\b\n\t\x00\u2028é'"
+        );
+    }
+
+    #[test]
+    fn unescapes_string_contents() {
+        assert_eq!(unescape_string_contents(r"it\'s"), "it's");
+        assert_eq!(unescape_string_contents(r"a\x62\u0063\u{64}\n"), "abcd\n");
+        assert_eq!(unescape_string_contents(r"\uD83D\uDE00!"), "\u{1F600}!");
+        assert_eq!(unescape_string_contents(r"\uD83D!"), "\u{FFFF}!");
+        assert_eq!(unescape_string_contents("a\\\nb"), "ab");
+    }
+
+    #[test]
+    fn detects_surrogate_escapes() {
+        assert!(has_surrogate_escape(r"'\uD83D'"));
+        assert!(has_surrogate_escape(r"'\u{dc00}'"));
+        assert!(!has_surrogate_escape(r"'\u00e9'"));
+    }
+}
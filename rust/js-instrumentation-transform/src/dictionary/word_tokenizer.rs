@@ -0,0 +1,108 @@
+use js_instrumentation_shared::instrumentation_options::PrivacyOptions;
+use ordermap::OrderSet;
+use regex::Regex;
+
+use super::OptimizedDictionary;
+
+/// Splits the dictionary's strings into words at build time, using the same rules as the
+/// add-to-dictionary helper would use at runtime.
+pub struct WordTokenizer {
+    regex: Regex,
+    max_word_length: usize,
+    lowercase: bool,
+}
+
+impl WordTokenizer {
+    /// Returns None if build-time tokenization isn't enabled.
+    pub fn new(options: &PrivacyOptions) -> Result<Option<WordTokenizer>, regex::Error> {
+        let Some(ref tokenization) = options.build_time_tokenization else {
+            return Ok(None);
+        };
+        Ok(Some(WordTokenizer {
+            regex: Regex::new(&tokenization.pattern)?,
+            max_word_length: tokenization.max_word_length,
+            lowercase: tokenization.lowercase,
+        }))
+    }
+
+    /// Returns the words in every entry in the dictionary, including entries that were left
+    /// out of the dictionary itself, without duplicates.
+    pub fn dictionary_words(self: &Self, dictionary: &OptimizedDictionary) -> Vec<String> {
+        let indices = dictionary.indices.iter().copied();
+        self.entry_words(dictionary, indices.chain(dictionary.omitted_indices()))
+    }
+
+    /// Returns the words in the entries with the given indices, without duplicates.
+    pub fn entry_words(
+        self: &Self,
+        dictionary: &OptimizedDictionary,
+        indices: impl Iterator<Item = usize>,
+    ) -> Vec<String> {
+        let mut words = OrderSet::new();
+        for index in indices {
+            if let Some((entry, _)) = dictionary.strings.get_index(index) {
+                for value in entry.values() {
+                    self.add_words(&value, &mut words);
+                }
+            }
+        }
+        words.into_iter().collect()
+    }
+
+    fn add_words(self: &Self, value: &str, words: &mut OrderSet<String>) {
+        for word in self.regex.find_iter(value) {
+            let word = word.as_str();
+            // The helper measures words in UTF-16 code units, as JavaScript does.
+            if word.encode_utf16().count() > self.max_word_length {
+                continue;
+            }
+            if self.lowercase {
+                words.insert(word.to_lowercase());
+            } else {
+                words.insert(word.to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use js_instrumentation_shared::InstrumentationOptions;
+    use swc_common::BytePos;
+
+    use crate::dictionary::{Dictionary, DictionaryEntry, DictionaryEntryStats};
+
+    use super::*;
+
+    #[test]
+    fn splits_entries_into_words() {
+        let mut options = InstrumentationOptions::default().privacy;
+        options.build_time_tokenization = Some(Default::default());
+        let tokenizer = WordTokenizer::new(&options).unwrap().unwrap();
+
+        let mut strings = Dictionary::default();
+        let entries = [
+            DictionaryEntry::String("'Hello, World! 42 ++'".into()),
+            DictionaryEntry::TaggedTemplate(vec!["hello ".into(), " Ünïcödé\\u00e9".into()]),
+            DictionaryEntry::TemplateQuasi("averyveryverylongwordindeed".into()),
+        ];
+        for (index, entry) in entries.into_iter().enumerate() {
+            strings.insert(
+                entry,
+                DictionaryEntryStats {
+                    count: 1,
+                    dictionary_entry: None,
+                    first_pos: BytePos(1),
+                    index,
+                    categories: vec![],
+                },
+            );
+        }
+        let dictionary = OptimizedDictionary::build(&"D".to_string(), strings, &[], true);
+
+        assert_eq!(
+            tokenizer.dictionary_words(&dictionary),
+            vec!["hello", "world", "++", "ünïcödéé"]
+        );
+    }
+}
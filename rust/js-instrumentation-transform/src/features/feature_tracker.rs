@@ -1,24 +1,50 @@
-use js_instrumentation_shared::module_kind::ModuleKeywordUsage;
+use js_instrumentation_shared::module_kind::{ModuleKind, ModuleSignal, ModuleSignals};
+use swc_ecma_ast::Program;
 
 pub struct FeatureTracker {
-    pub module_keyword_usage: ModuleKeywordUsage,
+    pub module_signals: ModuleSignals,
+    function_depth: usize,
 }
 
 impl FeatureTracker {
     pub fn new() -> FeatureTracker {
         FeatureTracker {
-            module_keyword_usage: ModuleKeywordUsage {
-                cjs: false,
-                esm: false,
-            },
+            module_signals: ModuleSignals::default(),
+            function_depth: 0,
         }
     }
 
-    pub fn observed_cjs_exports_or_require(self: &mut Self) {
-        self.module_keyword_usage.cjs = true;
+    pub fn observed(self: &mut Self, signal: ModuleSignal) {
+        self.module_signals.add(signal);
     }
 
-    pub fn observed_esm_export_or_import(self: &mut Self) {
-        self.module_keyword_usage.esm = true;
+    pub fn enter_function(self: &mut Self) {
+        self.function_depth += 1;
+    }
+
+    pub fn exit_function(self: &mut Self) {
+        self.function_depth -= 1;
+    }
+
+    pub fn observed_await(self: &mut Self) {
+        if self.function_depth == 0 {
+            self.observed(ModuleSignal::TopLevelAwait);
+        }
+    }
+
+    /// Record the parser's verdict on whether the program is a module. The parser treats a
+    /// program as a module if it contains any module syntax, so if it did, but we didn't
+    /// recognize any specific signal, record a generic one.
+    pub fn observed_program(self: &mut Self, program: &Program) {
+        let recognized = self.module_signals.contains_kind(ModuleKind::ESM)
+            || self.module_signals.contains(ModuleSignal::TsImportEquals)
+            || self
+                .module_signals
+                .contains(ModuleSignal::TsExportAssignment);
+        if let Program::Module(_) = program {
+            if !recognized {
+                self.observed(ModuleSignal::ModuleSyntax);
+            }
+        }
     }
 }
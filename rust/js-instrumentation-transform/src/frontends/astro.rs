@@ -0,0 +1,179 @@
+use std::ops::Range;
+
+use super::braced_markup::{collect_braced_markup, BracedMarkupRules};
+use super::embedded_document::{
+    DocumentHeader, DocumentKind, EmbeddedDocument, EmbeddedScript, MarkupCollector,
+};
+use super::markup_scanner::{
+    frontmatter, has_attribute, AttributeValue, ExpressionSyntax, MarkupScanner, MarkupToken,
+};
+
+/// Splits an Astro component into its frontmatter script, the expressions in its markup, and
+/// its markup. The frontmatter and the expressions are TypeScript, and the expressions may
+/// contain JSX. Scripts and styles in the markup are bundled for the browser separately, so
+/// they're left alone.
+pub fn split_astro_component(code: &str) -> EmbeddedDocument {
+    let frontmatter = frontmatter(code);
+    let markup = frontmatter.as_ref().map_or(0, |(_, end)| *end)..code.len();
+
+    let mut scripts: Vec<EmbeddedScript> = frontmatter
+        .iter()
+        .map(|(content, _)| script(content.clone(), false))
+        .collect();
+    for token in MarkupScanner::new(code, markup.clone(), ExpressionSyntax::Braces) {
+        match token {
+            MarkupToken::Expression(content) => scripts.push(script(content, true)),
+            MarkupToken::StartTag { attributes, .. } => {
+                for attribute in attributes {
+                    if let Some(AttributeValue::Expression(content)) = attribute.value {
+                        scripts.push(script(content, true));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let header = match frontmatter {
+        // In empty frontmatter, the header goes right before the closing fence, so it needs a
+        // line of its own.
+        Some((content, _)) if content.is_empty() => DocumentHeader {
+            offset: content.start,
+            enclosing_block: Some((String::new(), "\n".into())),
+            export_declarations: false,
+        },
+        Some((content, _)) => DocumentHeader {
+            offset: content.start,
+            enclosing_block: None,
+            export_declarations: false,
+        },
+        None => DocumentHeader {
+            offset: 0,
+            enclosing_block: Some(("---\n".into(), "\n---\n".into())),
+            export_declarations: false,
+        },
+    };
+
+    EmbeddedDocument {
+        kind: DocumentKind::Astro,
+        scripts,
+        markup: vec![markup],
+        header,
+        unsupported: vec![],
+    }
+}
+
+fn script(content: Range<usize>, expression: bool) -> EmbeddedScript {
+    EmbeddedScript {
+        content,
+        typescript: true,
+        jsx: true,
+        expression,
+    }
+}
+
+/// Astro collects text and static attributes, except in scripts and styles (including their
+/// attributes) and elements whose contents it doesn't compile or replaces, and except for
+/// directives (e.g. `client:load` or `set:html`). Unlike Svelte, it doesn't interpolate
+/// expressions in quoted attribute values.
+const ASTRO_MARKUP_RULES: BracedMarkupRules = BracedMarkupRules {
+    is_uncollected_element: |code, name, attributes| {
+        matches!(name, "pre" | "script" | "style")
+            || ["is:raw", "set:html", "set:text"]
+                .iter()
+                .any(|directive| has_attribute(code, attributes, directive))
+    },
+    is_collected_attribute: |name| !name.contains(':') && name != "slot",
+    interpolates_quoted_attributes: false,
+};
+
+/// Collects the text and static attribute values in Astro markup.
+pub fn collect_astro_markup(collector: &mut MarkupCollector, range: Range<usize>) {
+    collect_braced_markup(collector, range, &ASTRO_MARKUP_RULES);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::frontends::tests::{original_position, position_of, transform};
+
+    use super::*;
+
+    #[test]
+    fn splits_components_into_frontmatter_and_expressions() {
+        let code = "---\nconst items: string[] = [];\n---\n<ul {...rest}>{items.map((item) => <li>Don't {item}</li>)}</ul>\n<script>console.log('client');</script>\n";
+        let document = split_astro_component(code);
+        assert_eq!(
+            document
+                .scripts
+                .iter()
+                .map(|script| &code[script.content.clone()])
+                .collect::<Vec<_>>(),
+            vec![
+                "const items: string[] = [];\n",
+                "{...rest}",
+                "{items.map((item) => <li>Don't {item}</li>)}"
+            ]
+        );
+        assert_eq!(document.header.offset, 4);
+    }
+
+    #[test]
+    fn collects_frontmatter_expressions_and_markup() {
+        assert_eq!(
+            transform(
+                "Page.astro",
+                "---\nconst title = 'Welcome aboard';\n---\n<h1 title=\"A friendly greeting\" class:list={['big']}>{title}</h1>\n{ok && <p>Nice to meet you</p>}\n<p set:html={html}>Replaced anyway</p>\n<p>Thanks for visiting</p>\n"
+            ),
+            "---\nimport{$}from'datadog:privacy-helpers.mjs';const D=$(['Welcome aboard','big','Nice to meet you','A friendly greeting','Thanks for visiting']);const title = D[0];\n---\n<h1 title={D[3]} class:list={[D[1]]}>{title}</h1>\n{ok && <p>{D[2]}</p>}\n<p set:html={html}>Replaced anyway</p>\n<p>{D[4]}</p>\n"
+        );
+    }
+
+    #[test]
+    fn leaves_script_and_style_attributes_alone() {
+        assert_eq!(
+            transform("Page.astro", "<p>Thanks for visiting</p>\n<script type=\"text/partytown\" data-note=\"Loaded later on\">run();</script>\n<style lang=\"scss\" data-note=\"Styles for the page\">p { color: red; }</style>\n"),
+            "---\nimport{$}from'datadog:privacy-helpers.mjs';const D=$(['Thanks for visiting']);\n---\n<p>{D[0]}</p>\n<script type=\"text/partytown\" data-note=\"Loaded later on\">run();</script>\n<style lang=\"scss\" data-note=\"Styles for the page\">p { color: red; }</style>\n"
+        );
+    }
+
+    #[test]
+    fn adds_header_to_empty_frontmatter() {
+        assert_eq!(
+            transform("Page.astro", "---\n---\n<p>Some text here</p>\n"),
+            "---\nimport{$}from'datadog:privacy-helpers.mjs';const D=$(['Some text here']);\n---\n<p>{D[0]}</p>\n"
+        );
+    }
+
+    #[test]
+    fn adds_frontmatter_when_needed() {
+        assert_eq!(transform("Page.astro", "<p>Thanks for visiting</p>\n"), "---\nimport{$}from'datadog:privacy-helpers.mjs';const D=$(['Thanks for visiting']);\n---\n<p>{D[0]}</p>\n");
+    }
+
+    #[test]
+    fn escapes_script_end_tags_in_the_header() {
+        assert_eq!(
+            transform(
+                "Page.astro",
+                "<p>Close it with &lt;/script&gt; please</p>\n"
+            ),
+            "---\nimport{$}from'datadog:privacy-helpers.mjs';const D=$(['Close it with <\\/script> please']);\n---\n<p>{D[0]}</p>\n"
+        );
+    }
+
+    #[test]
+    fn maps_markup_and_script_strings_back_to_their_sources() {
+        let code = "---\nconst title = 'Welcome aboard';\n---\n<h1 title=\"A friendly greeting\">{title}</h1>\n<p>Thanks for visiting</p>\n";
+        assert_eq!(
+            original_position("Page.astro", code, "{D[1]}"),
+            position_of(code, "\"A friendly greeting\"")
+        );
+        assert_eq!(
+            original_position("Page.astro", code, "{D[2]}"),
+            position_of(code, "Thanks for visiting")
+        );
+        assert_eq!(
+            original_position("Page.astro", code, "D[0];"),
+            position_of(code, "'Welcome aboard'")
+        );
+    }
+}
@@ -0,0 +1,73 @@
+use std::ops::Range;
+
+use js_instrumentation_shared::instrumentation_output::ContextCategory;
+
+use crate::rewrite::replace_jsx_string_with_dictionary_ref;
+
+use super::embedded_document::MarkupCollector;
+use super::markup_scanner::{AttributeValue, ExpressionSyntax, MarkupAttribute, MarkupScanner};
+use super::markup_strings::{collect_markup_strings, MarkupStringToken};
+
+/// What a front end for markup with braced expressions, like Svelte or Astro, collects.
+pub struct BracedMarkupRules {
+    /// Returns true if the contents of the element with the given name and attributes aren't
+    /// collected, e.g. because the framework preserves their whitespace.
+    pub is_uncollected_element: fn(code: &str, name: &str, attributes: &[MarkupAttribute]) -> bool,
+    /// Returns true if the static attribute with the given name is collected, rather than
+    /// being a directive (e.g. `on:click`) that the framework handles itself.
+    pub is_collected_attribute: fn(name: &str) -> bool,
+    /// True if quoted attribute values can interpolate expressions, e.g. `title="Hi {name}"`.
+    pub interpolates_quoted_attributes: bool,
+}
+
+/// Collects the text and static attribute values in markup with braced expressions. Both
+/// become expressions, e.g. `{D[0]}` and `title={D[1]}`.
+pub fn collect_braced_markup(
+    collector: &mut MarkupCollector,
+    range: Range<usize>,
+    rules: &BracedMarkupRules,
+) {
+    let code = collector.code;
+    let tokens = MarkupScanner::new(code, range, ExpressionSyntax::Braces).filter_map(|token| {
+        MarkupStringToken::from_markup(token, replace_jsx_string_with_dictionary_ref)
+    });
+    collect_markup_strings(
+        collector,
+        tokens,
+        |name, attributes| (rules.is_uncollected_element)(code, name, attributes),
+        |collector, attribute| collect_braced_attribute(collector, attribute, rules),
+    );
+}
+
+/// Collects the value of the given attribute, if it's a static string, replacing it and its
+/// quotes with an expression, e.g. `title={D[0]}`.
+pub fn collect_braced_attribute(
+    collector: &mut MarkupCollector,
+    attribute: &MarkupAttribute,
+    rules: &BracedMarkupRules,
+) {
+    let code = collector.code;
+    let name = &code[attribute.name.clone()];
+    let Some(AttributeValue::Quoted(ref value)) = attribute.value else {
+        return;
+    };
+    if name.is_empty()
+        || !(rules.is_collected_attribute)(name)
+        || collector.jsx_filter.is_uncollected_attr(name)
+        || (rules.interpolates_quoted_attributes && code[value.clone()].contains('{'))
+    {
+        return;
+    }
+
+    // Replace the value and its quotes, unless the closing quote is missing.
+    if !code[value.end..].starts_with(['"', '\'']) {
+        return;
+    }
+    let quoted = value.start - 1..value.end + 1;
+    collector.collect_string(
+        value,
+        &quoted,
+        ContextCategory::JsxAttribute,
+        replace_jsx_string_with_dictionary_ref,
+    );
+}
@@ -0,0 +1,188 @@
+use std::ops::Range;
+
+use js_instrumentation_rewrite::rewrite::Rewrite;
+use js_instrumentation_shared::instrumentation_output::ContextCategory;
+use js_instrumentation_shared::{InputFile, InstrumentationOptions};
+use swc_common::{BytePos, Span};
+
+use crate::dictionary::DictionaryTracker;
+use crate::header::HeaderPosition;
+use crate::rewrite::{PrivacyRewriteTemplate, RewriteTracker};
+use crate::visitor::JSXFilter;
+
+use super::astro::collect_astro_markup;
+use super::mdx::collect_mdx_prose;
+use super::svelte::collect_svelte_markup;
+use super::vue::collect_vue_template;
+
+/// The kinds of documents that embed scripts in markup.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DocumentKind {
+    Vue,
+    Svelte,
+    Astro,
+    Mdx,
+}
+
+/// A script embedded in a document, e.g. a `<script>` block in a Vue component.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EmbeddedScript {
+    /// The script's code, as offsets into the document.
+    pub content: Range<usize>,
+    pub typescript: bool,
+    pub jsx: bool,
+    /// True if the script is a braced expression in markup, e.g. `{name}`. It's parsed as an
+    /// array literal, which can hold anything that can appear between the braces, like a
+    /// spread or just a comment.
+    pub expression: bool,
+}
+
+/// Where the header goes in a document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DocumentHeader {
+    /// The offset at which to insert the header.
+    pub offset: usize,
+    /// Some: the opening and closing tags of a script block to wrap the header in, if the
+    /// document has no script that can hold it.
+    pub enclosing_block: Option<(String, String)>,
+    /// True if the header's declarations must be exported; see `HeaderPosition`.
+    pub export_declarations: bool,
+}
+
+/// A document that embeds scripts in markup, like a Vue single-file component. Its scripts
+/// are instrumented together as a single program, and the strings in its markup are collected
+/// into the same dictionary.
+pub struct EmbeddedDocument {
+    pub kind: DocumentKind,
+    pub scripts: Vec<EmbeddedScript>,
+    /// The markup to collect strings from, as offsets into the document.
+    pub markup: Vec<Range<usize>>,
+    pub header: DocumentHeader,
+    /// Parts of the document that couldn't be instrumented: a description of each one and why
+    /// (e.g. "a <template lang=\"pug\"> block, since its language isn't supported"), and where
+    /// it starts.
+    pub unsupported: Vec<(String, usize)>,
+}
+
+impl EmbeddedDocument {
+    /// Returns the document's code with everything outside its scripts replaced by
+    /// whitespace, so that positions in the program parsed from it are positions in the
+    /// document. Each script is followed by a semicolon, so that consecutive scripts parse as
+    /// separate statements, and each expression's braces are replaced by brackets.
+    pub fn script_code(self: &Self, code: &str) -> String {
+        let mut script_code: Vec<u8> = code
+            .bytes()
+            .map(|byte| match byte {
+                b'\n' | b'\r' => byte,
+                _ => b' ',
+            })
+            .collect();
+        for script in &self.scripts {
+            script_code[script.content.clone()]
+                .copy_from_slice(&code.as_bytes()[script.content.clone()]);
+            if script.expression && !script.content.is_empty() {
+                script_code[script.content.start] = b'[';
+                script_code[script.content.end - 1] = b']';
+            }
+            if let Some(byte) = script_code.get_mut(script.content.end) {
+                *byte = b';';
+            }
+        }
+        // Only ASCII bytes were replaced, and only outside the scripts, which are copied whole.
+        String::from_utf8(script_code).unwrap_or_default()
+    }
+
+    /// Returns options for parsing the document's scripts, in the language they're written in.
+    pub fn script_options(self: &Self, options: &InstrumentationOptions) -> InstrumentationOptions {
+        let mut script_options = options.clone();
+        script_options.input.typescript = Some(self.scripts.iter().any(|script| script.typescript));
+        script_options.input.jsx = Some(self.scripts.iter().any(|script| script.jsx));
+        script_options
+    }
+
+    pub fn header_position(self: &Self, input_file: &InputFile) -> HeaderPosition {
+        HeaderPosition {
+            pos: input_file.start_pos + BytePos(self.header.offset as u32),
+            needs_terminator: false,
+            enclosing_block: self.header.enclosing_block.clone(),
+            export_declarations: self.header.export_declarations,
+            // Strings collected from markup may contain `</script>` once their entities
+            // are decoded. Escaping is harmless where the header isn't in a `<script>`
+            // element (e.g. Astro frontmatter), so it's done for every kind of document.
+            escape_script_end_tags: true,
+        }
+    }
+
+    /// Collects the strings in the document's markup, and emits rewrites that replace them
+    /// with dictionary references.
+    pub fn collect_markup(
+        self: &Self,
+        input_file: &InputFile,
+        dictionary_tracker: &mut DictionaryTracker,
+        rewrite_tracker: &mut RewriteTracker,
+        jsx_filter: &JSXFilter,
+    ) {
+        let mut collector = MarkupCollector {
+            code: input_file.code,
+            start_pos: input_file.start_pos,
+            dictionary_tracker,
+            rewrite_tracker,
+            jsx_filter,
+        };
+        for range in &self.markup {
+            match self.kind {
+                DocumentKind::Vue => collect_vue_template(&mut collector, range.clone()),
+                DocumentKind::Svelte => collect_svelte_markup(&mut collector, range.clone()),
+                DocumentKind::Astro => collect_astro_markup(&mut collector, range.clone()),
+                DocumentKind::Mdx => collect_mdx_prose(&mut collector, range.clone()),
+            }
+        }
+    }
+}
+
+/// The state shared by the front ends while they collect strings from markup.
+pub struct MarkupCollector<'a> {
+    pub code: &'a str,
+    start_pos: BytePos,
+    pub dictionary_tracker: &'a mut DictionaryTracker,
+    pub rewrite_tracker: &'a mut RewriteTracker,
+    pub jsx_filter: &'a JSXFilter,
+}
+
+impl<'a> MarkupCollector<'a> {
+    pub fn pos(self: &Self, offset: usize) -> BytePos {
+        self.start_pos + BytePos(offset as u32)
+    }
+
+    pub fn span(self: &Self, range: &Range<usize>) -> Span {
+        Span {
+            lo: self.pos(range.start),
+            hi: self.pos(range.end),
+        }
+    }
+
+    /// Adds the string in `value` to the dictionary, and emits the rewrite that `rewrite`
+    /// builds to replace `replaced` with a reference to it. Returns true if the string was
+    /// collected.
+    pub fn collect_string(
+        self: &mut Self,
+        value: &Range<usize>,
+        replaced: &Range<usize>,
+        category: ContextCategory,
+        rewrite: impl FnOnce(usize, Span) -> Rewrite<PrivacyRewriteTemplate>,
+    ) -> bool {
+        let Some(index) = self.dictionary_tracker.maybe_add_markup_string(
+            &self.code[value.clone()],
+            &self.span(value),
+            category,
+        ) else {
+            return false;
+        };
+
+        let span = self.span(replaced);
+        self.rewrite_tracker.emit(rewrite(index, span));
+        self.rewrite_tracker.add_token_position(span.lo);
+        self.rewrite_tracker.add_token_position(span.hi);
+        true
+    }
+}
@@ -0,0 +1,521 @@
+use std::ops::Range;
+
+use js_instrumentation_shared::expression_len;
+
+/// How a markup language embeds expressions in text and attributes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExpressionSyntax {
+    /// `{{ expression }}` in text, as in Vue templates. Attribute values are plain strings.
+    DoubleBraces,
+    /// `{expression}` in text and in attributes, as in Svelte and Astro.
+    Braces,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AttributeValue {
+    /// A quoted string. The range doesn't include the quotes.
+    Quoted(Range<usize>),
+    Unquoted(Range<usize>),
+    /// An expression, including its braces.
+    Expression(Range<usize>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MarkupAttribute {
+    /// The whole attribute, e.g. `title="Hello"`.
+    pub span: Range<usize>,
+    /// The attribute's name. It's empty for attributes like Svelte's `{...props}`, which are
+    /// just an expression.
+    pub name: Range<usize>,
+    pub value: Option<AttributeValue>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MarkupToken {
+    StartTag {
+        span: Range<usize>,
+        name: Range<usize>,
+        attributes: Vec<MarkupAttribute>,
+        self_closing: bool,
+    },
+    EndTag {
+        span: Range<usize>,
+        name: Range<usize>,
+    },
+    Text(Range<usize>),
+    /// An expression, including its braces.
+    Expression(Range<usize>),
+    Comment(Range<usize>),
+    /// The contents of an element like `<script>` or `<style>`, which aren't markup.
+    RawText(Range<usize>),
+}
+
+/// Elements whose contents aren't markup, and run until the element's end tag.
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style", "textarea"];
+
+/// Splits HTML-like markup into tokens. The scanner is lenient: it never fails, and anything
+/// it doesn't recognize as a tag, comment, or expression is treated as text. Ranges are byte
+/// offsets into the code the scanner was created with.
+pub struct MarkupScanner<'a> {
+    code: &'a str,
+    pos: usize,
+    end: usize,
+    syntax: ExpressionSyntax,
+    /// Some: the contents of the element with this name come next, and aren't markup.
+    raw_text_element: Option<String>,
+}
+
+impl<'a> MarkupScanner<'a> {
+    pub fn new(code: &'a str, range: Range<usize>, syntax: ExpressionSyntax) -> MarkupScanner<'a> {
+        MarkupScanner {
+            code,
+            pos: range.start,
+            end: range.end.min(code.len()),
+            syntax,
+            raw_text_element: None,
+        }
+    }
+
+    /// Treats everything up to the end tag of the given element as raw text, e.g. for the
+    /// contents of a custom block in a Vue component.
+    pub fn skip_contents_of(self: &mut Self, name: &str) {
+        self.raw_text_element = Some(name.to_string());
+    }
+
+    fn bytes(self: &Self) -> &'a [u8] {
+        &self.code.as_bytes()[..self.end]
+    }
+
+    fn starts_with(self: &Self, pos: usize, prefix: &str) -> bool {
+        self.bytes()[pos.min(self.end)..].starts_with(prefix.as_bytes())
+    }
+
+    fn find_from(self: &Self, from: usize, needle: &str) -> Option<usize> {
+        self.code[from.min(self.end)..self.end]
+            .find(needle)
+            .map(|offset| from + offset)
+    }
+
+    fn skip_whitespace(self: &Self, mut pos: usize) -> usize {
+        while pos < self.end && self.bytes()[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        pos
+    }
+
+    /// Returns true if a tag, comment, or end tag starts at the given position.
+    fn is_tag_start(self: &Self, pos: usize) -> bool {
+        let bytes = self.bytes();
+        bytes.get(pos) == Some(&b'<')
+            && bytes
+                .get(pos + 1)
+                .is_some_and(|next| next.is_ascii_alphabetic() || b"/!?>".contains(next))
+    }
+
+    fn is_expression_start(self: &Self, pos: usize) -> bool {
+        match self.syntax {
+            ExpressionSyntax::DoubleBraces => self.starts_with(pos, "{{"),
+            ExpressionSyntax::Braces => self.bytes().get(pos) == Some(&b'{'),
+        }
+    }
+
+    /// Returns the end of the braced expression that starts at the given position.
+    fn expression_end(self: &Self, start: usize) -> usize {
+        self.parsed_expression_end(start)
+            .unwrap_or_else(|| self.matching_brace_end(start))
+    }
+
+    /// Returns the end of the braced expression that starts at the given position by parsing
+    /// the expression, which, unlike matching braces, copes with JSX in the expression (e.g.
+    /// `{ok && <p>Don't panic</p>}`). Returns None if the braces don't hold an expression.
+    fn parsed_expression_end(self: &Self, start: usize) -> Option<usize> {
+        // Svelte's `{#if}`, `{:else}`, `{/if}`, and `{@html}` tags aren't expressions.
+        let expression_start = start + 1;
+        if self.syntax != ExpressionSyntax::Braces
+            || self
+                .bytes()
+                .get(expression_start)
+                .is_some_and(|byte| b"#:/@".contains(byte))
+        {
+            return None;
+        }
+        let len = expression_len(&self.code[expression_start..self.end])?;
+        let pos = self.skip_whitespace_and_comments(expression_start + len);
+        (self.bytes().get(pos) == Some(&b'}')).then_some(pos + 1)
+    }
+
+    fn skip_whitespace_and_comments(self: &Self, mut pos: usize) -> usize {
+        loop {
+            pos = self.skip_whitespace(pos);
+            if self.starts_with(pos, "//") {
+                pos = self.find_from(pos, "\n").unwrap_or(self.end);
+            } else if self.starts_with(pos, "/*") {
+                pos = self
+                    .find_from(pos + 2, "*/")
+                    .map(|end| end + 2)
+                    .unwrap_or(self.end);
+            } else {
+                return pos;
+            }
+        }
+    }
+
+    /// Returns the end of the braced expression that starts at the given position by matching
+    /// braces, skipping over any braces in strings.
+    fn matching_brace_end(self: &Self, start: usize) -> usize {
+        let bytes = self.bytes();
+        let mut depth = 0;
+        let mut pos = start;
+        while pos < self.end {
+            match bytes[pos] {
+                b'{' => depth += 1,
+                b'}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return pos + 1;
+                    }
+                }
+                quote @ (b'"' | b'\'' | b'`') => {
+                    pos += 1;
+                    while pos < self.end && bytes[pos] != quote {
+                        if bytes[pos] == b'\\' {
+                            pos += 1;
+                        }
+                        pos += 1;
+                    }
+                }
+                _ => {}
+            }
+            pos += 1;
+        }
+        self.end
+    }
+
+    fn name_end(self: &Self, mut pos: usize) -> usize {
+        let bytes = self.bytes();
+        while pos < self.end && !bytes[pos].is_ascii_whitespace() && !b"/>=".contains(&bytes[pos]) {
+            pos += 1;
+        }
+        pos
+    }
+
+    fn scan_raw_text(self: &mut Self, name: &str) -> MarkupToken {
+        let start = self.pos;
+        let close = format!("</{}", name);
+        let end = self.find_from(start, &close).unwrap_or(self.end);
+        self.pos = end;
+        MarkupToken::RawText(start..end)
+    }
+
+    fn scan_comment(self: &mut Self) -> MarkupToken {
+        let start = self.pos;
+        let end = if self.starts_with(start, "<!--") {
+            self.find_from(start + 4, "-->").map(|end| end + 3)
+        } else {
+            // `<!DOCTYPE html>` and the like.
+            self.find_from(start, ">").map(|end| end + 1)
+        }
+        .unwrap_or(self.end);
+        self.pos = end;
+        MarkupToken::Comment(start..end)
+    }
+
+    fn scan_end_tag(self: &mut Self) -> MarkupToken {
+        let start = self.pos;
+        let name_start = start + 2;
+        let name_end = self.name_end(name_start);
+        let end = self
+            .find_from(name_end, ">")
+            .map(|end| end + 1)
+            .unwrap_or(self.end);
+        self.pos = end;
+        MarkupToken::EndTag {
+            span: start..end,
+            name: name_start..name_end,
+        }
+    }
+
+    fn scan_start_tag(self: &mut Self) -> MarkupToken {
+        let start = self.pos;
+        let name_start = start + 1;
+        let name_end = self.name_end(name_start);
+        let mut attributes = vec![];
+        let mut pos = name_end;
+        let mut self_closing = false;
+
+        loop {
+            pos = self.skip_whitespace(pos);
+            if pos >= self.end {
+                break;
+            }
+            if self.starts_with(pos, ">") {
+                pos += 1;
+                break;
+            }
+            if self.starts_with(pos, "/>") {
+                self_closing = true;
+                pos += 2;
+                break;
+            }
+            if self.bytes()[pos] == b'/' {
+                pos += 1;
+                continue;
+            }
+            if self.syntax == ExpressionSyntax::Braces && self.bytes()[pos] == b'{' {
+                let end = self.expression_end(pos);
+                attributes.push(MarkupAttribute {
+                    span: pos..end,
+                    name: pos..pos,
+                    value: Some(AttributeValue::Expression(pos..end)),
+                });
+                pos = end;
+                continue;
+            }
+
+            let attr_start = pos;
+            // An attribute name is never empty; a stray `=` becomes part of the name.
+            let attr_name_end = self.name_end(pos + 1);
+            pos = self.skip_whitespace(attr_name_end);
+            let value = if self.starts_with(pos, "=") {
+                pos = self.skip_whitespace(pos + 1);
+                match self.bytes().get(pos) {
+                    Some(quote @ (b'"' | b'\'')) => {
+                        let value_start = pos + 1;
+                        let value_end = self
+                            .find_from(value_start, if *quote == b'"' { "\"" } else { "'" })
+                            .unwrap_or(self.end);
+                        pos = (value_end + 1).min(self.end);
+                        Some(AttributeValue::Quoted(value_start..value_end))
+                    }
+                    Some(b'{') if self.syntax == ExpressionSyntax::Braces => {
+                        let value_start = pos;
+                        pos = self.expression_end(pos);
+                        Some(AttributeValue::Expression(value_start..pos))
+                    }
+                    _ => {
+                        let value_start = pos;
+                        while pos < self.end
+                            && !self.bytes()[pos].is_ascii_whitespace()
+                            && self.bytes()[pos] != b'>'
+                        {
+                            pos += 1;
+                        }
+                        Some(AttributeValue::Unquoted(value_start..pos))
+                    }
+                }
+            } else {
+                // Leave the whitespace for the next attribute.
+                pos = attr_name_end;
+                None
+            };
+            attributes.push(MarkupAttribute {
+                span: attr_start..pos,
+                name: attr_start..attr_name_end,
+                value,
+            });
+        }
+
+        self.pos = pos;
+        let name = &self.code[name_start..name_end];
+        if !self_closing
+            && RAW_TEXT_ELEMENTS
+                .iter()
+                .any(|element| element.eq_ignore_ascii_case(name))
+        {
+            self.raw_text_element = Some(name.to_string());
+        }
+
+        MarkupToken::StartTag {
+            span: start..pos,
+            name: name_start..name_end,
+            attributes,
+            self_closing,
+        }
+    }
+
+    fn scan_text(self: &mut Self) -> MarkupToken {
+        let start = self.pos;
+        let mut pos = start + 1;
+        while pos < self.end && !self.is_tag_start(pos) && !self.is_expression_start(pos) {
+            pos += 1;
+        }
+        // Never split a multi-byte character.
+        while !self.code.is_char_boundary(pos) {
+            pos += 1;
+        }
+        self.pos = pos;
+        MarkupToken::Text(start..pos)
+    }
+}
+
+impl<'a> Iterator for MarkupScanner<'a> {
+    type Item = MarkupToken;
+
+    fn next(&mut self) -> Option<MarkupToken> {
+        if let Some(name) = self.raw_text_element.take() {
+            return Some(self.scan_raw_text(&name));
+        }
+        if self.pos >= self.end {
+            return None;
+        }
+
+        let pos = self.pos;
+        Some(
+            if self.starts_with(pos, "<!") || self.starts_with(pos, "<?") {
+                self.scan_comment()
+            } else if self.is_tag_start(pos) && self.starts_with(pos, "</") {
+                self.scan_end_tag()
+            } else if self.is_tag_start(pos) {
+                self.scan_start_tag()
+            } else if self.is_expression_start(pos) {
+                let end = self.expression_end(pos);
+                self.pos = end;
+                MarkupToken::Expression(pos..end)
+            } else {
+                self.scan_text()
+            },
+        )
+    }
+}
+
+/// Returns true if the given element never has contents or an end tag.
+pub fn is_void_element(name: &str) -> bool {
+    [
+        "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source",
+        "track", "wbr",
+    ]
+    .iter()
+    .any(|element| element.eq_ignore_ascii_case(name))
+}
+
+fn find_attribute<'a>(
+    code: &str,
+    attributes: &'a [MarkupAttribute],
+    name: &str,
+) -> Option<&'a MarkupAttribute> {
+    attributes
+        .iter()
+        .find(|attribute| &code[attribute.name.clone()] == name)
+}
+
+pub fn has_attribute(code: &str, attributes: &[MarkupAttribute], name: &str) -> bool {
+    find_attribute(code, attributes, name).is_some()
+}
+
+/// Returns the value of the given attribute, if it has a static one.
+pub fn attribute_value<'a>(
+    code: &'a str,
+    attributes: &[MarkupAttribute],
+    name: &str,
+) -> Option<&'a str> {
+    match find_attribute(code, attributes, name)?.value {
+        Some(AttributeValue::Quoted(ref value) | AttributeValue::Unquoted(ref value)) => {
+            Some(&code[value.clone()])
+        }
+        _ => None,
+    }
+}
+
+/// Returns the given range with leading and trailing whitespace removed, or None if the range
+/// contains only whitespace.
+pub fn trim_range(code: &str, range: Range<usize>) -> Option<Range<usize>> {
+    let text = &code[range.clone()];
+    let is_whitespace = |c: char| c.is_ascii_whitespace();
+    let trimmed = text.trim_matches(is_whitespace);
+    if trimmed.is_empty() {
+        return None;
+    }
+    let start = range.start + (text.len() - text.trim_start_matches(is_whitespace).len());
+    Some(start..start + trimmed.len())
+}
+
+/// The fence around a document's frontmatter, e.g. an Astro component's script.
+const FRONTMATTER_FENCE: &str = "---";
+
+/// Returns the contents of the document's frontmatter, if it has any, and the end of the line
+/// with the frontmatter's closing fence.
+pub fn frontmatter(code: &str) -> Option<(Range<usize>, usize)> {
+    let start = code.len() - code.trim_start().len();
+    if !code[start..].starts_with(FRONTMATTER_FENCE) {
+        return None;
+    }
+    let content_start = start + code[start..].find('\n')? + 1;
+    let mut line_start = content_start;
+    loop {
+        let line_end = code[line_start..]
+            .find('\n')
+            .map_or(code.len(), |end| line_start + end + 1);
+        if code[line_start..].starts_with(FRONTMATTER_FENCE) {
+            return Some((content_start..line_start, line_end));
+        }
+        if line_end == code.len() {
+            return None;
+        }
+        line_start = line_end;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(code: &str, syntax: ExpressionSyntax) -> Vec<(&'static str, &str)> {
+        MarkupScanner::new(code, 0..code.len(), syntax)
+            .map(|token| match token {
+                MarkupToken::StartTag { span, .. } => ("start", &code[span]),
+                MarkupToken::EndTag { span, .. } => ("end", &code[span]),
+                MarkupToken::Text(span) => ("text", &code[span]),
+                MarkupToken::Expression(span) => ("expression", &code[span]),
+                MarkupToken::Comment(span) => ("comment", &code[span]),
+                MarkupToken::RawText(span) => ("raw", &code[span]),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn splits_markup_into_tokens() {
+        assert_eq!(
+            tokens(
+                "<p title=\"a > b\">Hi {{ a < b ? '}}' : c }} &lt; there<!-- x --><br/></p><style>p > a {}</style>",
+                ExpressionSyntax::DoubleBraces
+            ),
+            vec![
+                ("start", "<p title=\"a > b\">"),
+                ("text", "Hi "),
+                ("expression", "{{ a < b ? '}}' : c }}"),
+                ("text", " &lt; there"),
+                ("comment", "<!-- x -->"),
+                ("start", "<br/>"),
+                ("end", "</p>"),
+                ("start", "<style>"),
+                ("raw", "p > a {}"),
+                ("end", "</style>"),
+            ]
+        );
+    }
+
+    #[test]
+    fn scans_attributes() {
+        let code =
+            "<Button {...rest} label='Save' on:click={() => go({ a: 1 })} disabled data-x=1>";
+        let Some(MarkupToken::StartTag { attributes, .. }) =
+            MarkupScanner::new(code, 0..code.len(), ExpressionSyntax::Braces).next()
+        else {
+            panic!("Expected a start tag");
+        };
+        let attributes: Vec<(&str, Option<AttributeValue>)> = attributes
+            .into_iter()
+            .map(|attribute| (&code[attribute.name], attribute.value))
+            .collect();
+        assert_eq!(
+            attributes,
+            vec![
+                ("", Some(AttributeValue::Expression(8..17))),
+                ("label", Some(AttributeValue::Quoted(25..29))),
+                ("on:click", Some(AttributeValue::Expression(40..60))),
+                ("disabled", None),
+                ("data-x", Some(AttributeValue::Unquoted(77..78))),
+            ]
+        );
+    }
+}
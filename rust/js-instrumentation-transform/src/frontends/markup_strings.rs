@@ -0,0 +1,104 @@
+use std::ops::Range;
+
+use js_instrumentation_rewrite::rewrite::Rewrite;
+use js_instrumentation_shared::instrumentation_output::ContextCategory;
+use swc_common::Span;
+
+use crate::rewrite::PrivacyRewriteTemplate;
+
+use super::embedded_document::MarkupCollector;
+use super::markup_scanner::{is_void_element, trim_range, MarkupAttribute, MarkupToken};
+
+/// Builds the rewrite that replaces a string in markup with a reference to its dictionary
+/// entry.
+pub type MarkupRewrite = fn(usize, Span) -> Rewrite<PrivacyRewriteTemplate>;
+
+/// The parts of markup that `collect_markup_strings()` cares about.
+pub enum MarkupStringToken {
+    StartTag {
+        name: Range<usize>,
+        attributes: Vec<MarkupAttribute>,
+        self_closing: bool,
+    },
+    EndTag {
+        name: Range<usize>,
+    },
+    /// Text, and the rewrite that replaces it.
+    Text(Range<usize>, MarkupRewrite),
+}
+
+impl MarkupStringToken {
+    /// Converts a token from a `MarkupScanner`, whose text is replaced by `text_rewrite`.
+    /// Returns None for tokens that don't hold strings or affect which strings are collected.
+    pub fn from_markup(token: MarkupToken, text_rewrite: MarkupRewrite) -> Option<Self> {
+        match token {
+            MarkupToken::StartTag {
+                name,
+                attributes,
+                self_closing,
+                ..
+            } => Some(MarkupStringToken::StartTag {
+                name,
+                attributes,
+                self_closing,
+            }),
+            MarkupToken::EndTag { name, .. } => Some(MarkupStringToken::EndTag { name }),
+            MarkupToken::Text(range) => Some(MarkupStringToken::Text(range, text_rewrite)),
+            _ => None,
+        }
+    }
+}
+
+/// Collects the text and attribute values in the given markup, skipping the contents of the
+/// elements that `is_uncollected_element` or the JSX filter picks out. `collect_attribute`
+/// decides which attributes are collected and how they're rewritten.
+pub fn collect_markup_strings(
+    collector: &mut MarkupCollector,
+    tokens: impl Iterator<Item = MarkupStringToken>,
+    is_uncollected_element: impl Fn(&str, &[MarkupAttribute]) -> bool,
+    collect_attribute: impl Fn(&mut MarkupCollector, &MarkupAttribute),
+) {
+    let code = collector.code;
+    // The open elements, and whether their contents are uncollected.
+    let mut open_elements: Vec<(&str, bool)> = vec![];
+
+    for token in tokens {
+        let in_uncollected_element = open_elements
+            .last()
+            .is_some_and(|(_, uncollected)| *uncollected);
+        match token {
+            MarkupStringToken::StartTag {
+                name,
+                attributes,
+                self_closing,
+            } => {
+                let name = &code[name];
+                let uncollected = in_uncollected_element
+                    || collector.jsx_filter.is_uncollected_element(name)
+                    || is_uncollected_element(name, &attributes);
+
+                if !uncollected {
+                    for attribute in &attributes {
+                        collect_attribute(collector, attribute);
+                    }
+                }
+                if !self_closing && !is_void_element(name) {
+                    open_elements.push((name, uncollected));
+                }
+            }
+            MarkupStringToken::EndTag { name } => {
+                let name = &code[name];
+                if let Some(index) = open_elements.iter().rposition(|(open, _)| *open == name) {
+                    open_elements.truncate(index);
+                }
+            }
+            MarkupStringToken::Text(range, rewrite) if !in_uncollected_element => {
+                let Some(range) = trim_range(code, range) else {
+                    continue;
+                };
+                collector.collect_string(&range, &range, ContextCategory::JsxText, rewrite);
+            }
+            _ => {}
+        }
+    }
+}
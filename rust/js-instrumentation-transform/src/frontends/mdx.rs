@@ -0,0 +1,509 @@
+use std::ops::Range;
+
+use crate::rewrite::{
+    replace_flow_text_with_dictionary_ref, replace_jsx_string_with_dictionary_ref,
+};
+
+use super::braced_markup::{collect_braced_attribute, BracedMarkupRules};
+use super::embedded_document::{
+    DocumentHeader, DocumentKind, EmbeddedDocument, EmbeddedScript, MarkupCollector,
+};
+use super::markup_scanner::{
+    frontmatter, trim_range, AttributeValue, ExpressionSyntax, MarkupScanner, MarkupToken,
+};
+use super::markup_strings::{collect_markup_strings, MarkupStringToken};
+
+/// A block of an MDX document.
+enum MdxBlock {
+    /// `import` and `export` statements.
+    Esm(Range<usize>),
+    /// Markdown, which may contain JSX and expressions.
+    Prose(Range<usize>),
+}
+
+/// Splits an MDX document into its `import` and `export` statements, the expressions in its
+/// prose (including JSX attribute values), and its prose. Frontmatter and code blocks are
+/// left alone.
+pub fn split_mdx_document(code: &str) -> EmbeddedDocument {
+    let content_start = frontmatter(code).map_or(0, |(_, end)| end);
+    let mut scripts = vec![];
+    let mut markup = vec![];
+
+    for block in mdx_blocks(code, content_start) {
+        match block {
+            MdxBlock::Esm(content) => scripts.push(script(content, false)),
+            MdxBlock::Prose(range) => {
+                for token in ProseScanner::new(code, range.clone()) {
+                    match token {
+                        ProseToken::Expression(content) => scripts.push(script(content, true)),
+                        ProseToken::Tag(MarkupToken::StartTag { attributes, .. }) => {
+                            for attribute in attributes {
+                                if let Some(AttributeValue::Expression(content)) = attribute.value {
+                                    scripts.push(script(content, true));
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                markup.push(range);
+            }
+        }
+    }
+
+    // Only `import` and `export` statements may appear outside of expressions, so the header
+    // goes in the first block of them, or in a block of its own, and its declarations are
+    // exported.
+    let header = match scripts.iter().find(|script| !script.expression) {
+        Some(esm) => DocumentHeader {
+            offset: esm.content.start,
+            enclosing_block: None,
+            export_declarations: true,
+        },
+        None => DocumentHeader {
+            offset: content_start,
+            enclosing_block: Some((String::new(), "\n\n".into())),
+            export_declarations: true,
+        },
+    };
+
+    EmbeddedDocument {
+        kind: DocumentKind::Mdx,
+        scripts,
+        markup,
+        header,
+        unsupported: vec![],
+    }
+}
+
+fn script(content: Range<usize>, expression: bool) -> EmbeddedScript {
+    EmbeddedScript {
+        content,
+        typescript: false,
+        jsx: true,
+        expression,
+    }
+}
+
+/// Returns true if the given line starts an `import` or `export` statement.
+fn is_esm_line(line: &str) -> bool {
+    ["import", "export"].iter().any(|keyword| {
+        line.strip_prefix(keyword).is_some_and(|rest| {
+            rest.starts_with(|c: char| c.is_whitespace() || c == '{' || c == '*')
+        })
+    })
+}
+
+/// Returns the fence that opens a fenced code block on the given line, if any.
+fn code_fence(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    ["```", "~~~"].iter().find_map(|fence| {
+        line.starts_with(fence).then(|| {
+            let fence_char = fence.as_bytes()[0] as char;
+            &line[..line.len() - line.trim_start_matches(fence_char).len()]
+        })
+    })
+}
+
+/// Splits the document, from the given offset on, into blocks of `import` and `export`
+/// statements and blocks of prose. Fenced code blocks are in neither.
+fn mdx_blocks(code: &str, start: usize) -> Vec<MdxBlock> {
+    let line_end = |pos: usize| {
+        code[pos..]
+            .find('\n')
+            .map_or(code.len(), |end| pos + end + 1)
+    };
+    let mut blocks = vec![];
+    let mut prose_start = start;
+    let mut pos = start;
+    let mut previous_line_blank = true;
+
+    let end_prose = |blocks: &mut Vec<MdxBlock>, prose_start: usize, end: usize| {
+        if prose_start < end {
+            blocks.push(MdxBlock::Prose(prose_start..end));
+        }
+    };
+
+    while pos < code.len() {
+        let line = &code[pos..line_end(pos)];
+        if previous_line_blank && is_esm_line(line) {
+            // The statements run until the next blank line.
+            end_prose(&mut blocks, prose_start, pos);
+            let mut end = pos;
+            while end < code.len() && !code[end..line_end(end)].trim().is_empty() {
+                end = line_end(end);
+            }
+            blocks.push(MdxBlock::Esm(pos..end));
+            pos = end;
+            prose_start = end;
+        } else if let Some(fence) = code_fence(line) {
+            end_prose(&mut blocks, prose_start, pos);
+            let mut end = line_end(pos);
+            while end < code.len() && !code[end..line_end(end)].trim_start().starts_with(fence) {
+                end = line_end(end);
+            }
+            pos = line_end(end.min(code.len()));
+            prose_start = pos;
+            previous_line_blank = true;
+        } else {
+            previous_line_blank = line.trim().is_empty();
+            pos = line_end(pos);
+        }
+    }
+    end_prose(&mut blocks, prose_start, code.len());
+    blocks
+}
+
+enum ProseToken {
+    /// Text, and whether it makes up a whole line where MDX would treat an expression as a
+    /// block of its own.
+    Text(Range<usize>, bool),
+    /// An expression, including its braces.
+    Expression(Range<usize>),
+    /// A JSX start or end tag.
+    Tag(MarkupToken),
+}
+
+/// Splits Markdown prose into runs of text, expressions, and JSX tags, skipping Markdown
+/// syntax like emphasis markers, link destinations, and code spans.
+struct ProseScanner<'a> {
+    code: &'a str,
+    pos: usize,
+    end: usize,
+    at_line_start: bool,
+    /// Some: where the current line's content starts, if it's in a position where MDX would
+    /// treat an expression as a block (i.e. not in a heading).
+    flow_start: Option<usize>,
+}
+
+impl<'a> ProseScanner<'a> {
+    fn new(code: &'a str, range: Range<usize>) -> ProseScanner<'a> {
+        ProseScanner {
+            code,
+            pos: range.start,
+            end: range.end,
+            at_line_start: true,
+            flow_start: None,
+        }
+    }
+
+    fn byte(self: &Self, pos: usize) -> Option<u8> {
+        (pos < self.end).then(|| self.code.as_bytes()[pos])
+    }
+
+    fn line_end(self: &Self, pos: usize) -> usize {
+        self.code[pos..self.end]
+            .find('\n')
+            .map_or(self.end, |end| pos + end)
+    }
+
+    fn skip_spaces(self: &Self, mut pos: usize) -> usize {
+        while matches!(self.byte(pos), Some(b' ' | b'\t')) {
+            pos += 1;
+        }
+        pos
+    }
+
+    /// Returns the position after the closing delimiter that matches the opening delimiter at
+    /// the given position, or the position after the opening delimiter if there's no match.
+    fn skip_delimited(self: &Self, pos: usize, open: u8, close: u8) -> usize {
+        let mut depth = 0;
+        for (offset, byte) in self.code.as_bytes()[pos..self.end].iter().enumerate() {
+            if *byte == open {
+                depth += 1;
+            } else if *byte == close {
+                depth -= 1;
+                if depth == 0 {
+                    return pos + offset + 1;
+                }
+            }
+        }
+        pos + 1
+    }
+
+    /// Skips the block syntax at the start of a line: indentation, block quote and list
+    /// markers, and heading markers. Lines that are only syntax, like thematic breaks, table
+    /// delimiter rows, and link reference definitions, are skipped entirely.
+    fn start_line(self: &mut Self) {
+        let line_end = self.line_end(self.pos);
+        let line = self.code[self.pos..line_end].trim();
+        let only_syntax = line.chars().all(|c| "-=*_|: \t".contains(c))
+            || (line.starts_with('[') && line.contains("]:"));
+        if only_syntax {
+            self.pos = line_end;
+            return;
+        }
+
+        let mut pos = self.skip_spaces(self.pos);
+        loop {
+            let rest = &self.code[pos..line_end];
+            let is_bullet = rest.starts_with(['-', '*', '+']) && rest[1..].starts_with([' ', '\t']);
+            let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            let is_ordered = (1..=9).contains(&digits)
+                && rest[digits..].starts_with(['.', ')'])
+                && rest[digits + 1..].starts_with([' ', '\t']);
+            let marker_len = if rest.starts_with('>') || is_bullet {
+                1
+            } else if is_ordered {
+                digits + 1
+            } else {
+                break;
+            };
+            pos = self.skip_spaces(pos + marker_len);
+            // A task list item's checkbox.
+            let rest = &self.code[pos..line_end];
+            if ["[ ]", "[x]", "[X]"]
+                .iter()
+                .any(|box_| rest.starts_with(box_))
+            {
+                pos = self.skip_spaces(pos + 3);
+            }
+        }
+
+        let rest = &self.code[pos..line_end];
+        let heading_level = rest.len() - rest.trim_start_matches('#').len();
+        if (1..=6).contains(&heading_level) && self.byte(pos + heading_level) != Some(b'#') {
+            self.pos = self.skip_spaces(pos + heading_level);
+            self.flow_start = None;
+        } else {
+            self.pos = pos;
+            self.flow_start = Some(pos);
+        }
+    }
+
+    fn is_tag_start(self: &Self, pos: usize) -> bool {
+        self.byte(pos) == Some(b'<')
+            && self
+                .byte(pos + 1)
+                .is_some_and(|next| next.is_ascii_alphabetic() || next == b'/' || next == b'>')
+    }
+
+    fn is_text(self: &Self, pos: usize) -> bool {
+        match self.byte(pos) {
+            Some(b'\n' | b'`' | b'{' | b'\\' | b']' | b'*' | b'_' | b'~' | b'[' | b'|') => false,
+            Some(b'!') => self.byte(pos + 1) != Some(b'['),
+            Some(b'<') => !self.is_tag_start(pos),
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    /// Scans the expression or JSX tag at the current position. Returns None for comments,
+    /// which are skipped.
+    fn scan_markup(self: &mut Self) -> Option<ProseToken> {
+        let token =
+            MarkupScanner::new(self.code, self.pos..self.end, ExpressionSyntax::Braces).next()?;
+        match token {
+            MarkupToken::Expression(ref range) | MarkupToken::Comment(ref range) => {
+                self.pos = range.end
+            }
+            MarkupToken::StartTag { ref span, .. } | MarkupToken::EndTag { ref span, .. } => {
+                self.pos = span.end
+            }
+            _ => {
+                self.pos += 1;
+                return None;
+            }
+        }
+        match token {
+            MarkupToken::Expression(range) => Some(ProseToken::Expression(range)),
+            MarkupToken::Comment(_) => None,
+            tag => Some(ProseToken::Tag(tag)),
+        }
+    }
+
+    fn scan_text(self: &mut Self) -> Option<ProseToken> {
+        let start = self.pos;
+        let mut pos = start + 1;
+        while self.is_text(pos) {
+            pos += 1;
+        }
+        // Never split a multi-byte character.
+        while !self.code.is_char_boundary(pos) {
+            pos += 1;
+        }
+        self.pos = pos;
+
+        let range = trim_range(self.code, start..pos)?;
+        let whole_line =
+            self.flow_start == Some(start) && self.code[pos..self.line_end(pos)].trim().is_empty();
+        Some(ProseToken::Text(range, whole_line))
+    }
+}
+
+impl<'a> Iterator for ProseScanner<'a> {
+    type Item = ProseToken;
+
+    fn next(&mut self) -> Option<ProseToken> {
+        while self.pos < self.end {
+            if self.at_line_start {
+                self.at_line_start = false;
+                self.start_line();
+                continue;
+            }
+
+            let pos = self.pos;
+            match self.code.as_bytes()[pos] {
+                b'\n' => {
+                    self.pos += 1;
+                    self.at_line_start = true;
+                }
+                // A code span runs until a run of backticks of the same length.
+                b'`' => {
+                    let len = self.code[pos..self.end]
+                        .find(|c| c != '`')
+                        .unwrap_or(self.end - pos);
+                    let fence = &self.code[pos..pos + len];
+                    self.pos = self.code[pos + len..self.end]
+                        .find(fence)
+                        .map_or(pos + len, |end| pos + len + end + len);
+                }
+                b'{' => {
+                    if let Some(token) = self.scan_markup() {
+                        return Some(token);
+                    }
+                }
+                b'<' if self.is_tag_start(pos) => {
+                    if let Some(token) = self.scan_markup() {
+                        return Some(token);
+                    }
+                }
+                b'\\' => {
+                    self.pos += match self.byte(pos + 1) {
+                        Some(escaped) if escaped.is_ascii_punctuation() => 2,
+                        _ => 1,
+                    }
+                }
+                // An image's alternative text, which can't hold an expression.
+                b'!' if self.byte(pos + 1) == Some(b'[') => {
+                    self.pos = self.skip_delimited(pos + 1, b'[', b']');
+                    if self.byte(self.pos) == Some(b'(') {
+                        self.pos = self.skip_delimited(self.pos, b'(', b')');
+                    }
+                }
+                // A link's destination or reference.
+                b']' => {
+                    self.pos += 1;
+                    match self.byte(self.pos) {
+                        Some(b'(') => self.pos = self.skip_delimited(self.pos, b'(', b')'),
+                        Some(b'[') => self.pos = self.skip_delimited(self.pos, b'[', b']'),
+                        _ => {}
+                    }
+                }
+                b'*' | b'_' | b'~' | b'[' | b'|' => self.pos += 1,
+                _ => {
+                    if let Some(token) = self.scan_text() {
+                        return Some(token);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// JSX attributes in MDX are collected like JSX attributes elsewhere.
+const MDX_ATTRIBUTE_RULES: BracedMarkupRules = BracedMarkupRules {
+    is_uncollected_element: |_, _, _| false,
+    is_collected_attribute: |_| true,
+    interpolates_quoted_attributes: false,
+};
+
+/// Collects the text in MDX prose, and the static attribute values of its JSX elements. Both
+/// become expressions, e.g. `{D[0]}` and `title={D[1]}`.
+pub fn collect_mdx_prose(collector: &mut MarkupCollector, range: Range<usize>) {
+    let tokens = ProseScanner::new(collector.code, range).filter_map(|token| match token {
+        ProseToken::Text(range, true) => Some(MarkupStringToken::Text(
+            range,
+            replace_flow_text_with_dictionary_ref,
+        )),
+        ProseToken::Text(range, false) => Some(MarkupStringToken::Text(
+            range,
+            replace_jsx_string_with_dictionary_ref,
+        )),
+        ProseToken::Tag(tag) => {
+            MarkupStringToken::from_markup(tag, replace_jsx_string_with_dictionary_ref)
+        }
+        ProseToken::Expression(_) => None,
+    });
+    collect_markup_strings(
+        collector,
+        tokens,
+        |_, _| false,
+        |collector, attribute| collect_braced_attribute(collector, attribute, &MDX_ATTRIBUTE_RULES),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::frontends::tests::{original_position, position_of, transform};
+
+    use super::*;
+
+    #[test]
+    fn splits_documents_into_blocks() {
+        let code = "---\ntitle: Hello\n---\nimport { Note } from './note'\nexport const meta = {}\n\n# Hello {meta.title}\n\n```js\nconst a = {b};\n```\n\n<Note kind={'info'}>Text with `{code}`</Note>\n";
+        let document = split_mdx_document(code);
+        assert_eq!(
+            document
+                .scripts
+                .iter()
+                .map(|script| &code[script.content.clone()])
+                .collect::<Vec<_>>(),
+            vec![
+                "import { Note } from './note'\nexport const meta = {}\n",
+                "{meta.title}",
+                "{'info'}"
+            ]
+        );
+        assert_eq!(document.header.offset, code.find("import").unwrap());
+    }
+
+    #[test]
+    fn collects_prose_text() {
+        assert_eq!(
+            transform(
+                "page.mdx",
+                "import { Note } from './note'\n\n# Welcome to the docs\n\nThis paragraph stands alone.\n\nSome **bold claims** and a [helpful link](https://example.com) here.\n\n- First list item\n- ![An image](a.png) Second list item\n\n<Note title=\"Please note\">\n  Nothing to see here\n</Note>\n\n`inline code stays` as is.\n"
+            ),
+            "import{$}from'datadog:privacy-helpers.mjs';export const D=$(['Welcome to the docs','This paragraph stands alone.','bold claims','and a','helpful link','here.','First list item','Second list item','Please note','Nothing to see here','Some','as is.']);import { Note } from './note'\n\n# {D[0]}\n\n{D[1]}{}\n\nSome **{D[2]}** and a [{D[4]}](https://example.com) here.\n\n- {D[6]}{}\n- ![An image](a.png) {D[7]}\n\n<Note title={D[8]}>\n  {D[9]}{}\n</Note>\n\n`inline code stays` as is.\n"
+        );
+    }
+
+    #[test]
+    fn adds_an_esm_block_when_needed() {
+        assert_eq!(
+            transform("page.mdx", "---\ntitle: Hello\n---\nThis paragraph stands alone.\n"),
+            "---\ntitle: Hello\n---\nimport{$}from'datadog:privacy-helpers.mjs';export const D=$(['This paragraph stands alone.']);\n\n{D[0]}{}\n"
+        );
+    }
+
+    #[test]
+    fn escapes_script_end_tags_in_the_header() {
+        assert_eq!(
+            transform("page.mdx", "Close it with &lt;/script&gt; please\n"),
+            "import{$}from'datadog:privacy-helpers.mjs';export const D=$(['Close it with <\\/script> please']);\n\n{D[0]}{}\n"
+        );
+    }
+
+    #[test]
+    fn maps_prose_and_script_strings_back_to_their_sources() {
+        let code = "export const name = 'World'\n\n# Welcome to the docs\n\n<Note title=\"Please note\">\n  Nothing to see here\n</Note>\n";
+        assert_eq!(
+            original_position("page.mdx", code, "{D[1]}"),
+            position_of(code, "Welcome to the docs")
+        );
+        assert_eq!(
+            original_position("page.mdx", code, "{D[2]}"),
+            position_of(code, "\"Please note\"")
+        );
+        assert_eq!(
+            original_position("page.mdx", code, "{D[3]}"),
+            position_of(code, "Nothing to see here")
+        );
+        assert_eq!(
+            original_position("page.mdx", code, "D[0]\n"),
+            position_of(code, "'World'")
+        );
+    }
+}
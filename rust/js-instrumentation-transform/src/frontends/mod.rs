@@ -0,0 +1,90 @@
+mod astro;
+pub use astro::split_astro_component;
+
+mod braced_markup;
+
+mod embedded_document;
+pub use embedded_document::EmbeddedDocument;
+
+mod markup_scanner;
+
+mod markup_strings;
+
+mod mdx;
+pub use mdx::split_mdx_document;
+
+mod svelte;
+pub use svelte::split_svelte_component;
+
+mod vue;
+pub use vue::split_vue_component;
+
+use js_instrumentation_shared::{
+    filename_is_astro, filename_is_mdx, filename_is_svelte, filename_is_vue,
+};
+
+/// Splits the given file into scripts and markup, if it's a document that embeds scripts in
+/// markup (e.g. a Vue component or an MDX document). Returns None for ordinary JavaScript and
+/// TypeScript files.
+pub fn split_document(filename: &str, code: &str) -> Option<EmbeddedDocument> {
+    if filename_is_vue(filename) {
+        return Some(split_vue_component(code));
+    }
+    if filename_is_svelte(filename) {
+        return Some(split_svelte_component(code));
+    }
+    if filename_is_astro(filename) {
+        return Some(split_astro_component(code));
+    }
+    if filename_is_mdx(filename) {
+        return Some(split_mdx_document(code));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use js_instrumentation_shared::{
+        InstrumentationInput, InstrumentationOptions, InstrumentationOutput,
+    };
+
+    use crate::apply_transform;
+    use crate::source_maps::parse_source_map;
+
+    fn transform_output(id: &str, code: &str) -> InstrumentationOutput {
+        apply_transform(
+            &InstrumentationInput {
+                id: id.into(),
+                code: code.into(),
+                map: None,
+            },
+            &InstrumentationOptions::default(),
+        )
+        .unwrap()
+    }
+
+    /// Instruments the given document with the default options.
+    pub fn transform(id: &str, code: &str) -> String {
+        transform_output(id, code).code
+    }
+
+    /// Returns the zero-based line and column of the first occurrence of `needle` in `code`.
+    pub fn position_of(code: &str, needle: &str) -> (u32, u32) {
+        let offset = code.find(needle).unwrap();
+        let line_start = code[..offset].rfind('\n').map_or(0, |pos| pos + 1);
+        (
+            code[..offset].matches('\n').count() as u32,
+            (offset - line_start) as u32,
+        )
+    }
+
+    /// Instruments the given document, and returns the position in it, as a zero-based line
+    /// and column, that the first occurrence of `generated` in the output maps back to.
+    pub fn original_position(id: &str, code: &str, generated: &str) -> (u32, u32) {
+        let output = transform_output(id, code);
+        let (map, _) = parse_source_map(output.map.unwrap().as_bytes()).unwrap();
+        let (line, column) = position_of(&output.code, generated);
+        let token = map.lookup_token(line, column).unwrap();
+        (token.get_src_line(), token.get_src_col())
+    }
+}
@@ -0,0 +1,212 @@
+use std::ops::Range;
+
+use super::braced_markup::{collect_braced_markup, BracedMarkupRules};
+use super::embedded_document::{
+    DocumentHeader, DocumentKind, EmbeddedDocument, EmbeddedScript, MarkupCollector,
+};
+use super::markup_scanner::{
+    attribute_value, has_attribute, is_void_element, ExpressionSyntax, MarkupScanner, MarkupToken,
+};
+
+/// Attributes that Svelte handles itself, rather than passing them to the element or component.
+const SVELTE_SPECIAL_ATTRIBUTES: &[&str] = &["slot", "this"];
+
+/// Splits a Svelte component into its instance `<script>`, its module script (i.e.
+/// `<script context="module">` or `<script module>`), and its markup, which is everything
+/// else outside of `<style>`.
+pub fn split_svelte_component(code: &str) -> EmbeddedDocument {
+    let mut scanner = MarkupScanner::new(code, 0..code.len(), ExpressionSyntax::Braces);
+    let mut instance_script: Option<EmbeddedScript> = None;
+    let mut module_script: Option<EmbeddedScript> = None;
+    // Only top-level scripts belong to the component; a `<script>` in `<svelte:head>`, for
+    // example, is just markup. End tags close the innermost open element with the same name,
+    // and any elements in it, since some end tags (e.g. `</li>`) are optional.
+    let mut open_elements: Vec<&str> = vec![];
+
+    while let Some(token) = scanner.next() {
+        match token {
+            MarkupToken::StartTag {
+                name,
+                attributes,
+                self_closing,
+                ..
+            } => {
+                let name = &code[name];
+                if self_closing || is_void_element(name) {
+                    continue;
+                }
+                if !open_elements.is_empty() || name != "script" {
+                    open_elements.push(name);
+                    continue;
+                }
+
+                let Some(MarkupToken::RawText(content)) = scanner.next() else {
+                    continue;
+                };
+                let embedded = EmbeddedScript {
+                    content,
+                    typescript: matches!(
+                        attribute_value(code, &attributes, "lang"),
+                        Some("ts" | "typescript")
+                    ),
+                    jsx: false,
+                    expression: false,
+                };
+                if has_attribute(code, &attributes, "module")
+                    || attribute_value(code, &attributes, "context") == Some("module")
+                {
+                    module_script = Some(embedded);
+                } else {
+                    instance_script = Some(embedded);
+                }
+            }
+            MarkupToken::EndTag { name, .. } => {
+                let name = &code[name];
+                if let Some(index) = open_elements.iter().rposition(|open| *open == name) {
+                    open_elements.truncate(index);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // The header usually goes in the instance script. If there's a module script, though, the
+    // header goes there instead: it can't see the instance script's bindings, but the instance
+    // script and the markup can see its bindings.
+    let header = match (&module_script, &instance_script) {
+        (Some(script), _) | (None, Some(script)) => DocumentHeader {
+            offset: script.content.start,
+            enclosing_block: None,
+            export_declarations: false,
+        },
+        (None, None) => DocumentHeader {
+            offset: 0,
+            enclosing_block: Some(("<script>".into(), "</script>\n".into())),
+            export_declarations: false,
+        },
+    };
+
+    // Scripts and styles aren't markup, but collecting markup skips their contents anyway.
+    let markup = 0..code.len();
+    EmbeddedDocument {
+        kind: DocumentKind::Svelte,
+        scripts: module_script.into_iter().chain(instance_script).collect(),
+        markup: vec![markup],
+        header,
+        unsupported: vec![],
+    }
+}
+
+/// Svelte collects text and static attributes, except in scripts and styles (including their
+/// attributes, e.g. `context="module"`) and elements whose whitespace it preserves, and except
+/// for directives (e.g. `on:click` or `bind:value`) and CSS custom properties (e.g. `--color`).
+const SVELTE_MARKUP_RULES: BracedMarkupRules = BracedMarkupRules {
+    is_uncollected_element: |_, name, _| matches!(name, "pre" | "script" | "style"),
+    is_collected_attribute: |name| {
+        !name.contains(':') && !name.starts_with("--") && !SVELTE_SPECIAL_ATTRIBUTES.contains(&name)
+    },
+    interpolates_quoted_attributes: true,
+};
+
+/// Collects the text and static attribute values in Svelte markup.
+pub fn collect_svelte_markup(collector: &mut MarkupCollector, range: Range<usize>) {
+    collect_braced_markup(collector, range, &SVELTE_MARKUP_RULES);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::frontends::tests::{original_position, position_of, transform};
+
+    use super::*;
+
+    #[test]
+    fn splits_components_into_blocks() {
+        let code = "<script context=\"module\" lang=\"ts\">export const a = 1;</script>\n<script lang=\"ts\">let b = 2;</script>\n<svelte:head><script>let c = 3;</script></svelte:head>\n<p>{a + b}</p>\n<style>p { color: red; }</style>\n";
+        let document = split_svelte_component(code);
+        assert_eq!(
+            document
+                .scripts
+                .iter()
+                .map(|script| (&code[script.content.clone()], script.typescript))
+                .collect::<Vec<_>>(),
+            vec![("export const a = 1;", true), ("let b = 2;", true)]
+        );
+        assert_eq!(document.header.offset, code.find("export const").unwrap());
+    }
+
+    #[test]
+    fn finds_scripts_after_implicitly_closed_elements() {
+        let code = "<ul>\n<li>First item here\n<li>Second item here\n</ul>\n<script>let a = 'Hello world';</script>";
+        let document = split_svelte_component(code);
+        assert_eq!(
+            document
+                .scripts
+                .iter()
+                .map(|script| &code[script.content.clone()])
+                .collect::<Vec<_>>(),
+            vec!["let a = 'Hello world';"]
+        );
+        assert_eq!(
+            transform("Component.svelte", code),
+            "<ul>\n<li>{D[1]}\n<li>{D[2]}\n</ul>\n<script>import{$}from'datadog:privacy-helpers.mjs';const D=$(['Hello world','First item here','Second item here']);let a = D[0];</script>"
+        );
+    }
+
+    #[test]
+    fn collects_markup_text_and_static_attributes() {
+        assert_eq!(
+            transform(
+                "Component.svelte",
+                "<script>\n  let name = 'World';\n</script>\n\n<h1 title=\"A friendly greeting\" class=\"title\" on:click={go}>\n  Hello {name}, welcome back!\n</h1>\n<input placeholder=\"Your name, please\" bind:value={name}>\n<p title=\"Hi {name}\">{#if name}Nice to meet you{/if}</p>\n<pre>  Kept as is  </pre>\n<style>h1::after { content: 'Not markup'; }</style>\n"
+            ),
+            "<script>import{$}from'datadog:privacy-helpers.mjs';const D=$(['World','A friendly greeting','Hello',', welcome back!','Your name, please','Nice to meet you']);\n  let name = D[0];\n</script>\n\n<h1 title={D[1]} class=\"title\" on:click={go}>\n  Hello {name}{D[3]}\n</h1>\n<input placeholder={D[4]} bind:value={name}>\n<p title=\"Hi {name}\">{#if name}{D[5]}{/if}</p>\n<pre>  Kept as is  </pre>\n<style>h1::after { content: 'Not markup'; }</style>\n"
+        );
+    }
+
+    #[test]
+    fn declares_the_dictionary_in_the_module_script_if_there_is_one() {
+        assert_eq!(
+            transform(
+                "Component.svelte",
+                "<script context=\"module\">\n  export const title = 'Shared title';\n</script>\n<script>\n  let name = 'Instance name';\n</script>\n<p>Hello there, {name}</p>\n"
+            ),
+            "<script context=\"module\">import{$}from'datadog:privacy-helpers.mjs';const D=$(['Shared title','Instance name','Hello there,']);\n  export const title = D[0];\n</script>\n<script>\n  let name = D[1];\n</script>\n<p>{D[2]} {name}</p>\n"
+        );
+    }
+
+    #[test]
+    fn adds_a_script_block_when_needed() {
+        assert_eq!(
+            transform("Component.svelte", "<p>Hello there, friend</p>\n"),
+            "<script>import{$}from'datadog:privacy-helpers.mjs';const D=$(['Hello there, friend']);</script>\n<p>{D[0]}</p>\n"
+        );
+    }
+
+    #[test]
+    fn escapes_script_end_tags_in_the_header() {
+        assert_eq!(
+            transform(
+                "Component.svelte",
+                "<script context=\"module\">\n  export const title = 'Shared title';\n</script>\n<p>Close it with &lt;/script&gt; please</p>\n"
+            ),
+            "<script context=\"module\">import{$}from'datadog:privacy-helpers.mjs';const D=$(['Shared title','Close it with <\\/script> please']);\n  export const title = D[0];\n</script>\n<p>{D[1]}</p>\n"
+        );
+    }
+
+    #[test]
+    fn maps_markup_and_script_strings_back_to_their_sources() {
+        let code = "<script>\n  let name = 'World';\n</script>\n\n<h1 title=\"A friendly greeting\">\n  {name}, welcome back!\n</h1>\n";
+        assert_eq!(
+            original_position("Component.svelte", code, "{D[1]}"),
+            position_of(code, "\"A friendly greeting\"")
+        );
+        assert_eq!(
+            original_position("Component.svelte", code, "{D[2]}"),
+            position_of(code, ", welcome back!")
+        );
+        assert_eq!(
+            original_position("Component.svelte", code, "D[0];"),
+            position_of(code, "'World'")
+        );
+    }
+}
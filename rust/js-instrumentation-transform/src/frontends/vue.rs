@@ -0,0 +1,284 @@
+use std::ops::Range;
+
+use js_instrumentation_shared::instrumentation_output::ContextCategory;
+
+use crate::rewrite::{
+    replace_attribute_with_bound_dictionary_ref, replace_mustache_text_with_dictionary_ref,
+};
+
+use super::embedded_document::{
+    DocumentHeader, DocumentKind, EmbeddedDocument, EmbeddedScript, MarkupCollector,
+};
+use super::markup_scanner::{
+    attribute_value, has_attribute, AttributeValue, ExpressionSyntax, MarkupAttribute,
+    MarkupScanner, MarkupToken,
+};
+use super::markup_strings::{collect_markup_strings, MarkupStringToken};
+
+/// Attributes that Vue handles itself, rather than passing them to the element or component.
+const VUE_SPECIAL_ATTRIBUTES: &[&str] = &["key", "ref", "is", "slot"];
+
+/// Splits a Vue single-file component into its `<script>` and `<script setup>` blocks and its
+/// `<template>`. Styles and custom blocks are left alone.
+pub fn split_vue_component(code: &str) -> EmbeddedDocument {
+    let mut scanner = MarkupScanner::new(code, 0..code.len(), ExpressionSyntax::DoubleBraces);
+    let mut script: Option<EmbeddedScript> = None;
+    let mut script_setup: Option<EmbeddedScript> = None;
+    let mut templates = vec![];
+    let mut unsupported = vec![];
+
+    while let Some(token) = scanner.next() {
+        let MarkupToken::StartTag {
+            span,
+            name,
+            attributes,
+            self_closing,
+        } = token
+        else {
+            continue;
+        };
+        if self_closing {
+            continue;
+        }
+
+        let lang = attribute_value(code, &attributes, "lang");
+        match &code[name.clone()] {
+            "template" => {
+                let content = template_content(code, &mut scanner, span.end);
+                match lang {
+                    None | Some("html") => templates.push((content, span.start)),
+                    Some(lang) => unsupported.push((
+                        format!(
+                            "a <template lang=\"{}\"> block, since its language isn't supported",
+                            lang
+                        ),
+                        span.start,
+                    )),
+                }
+            }
+            "script" => {
+                let Some(MarkupToken::RawText(content)) = scanner.next() else {
+                    continue;
+                };
+                // Scripts loaded from another file have nothing to instrument here.
+                if attribute_value(code, &attributes, "src").is_some() {
+                    continue;
+                }
+                let embedded = EmbeddedScript {
+                    content,
+                    typescript: matches!(lang, Some("ts" | "tsx")),
+                    jsx: matches!(lang, Some("jsx" | "tsx")),
+                    expression: false,
+                };
+                if has_attribute(code, &attributes, "setup") {
+                    script_setup = Some(embedded);
+                } else {
+                    script = Some(embedded);
+                }
+            }
+            // Styles and custom blocks (e.g. `<i18n>`) aren't instrumented.
+            other => scanner.skip_contents_of(other),
+        }
+    }
+
+    // The header goes in the `<script>` block if there is one, since it runs once per module;
+    // `<script setup>` runs once per component instance. Either way, the template can see
+    // the dictionary only if the component has a `<script setup>` block. Adding one to a
+    // component that only has a `<script>` block would replace any `setup()` option it has,
+    // and isn't supported before Vue 2.7, so its template is left alone.
+    let mut markup = vec![];
+    for (content, start) in templates {
+        if script.is_some() && script_setup.is_none() {
+            unsupported.push((
+                "a <template> block, since the component has no <script setup> block to expose \
+                 the dictionary to it"
+                    .into(),
+                start,
+            ));
+        } else {
+            markup.push(content);
+        }
+    }
+    let header = match script.as_ref().or(script_setup.as_ref()) {
+        Some(script) => DocumentHeader {
+            offset: script.content.start,
+            enclosing_block: None,
+            export_declarations: false,
+        },
+        None => DocumentHeader {
+            offset: 0,
+            enclosing_block: Some(("<script setup>".into(), "</script>\n".into())),
+            export_declarations: false,
+        },
+    };
+
+    EmbeddedDocument {
+        kind: DocumentKind::Vue,
+        scripts: script.into_iter().chain(script_setup).collect(),
+        markup,
+        header,
+        unsupported,
+    }
+}
+
+/// Returns the contents of a top-level `<template>` block, which may contain nested
+/// `<template>` elements, and advances the scanner past its end tag.
+fn template_content(code: &str, scanner: &mut MarkupScanner, start: usize) -> Range<usize> {
+    let mut depth = 0;
+    for token in scanner.by_ref() {
+        match token {
+            MarkupToken::StartTag {
+                name, self_closing, ..
+            } if !self_closing && &code[name.clone()] == "template" => depth += 1,
+            MarkupToken::EndTag { span, name } if &code[name.clone()] == "template" => {
+                if depth == 0 {
+                    return start..span.start;
+                }
+                depth -= 1;
+            }
+            _ => {}
+        }
+    }
+    start..code.len()
+}
+
+/// Returns true if the given attribute is a static string that we can collect, rather than a
+/// binding (e.g. `:title`), an event listener (e.g. `@click`), or a directive (e.g. `v-if`).
+fn is_collected_attribute(collector: &MarkupCollector, name: &str) -> bool {
+    !name.starts_with([':', '@', '#', '.'])
+        && !name.starts_with("v-")
+        && !VUE_SPECIAL_ATTRIBUTES.contains(&name)
+        && !collector.jsx_filter.is_uncollected_attr(name)
+}
+
+/// Collects the text and static attribute values in a Vue template. Text becomes an
+/// interpolation, e.g. `{{D[0]}}`, and attributes become bindings, e.g. `:title="D[1]"`.
+pub fn collect_vue_template(collector: &mut MarkupCollector, range: Range<usize>) {
+    let code = collector.code;
+    let tokens =
+        MarkupScanner::new(code, range, ExpressionSyntax::DoubleBraces).filter_map(|token| {
+            MarkupStringToken::from_markup(token, replace_mustache_text_with_dictionary_ref)
+        });
+    collect_markup_strings(
+        collector,
+        tokens,
+        // Vue preserves whitespace in these elements, and doesn't compile the contents of
+        // elements with `v-pre`.
+        |name, attributes| name == "pre" || has_attribute(code, attributes, "v-pre"),
+        collect_attribute,
+    );
+}
+
+fn collect_attribute(collector: &mut MarkupCollector, attribute: &MarkupAttribute) {
+    let code = collector.code;
+    let name = &code[attribute.name.clone()];
+    let Some(AttributeValue::Quoted(ref value)) = attribute.value else {
+        return;
+    };
+    if !is_collected_attribute(collector, name) {
+        return;
+    }
+
+    collector.collect_string(
+        value,
+        &attribute.span,
+        ContextCategory::JsxAttribute,
+        |index, span| replace_attribute_with_bound_dictionary_ref(name, index, span),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::frontends::tests::{original_position, position_of, transform};
+
+    use super::*;
+
+    #[test]
+    fn splits_components_into_blocks() {
+        let code = "<template>\n  <template v-if=\"a\">Hi</template>\n</template>\n<script lang=\"ts\">export default {}</script>\n<script setup lang=\"ts\">const a = 1</script>\n<i18n>{ \"<template>\": 1 }</i18n>\n<template lang=\"pug\">p Hi</template>\n";
+        let document = split_vue_component(code);
+        assert_eq!(
+            document
+                .markup
+                .iter()
+                .map(|range| &code[range.clone()])
+                .collect::<Vec<_>>(),
+            vec!["\n  <template v-if=\"a\">Hi</template>\n"]
+        );
+        assert_eq!(
+            document
+                .scripts
+                .iter()
+                .map(|script| (&code[script.content.clone()], script.typescript))
+                .collect::<Vec<_>>(),
+            vec![("export default {}", true), ("const a = 1", true)]
+        );
+        assert_eq!(document.header.offset, code.find("export default").unwrap());
+        assert_eq!(document.unsupported.len(), 1);
+        let script_code = document.script_code(code);
+        assert_eq!(script_code.len(), code.len());
+        assert_eq!(
+            script_code.split_whitespace().collect::<Vec<_>>(),
+            vec!["export", "default", "{};", "const", "a", "=", "1;"]
+        );
+    }
+
+    #[test]
+    fn collects_template_text_and_static_attributes() {
+        assert_eq!(
+            transform(
+                "Component.vue",
+                "<template>\n  <p title=\"Greeting\" class=\"greeting\" :id=\"id\">\n    Hello, {{ name }}! Welcome back\n  </p>\n  <input placeholder=\"Greeting\">\n  <pre>  Kept as is  </pre>\n</template>\n\n<script setup>\nconst name = 'World';\n</script>\n"
+            ),
+            "<template>\n  <p :title=\"D[1]\" class=\"greeting\" :id=\"id\">\n    Hello, {{ name }}{{D[3]}}\n  </p>\n  <input :placeholder=\"D[1]\">\n  <pre>  Kept as is  </pre>\n</template>\n\n<script setup>import{$}from'datadog:privacy-helpers.mjs';const D=$(['World','Greeting','Hello,','! Welcome back']);\nconst name = D[0];\n</script>\n"
+        );
+    }
+
+    #[test]
+    fn adds_a_script_block_when_needed() {
+        assert_eq!(
+            transform("Component.vue", "<template>\n  <p>Hello there</p>\n</template>\n"),
+            "<script setup>import{$}from'datadog:privacy-helpers.mjs';const D=$(['Hello there']);</script>\n<template>\n  <p>{{D[0]}}</p>\n</template>\n"
+        );
+    }
+
+    #[test]
+    fn leaves_templates_alone_without_script_setup() {
+        let code = "<template><p>Hello there</p></template>\n<script>\nexport default defineComponent({ setup() { return { a: 'General Kenobi' }; } });\n</script>\n";
+        let document = split_vue_component(code);
+        assert!(document.markup.is_empty());
+        assert_eq!(document.unsupported.len(), 1);
+        assert_eq!(
+            transform("Component.vue", code),
+            "<template><p>Hello there</p></template>\n<script>import{$}from'datadog:privacy-helpers.mjs';const D=$(['General Kenobi']);\nexport default defineComponent({ setup() { return { a: D[0] }; } });\n</script>\n"
+        );
+    }
+
+    #[test]
+    fn escapes_script_end_tags_in_the_header() {
+        assert_eq!(
+            transform(
+                "Component.vue",
+                "<template>\n  <p>Close it with <code>&lt;/script&gt;</code> tag</p>\n</template>\n"
+            ),
+            "<script setup>import{$}from'datadog:privacy-helpers.mjs';const D=$(['Close it with','<\\/script>','tag']);</script>\n<template>\n  <p>{{D[0]}} <code>{{D[1]}}</code> tag</p>\n</template>\n"
+        );
+    }
+
+    #[test]
+    fn maps_template_and_script_strings_back_to_their_sources() {
+        let code = "<template>\n  <p title=\"Greeting\">\n    Hello there\n  </p>\n</template>\n\n<script setup>\nconst name = 'World';\n</script>\n";
+        assert_eq!(
+            original_position("Component.vue", code, ":title=\"D[1]\""),
+            position_of(code, "title=")
+        );
+        assert_eq!(
+            original_position("Component.vue", code, "{{D[2]}}"),
+            position_of(code, "Hello there")
+        );
+        assert_eq!(
+            original_position("Component.vue", code, "D[0];"),
+            position_of(code, "'World'")
+        );
+    }
+}
@@ -0,0 +1,178 @@
+use js_instrumentation_shared::{InputFile, InstrumentationOptions};
+use swc_common::comments::{CommentKind, SingleThreadedComments};
+use swc_common::{BytePos, Spanned};
+use swc_ecma_ast::{Expr, ExprStmt, Lit, ModuleItem, Program, Stmt};
+
+/// Where to insert the helper import and dictionary declaration.
+pub struct HeaderPosition {
+    pub pos: BytePos,
+    /// True if the header follows a directive that isn't terminated by a semicolon, so we
+    /// need to insert one to keep the directive separate from the header.
+    pub needs_terminator: bool,
+    /// Some: the opening and closing tags of a script block to wrap the header in, for
+    /// documents like Vue components that have no script to hold it.
+    pub enclosing_block: Option<(String, String)>,
+    /// True if the header's declarations must be exported, because the document only allows
+    /// import and export statements at the top level of its scripts (e.g. MDX).
+    pub export_declarations: bool,
+    /// True if the header is embedded in markup (e.g. in a Vue component's `<script>` block),
+    /// so the string literals in it must not contain `</`, which could end the block.
+    pub escape_script_end_tags: bool,
+}
+
+/// The header must go after any shebang and any directive prologue (e.g. "use strict" or
+/// "use client"); otherwise, the directives would become ordinary expression statements.
+/// If configured, it also goes after any license comments that precede the first statement.
+pub fn compute_header_position(
+    input_file: &InputFile,
+    program: &Program,
+    comments: &SingleThreadedComments,
+    options: &InstrumentationOptions,
+) -> HeaderPosition {
+    let (has_shebang, first_stmts): (bool, Vec<&Stmt>) = match program {
+        Program::Module(ref module) => (
+            module.shebang.is_some(),
+            module
+                .body
+                .iter()
+                .map_while(|item| match item {
+                    ModuleItem::Stmt(ref stmt) => Some(stmt),
+                    _ => None,
+                })
+                .collect(),
+        ),
+        Program::Script(ref script) => (script.shebang.is_some(), script.body.iter().collect()),
+    };
+
+    let mut header_position = HeaderPosition {
+        pos: if has_shebang {
+            input_file.next_line_start(input_file.start_pos)
+        } else {
+            input_file.start_pos
+        },
+        needs_terminator: false,
+        enclosing_block: None,
+        export_declarations: false,
+        escape_script_end_tags: false,
+    };
+
+    if let Some(directive) = first_stmts
+        .into_iter()
+        .take_while(|stmt| is_directive(stmt))
+        .last()
+    {
+        let end = directive.span().hi;
+        header_position = HeaderPosition {
+            pos: end,
+            needs_terminator: !ends_with_semicolon(input_file, end),
+            enclosing_block: None,
+            export_declarations: false,
+            escape_script_end_tags: false,
+        };
+    }
+
+    if options.output.insert_header_after_license_comments {
+        // License comments may come before or after the directive prologue.
+        let first_item_pos = match program {
+            Program::Module(ref module) => module
+                .body
+                .iter()
+                .find(|item| !matches!(item, ModuleItem::Stmt(ref stmt) if is_directive(stmt)))
+                .map(|item| item.span().lo),
+            Program::Script(ref script) => script
+                .body
+                .iter()
+                .find(|stmt| !is_directive(stmt))
+                .map(|stmt| stmt.span().lo),
+        }
+        .unwrap_or(input_file.end_pos);
+
+        let (leading_comments, _) = comments.borrow_all();
+        let last_license_comment_end = leading_comments
+            .values()
+            .flatten()
+            .filter(|comment| comment.span.hi <= first_item_pos && is_license_comment(comment))
+            .map(|comment| comment.span.hi)
+            .max();
+
+        if let Some(end) = last_license_comment_end {
+            if end > header_position.pos {
+                // A directive before the comments still needs terminating.
+                header_position = HeaderPosition {
+                    pos: end,
+                    needs_terminator: header_position.needs_terminator,
+                    enclosing_block: None,
+                    export_declarations: false,
+                    escape_script_end_tags: false,
+                };
+            }
+        }
+    }
+
+    header_position
+}
+
+fn is_directive(stmt: &Stmt) -> bool {
+    match stmt {
+        Stmt::Expr(ExprStmt { ref expr, .. }) => matches!(**expr, Expr::Lit(Lit::Str(_))),
+        _ => false,
+    }
+}
+
+fn ends_with_semicolon(input_file: &InputFile, end: BytePos) -> bool {
+    let offset = (end.0 - input_file.start_pos.0) as usize;
+    offset > 0 && input_file.code.as_bytes().get(offset - 1) == Some(&b';')
+}
+
+/// License comments are block comments that start with `!`, or that contain `@license` or
+/// `@preserve`; these are the conventions minifiers use to decide which comments to keep.
+fn is_license_comment(comment: &swc_common::comments::Comment) -> bool {
+    comment.kind == CommentKind::Block
+        && (comment.text.starts_with('!')
+            || comment.text.contains("@license")
+            || comment.text.contains("@preserve"))
+}
+
+#[cfg(test)]
+mod tests {
+    use js_instrumentation_shared::InstrumentationInput;
+
+    use crate::apply_transform;
+
+    fn transform(code: &str, insert_header_after_license_comments: bool) -> String {
+        let mut options = js_instrumentation_shared::InstrumentationOptions::default();
+        options.output.insert_header_after_license_comments = insert_header_after_license_comments;
+        apply_transform(
+            &InstrumentationInput {
+                id: "input.js".into(),
+                code: code.into(),
+                map: None,
+            },
+            &options,
+        )
+        .unwrap()
+        .code
+    }
+
+    #[test]
+    fn inserts_header_after_license_comments_if_configured() {
+        let code = "/*! Copyright Example */\nconst a = 'Hello there';\n";
+        assert!(transform(code, false).starts_with("import{$}"));
+        assert_eq!(
+            transform(code, true),
+            "/*! Copyright Example */import{$}from'datadog:privacy-helpers.mjs';const D=$(['Hello there']);\nconst a = D[0];\n"
+        );
+    }
+
+    #[test]
+    fn terminates_directives_without_semicolons() {
+        assert_eq!(
+            transform("'use strict'\nconst a = 'Hello there';\n", false),
+            "'use strict';import{$}from'datadog:privacy-helpers.mjs';const D=$(['Hello there']);\nconst a = D[0];\n"
+        );
+        assert_eq!(
+            transform("'use strict'\n/*! Copyright Example */\nconst a = 'Hello there';\n", true),
+            "'use strict'\n/*! Copyright Example */;import{$}from'datadog:privacy-helpers.mjs';const D=$(['Hello there']);\nconst a = D[0];\n"
+        );
+    }
+}
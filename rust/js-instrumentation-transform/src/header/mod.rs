@@ -0,0 +1,2 @@
+mod header_position;
+pub use header_position::{compute_header_position, HeaderPosition};
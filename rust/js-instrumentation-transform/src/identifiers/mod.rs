@@ -4,3 +4,6 @@ mod character_tracker;
 
 mod identifier_tracker;
 pub use identifier_tracker::IdentifierTracker;
+
+mod script_identifier;
+pub use script_identifier::script_dictionary_identifier;
@@ -0,0 +1,52 @@
+use crate::dictionary::DEFAULT_DICTIONARY_IDENTIFIER;
+
+/// Returns a dictionary identifier that's unique to the file with the given id. Classic
+/// scripts share a single global scope, so if two instrumented scripts both declared `D`,
+/// the second would fail with "Identifier 'D' has already been declared".
+///
+/// The identifier is derived from a hash of the id, so it's stable across builds. It's still
+/// a global binding; see `ModuleKind::Script`.
+pub fn script_dictionary_identifier(id: &str) -> String {
+    format!("{}_{}", DEFAULT_DICTIONARY_IDENTIFIER, to_base36(fnv1a(id)))
+}
+
+/// The 32-bit FNV-1a hash. Unlike std's hashers, its output is guaranteed never to change.
+fn fnv1a(string: &str) -> u32 {
+    string.bytes().fold(0x811c9dc5, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x01000193)
+    })
+}
+
+fn to_base36(mut value: u32) -> String {
+    let mut digits: Vec<char> = Vec::new();
+    loop {
+        digits.push(std::char::from_digit(value % 36, 36).unwrap());
+        value /= 36;
+        if value == 0 {
+            break;
+        }
+    }
+    digits.iter().rev().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derives_stable_identifiers_from_file_ids() {
+        assert_eq!(fnv1a(""), 0x811c9dc5);
+        assert_eq!(fnv1a("a"), 0xe40c292c);
+        assert_eq!(
+            script_dictionary_identifier("a.js"),
+            script_dictionary_identifier("a.js")
+        );
+        assert_ne!(
+            script_dictionary_identifier("a.js"),
+            script_dictionary_identifier("b.js")
+        );
+        assert_eq!(script_dictionary_identifier("app.js"), "D_1e7p1md");
+        assert_eq!(to_base36(0), "0");
+        assert_eq!(to_base36(36 * 36 + 35), "10z");
+    }
+}
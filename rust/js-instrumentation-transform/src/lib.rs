@@ -1,10 +1,14 @@
+mod batch_transform;
 mod comments;
 mod dictionary;
 mod features;
+mod frontends;
+mod header;
 mod identifiers;
 mod instrumentation_transform;
 mod rewrite;
 mod source_maps;
 mod visitor;
 
+pub use batch_transform::apply_batch_transform;
 pub use instrumentation_transform::apply_transform;
@@ -2,10 +2,15 @@ mod privacy_rewrite_content;
 pub use privacy_rewrite_content::PrivacyRewriteContent;
 
 mod privacy_rewrite_declarations;
-pub use privacy_rewrite_declarations::{build_dictionary_declaration, build_helper_declaration};
+pub use privacy_rewrite_declarations::{
+    build_dictionary_declaration, build_directive_prologue_terminator,
+    build_enclosing_script_block, build_helper_declaration, build_shared_dictionary_import,
+};
 
 mod privacy_rewrite_template;
-pub use privacy_rewrite_template::{PrivacyRewriteTemplate, TemplateParameters};
+pub use privacy_rewrite_template::{
+    dictionary_reference, PrivacyRewriteTemplate, RegistrationCall, TemplateParameters,
+};
 
 mod rewrites;
 pub use rewrites::*;
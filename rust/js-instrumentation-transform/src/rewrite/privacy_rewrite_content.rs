@@ -5,9 +5,12 @@ use swc_common::BytePos;
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum PrivacyRewriteContent {
+    DirectivePrologueTerminator(String),
     HelperImport(String),
+    DictionaryImport(String),
     DictionaryDeclarationOpener(String),
     DictionaryDeclarationEntry(String, BytePos),
+    DictionaryDeclarationSeparator(String),
     DictionaryDeclarationCloser(String),
     JSXStringDictionaryReference(String),
     PropertyKeyDictionaryReference(String),
@@ -17,15 +20,23 @@ pub enum PrivacyRewriteContent {
     TaggedTemplateAfterExpr(String),
     TaggedTemplateTerminator(String),
     TemplateQuasiDictionaryReference(String),
+    MustacheDictionaryReference(String),
+    FlowTextDictionaryReference(String),
+    BoundAttributeDictionaryReference(String),
+    ScriptBlock(String),
     DeleteSourceMapComment(String),
+    ReplaceSourceMapComment(String),
 }
 
 impl RewriteContent for PrivacyRewriteContent {
     fn source_pos(self: &Self) -> Option<BytePos> {
         match self {
+            PrivacyRewriteContent::DirectivePrologueTerminator(_) => None,
             PrivacyRewriteContent::HelperImport(_) => None,
+            PrivacyRewriteContent::DictionaryImport(_) => None,
             PrivacyRewriteContent::DictionaryDeclarationOpener(_) => None,
             PrivacyRewriteContent::DictionaryDeclarationEntry(_, ref pos) => Some(pos.clone()),
+            PrivacyRewriteContent::DictionaryDeclarationSeparator(_) => None,
             PrivacyRewriteContent::DictionaryDeclarationCloser(_) => None,
             PrivacyRewriteContent::JSXStringDictionaryReference(_) => None,
             PrivacyRewriteContent::PropertyKeyDictionaryReference(_) => None,
@@ -35,7 +46,12 @@ impl RewriteContent for PrivacyRewriteContent {
             PrivacyRewriteContent::TaggedTemplateAfterExpr(_) => None,
             PrivacyRewriteContent::TaggedTemplateTerminator(_) => None,
             PrivacyRewriteContent::TemplateQuasiDictionaryReference(_) => None,
+            PrivacyRewriteContent::MustacheDictionaryReference(_) => None,
+            PrivacyRewriteContent::FlowTextDictionaryReference(_) => None,
+            PrivacyRewriteContent::BoundAttributeDictionaryReference(_) => None,
+            PrivacyRewriteContent::ScriptBlock(_) => None,
             PrivacyRewriteContent::DeleteSourceMapComment(_) => None,
+            PrivacyRewriteContent::ReplaceSourceMapComment(_) => None,
         }
     }
 }
@@ -52,9 +68,12 @@ impl PrivacyRewriteContent {
     /// are almost always a size win.
     pub fn should_only_replace_if_smaller(self: &Self) -> bool {
         match self {
+            PrivacyRewriteContent::DirectivePrologueTerminator(_) => false,
             PrivacyRewriteContent::HelperImport(_) => false,
+            PrivacyRewriteContent::DictionaryImport(_) => false,
             PrivacyRewriteContent::DictionaryDeclarationOpener(_) => false,
             PrivacyRewriteContent::DictionaryDeclarationEntry(_, _) => false,
+            PrivacyRewriteContent::DictionaryDeclarationSeparator(_) => false,
             PrivacyRewriteContent::DictionaryDeclarationCloser(_) => false,
             PrivacyRewriteContent::JSXStringDictionaryReference(_) => true,
             PrivacyRewriteContent::PropertyKeyDictionaryReference(_) => true,
@@ -64,15 +83,23 @@ impl PrivacyRewriteContent {
             PrivacyRewriteContent::TaggedTemplateAfterExpr(_) => false,
             PrivacyRewriteContent::TaggedTemplateTerminator(_) => false,
             PrivacyRewriteContent::TemplateQuasiDictionaryReference(_) => true,
+            PrivacyRewriteContent::MustacheDictionaryReference(_) => true,
+            PrivacyRewriteContent::FlowTextDictionaryReference(_) => true,
+            PrivacyRewriteContent::BoundAttributeDictionaryReference(_) => true,
+            PrivacyRewriteContent::ScriptBlock(_) => false,
             PrivacyRewriteContent::DeleteSourceMapComment(_) => false,
+            PrivacyRewriteContent::ReplaceSourceMapComment(_) => false,
         }
     }
 
     pub fn len(self: &Self) -> usize {
         match self {
+            PrivacyRewriteContent::DirectivePrologueTerminator(string) => string.len(),
             PrivacyRewriteContent::HelperImport(string) => string.len(),
+            PrivacyRewriteContent::DictionaryImport(string) => string.len(),
             PrivacyRewriteContent::DictionaryDeclarationOpener(string) => string.len(),
             PrivacyRewriteContent::DictionaryDeclarationEntry(string, _) => string.len(),
+            PrivacyRewriteContent::DictionaryDeclarationSeparator(string) => string.len(),
             PrivacyRewriteContent::DictionaryDeclarationCloser(string) => string.len(),
             PrivacyRewriteContent::JSXStringDictionaryReference(string) => string.len(),
             PrivacyRewriteContent::PropertyKeyDictionaryReference(string) => string.len(),
@@ -82,7 +109,12 @@ impl PrivacyRewriteContent {
             PrivacyRewriteContent::TaggedTemplateAfterExpr(string) => string.len(),
             PrivacyRewriteContent::TaggedTemplateTerminator(string) => string.len(),
             PrivacyRewriteContent::TemplateQuasiDictionaryReference(string) => string.len(),
+            PrivacyRewriteContent::MustacheDictionaryReference(string) => string.len(),
+            PrivacyRewriteContent::FlowTextDictionaryReference(string) => string.len(),
+            PrivacyRewriteContent::BoundAttributeDictionaryReference(string) => string.len(),
+            PrivacyRewriteContent::ScriptBlock(string) => string.len(),
             PrivacyRewriteContent::DeleteSourceMapComment(string) => string.len(),
+            PrivacyRewriteContent::ReplaceSourceMapComment(string) => string.len(),
         }
     }
 }
@@ -90,9 +122,14 @@ impl PrivacyRewriteContent {
 impl Display for PrivacyRewriteContent {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            PrivacyRewriteContent::DirectivePrologueTerminator(string) => write!(f, "{}", string),
             PrivacyRewriteContent::HelperImport(string) => write!(f, "{}", string),
+            PrivacyRewriteContent::DictionaryImport(string) => write!(f, "{}", string),
             PrivacyRewriteContent::DictionaryDeclarationOpener(string) => write!(f, "{}", string),
             PrivacyRewriteContent::DictionaryDeclarationEntry(string, _) => write!(f, "{}", string),
+            PrivacyRewriteContent::DictionaryDeclarationSeparator(string) => {
+                write!(f, "{}", string)
+            }
             PrivacyRewriteContent::DictionaryDeclarationCloser(string) => write!(f, "{}", string),
             PrivacyRewriteContent::JSXStringDictionaryReference(string) => write!(f, "{}", string),
             PrivacyRewriteContent::PropertyKeyDictionaryReference(string) => {
@@ -108,7 +145,14 @@ impl Display for PrivacyRewriteContent {
             PrivacyRewriteContent::TemplateQuasiDictionaryReference(string) => {
                 write!(f, "{}", string)
             }
+            PrivacyRewriteContent::MustacheDictionaryReference(string) => write!(f, "{}", string),
+            PrivacyRewriteContent::FlowTextDictionaryReference(string) => write!(f, "{}", string),
+            PrivacyRewriteContent::BoundAttributeDictionaryReference(string) => {
+                write!(f, "{}", string)
+            }
+            PrivacyRewriteContent::ScriptBlock(string) => write!(f, "{}", string),
             PrivacyRewriteContent::DeleteSourceMapComment(string) => write!(f, "{}", string),
+            PrivacyRewriteContent::ReplaceSourceMapComment(string) => write!(f, "{}", string),
         }
     }
 }
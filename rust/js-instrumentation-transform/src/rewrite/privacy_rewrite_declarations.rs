@@ -1,13 +1,49 @@
 use std::fmt::Write;
 
 use js_instrumentation_rewrite::rewrite::Rewrite;
-use js_instrumentation_shared::{instrumentation_options::HelperFunctionSource, ModuleKind};
+use js_instrumentation_shared::{
+    instrumentation_options::{DictionaryDeclaration, HelperFunctionSource},
+    ModuleKind,
+};
 use swc_common::BytePos;
 
-use crate::{dictionary::DictionaryEntry, rewrite::TemplateParameters};
+use crate::{
+    dictionary::{
+        canonical_string_literal, escape_script_end_tags, DictionaryEntry,
+        DEFAULT_DICTIONARY_IDENTIFIER,
+    },
+    rewrite::{RegistrationCall, TemplateParameters},
+};
 
 use super::PrivacyRewriteContent;
 
+pub fn build_directive_prologue_terminator(pos: BytePos) -> Vec<Rewrite<PrivacyRewriteContent>> {
+    vec![Rewrite::Insert {
+        content: PrivacyRewriteContent::DirectivePrologueTerminator(";".to_string()),
+        pos,
+    }]
+}
+
+/// Wraps the header in a script block of its own, for documents like Vue components that have
+/// no script to hold it.
+pub fn build_enclosing_script_block(
+    pos: BytePos,
+    opener: &str,
+    closer: &str,
+    header: Vec<Rewrite<PrivacyRewriteContent>>,
+) -> Vec<Rewrite<PrivacyRewriteContent>> {
+    std::iter::once(Rewrite::Insert {
+        content: PrivacyRewriteContent::ScriptBlock(opener.to_string()),
+        pos,
+    })
+    .chain(header)
+    .chain(std::iter::once(Rewrite::Insert {
+        content: PrivacyRewriteContent::ScriptBlock(closer.to_string()),
+        pos,
+    }))
+    .collect()
+}
+
 pub fn build_helper_declaration(
     pos: BytePos,
     params: &TemplateParameters,
@@ -19,63 +55,155 @@ pub fn build_helper_declaration(
         return declaration;
     }
 
+    // The helper may need to be declared as part of the dictionary declaration instead.
+    if !helper_declared_at_top_level(params) {
+        return declaration;
+    }
+
+    declaration.push(Rewrite::Insert {
+        content: PrivacyRewriteContent::HelperImport(helper_declaration_code(params)),
+        pos,
+    });
+
+    return declaration;
+}
+
+fn helper_declared_at_top_level(params: &TemplateParameters) -> bool {
+    match (&params.module_kind, &params.dictionary_declaration) {
+        // A classic script's top-level bindings are shared with every other script, so the
+        // helper is kept out of the top-level scope entirely.
+        (ModuleKind::Script, _) => false,
+        (_, DictionaryDeclaration::Const) => true,
+        // When the dictionary is declared as a function, the helper must be available
+        // whenever the function is called, so any helper declaration that isn't hoisted
+        // (i.e. anything but an ES module import) is moved into the function body.
+        (_, DictionaryDeclaration::Function) => matches!(
+            (&params.add_to_dictionary_helper_source, &params.module_kind),
+            (HelperFunctionSource::Import { .. }, ModuleKind::ESM)
+        ),
+    }
+}
+
+/// Returns an expression that evaluates to the helper, if it can be obtained without an
+/// import.
+fn helper_value_code(params: &TemplateParameters) -> Option<String> {
+    match &params.add_to_dictionary_helper_source {
+        HelperFunctionSource::Expression { code } => Some(code.clone()),
+        HelperFunctionSource::Global { path } => Some(format!("globalThis.{}", path)),
+        HelperFunctionSource::Import { .. } => None,
+    }
+}
+
+fn helper_declaration_code(params: &TemplateParameters) -> String {
+    let helper = &params.add_to_dictionary_helper_identifier;
     match &params.add_to_dictionary_helper_source {
-        HelperFunctionSource::Expression { code } => {
-            declaration.push(Rewrite::Insert {
-                content: PrivacyRewriteContent::HelperImport(format!(
-                    "const {}={};",
-                    params.add_to_dictionary_helper_identifier, code
-                )),
-                pos,
-            });
-        }
         HelperFunctionSource::Import {
             cjs_module,
             esm_module,
             func,
         } => match params.module_kind {
-            ModuleKind::CJS if &params.add_to_dictionary_helper_identifier == func => {
-                declaration.push(Rewrite::Insert {
-                    content: PrivacyRewriteContent::HelperImport(format!(
-                        "const{{{}}}=require('{}');",
-                        params.add_to_dictionary_helper_identifier, cjs_module,
-                    )),
-                    pos,
-                });
-            }
-            ModuleKind::CJS => {
-                declaration.push(Rewrite::Insert {
-                    content: PrivacyRewriteContent::HelperImport(format!(
-                        "const{{{}:{}}}=require('{}');",
-                        func, params.add_to_dictionary_helper_identifier, cjs_module,
-                    )),
-                    pos,
-                });
-            }
-            ModuleKind::ESM if &params.add_to_dictionary_helper_identifier == func => {
-                declaration.push(Rewrite::Insert {
-                    content: PrivacyRewriteContent::HelperImport(format!(
-                        "import{{{}}}from'{}';",
-                        params.add_to_dictionary_helper_identifier, esm_module,
-                    )),
-                    pos,
-                });
+            ModuleKind::CJS if helper == func => {
+                format!("const{{{}}}=require('{}');", helper, cjs_module)
             }
-            ModuleKind::ESM => {
-                declaration.push(Rewrite::Insert {
-                    content: PrivacyRewriteContent::HelperImport(format!(
-                        "import{{{} as {}}}from'{}';",
-                        func, params.add_to_dictionary_helper_identifier, esm_module,
-                    )),
-                    pos,
-                });
+            ModuleKind::CJS => format!("const{{{}:{}}}=require('{}');", func, helper, cjs_module),
+            ModuleKind::ESM if helper == func => {
+                format!("import{{{}}}from'{}';", helper, esm_module)
             }
+            ModuleKind::ESM => format!("import{{{} as {}}}from'{}';", func, helper, esm_module),
+            // Scripts can't import anything; this combination is rejected before rewriting.
+            ModuleKind::Script => String::new(),
         },
+        _ => format!(
+            "{}const {}={};",
+            export_keyword(params),
+            helper,
+            helper_value_code(params).unwrap_or_default()
+        ),
+    }
+}
+
+fn export_keyword(params: &TemplateParameters) -> &'static str {
+    if params.export_declarations {
+        "export "
+    } else {
+        ""
+    }
+}
+
+pub fn build_shared_dictionary_import(
+    pos: BytePos,
+    params: &TemplateParameters,
+    shared_dictionary_id: &str,
+) -> Vec<Rewrite<PrivacyRewriteContent>> {
+    let mut declaration: Vec<Rewrite<PrivacyRewriteContent>> = Vec::new();
+
+    // Don't generate an import statement if we didn't collect any strings.
+    if params.dictionary.strings.is_empty() {
+        return declaration;
+    }
+
+    // The shared dictionary module always exports the dictionary using the default
+    // identifier; rename it on import if that identifier is already in use in this file.
+    let exported = DEFAULT_DICTIONARY_IDENTIFIER;
+    let local = &params.dictionary_identifier;
+    let specifier = canonical_string_literal(shared_dictionary_id);
+    let content = match params.module_kind {
+        // require() calls aren't hoisted, so when the dictionary may be accessed before the
+        // module body runs, it's looked up from the shared module on each access instead.
+        ModuleKind::CJS if params.dictionary_declaration == DictionaryDeclaration::Function => {
+            format!(
+                "function {}(){{return require({}).{}}}",
+                local, specifier, exported
+            )
+        }
+        ModuleKind::CJS if local == exported => {
+            format!("const{{{}}}=require({});", local, specifier)
+        }
+        ModuleKind::CJS => format!("const{{{}:{}}}=require({});", exported, local, specifier),
+        ModuleKind::ESM if local == exported => format!("import{{{}}}from{};", local, specifier),
+        ModuleKind::ESM => format!("import{{{} as {}}}from{};", exported, local, specifier),
+        // The shared dictionary script stores the dictionary in a global keyed by its id.
+        ModuleKind::Script => format!("const {}=globalThis[{}];", local, specifier),
     };
+    declaration.push(Rewrite::Insert {
+        content: PrivacyRewriteContent::DictionaryImport(content),
+        pos,
+    });
 
     return declaration;
 }
 
+/// Returns the code for the helper's arguments after the entries: the entries' words, if they
+/// were split at build time, and their category, if the dictionary is registered one category
+/// at a time. `0` stands in for the words when only the category is passed.
+fn registration_arguments_code(params: &TemplateParameters, call: &RegistrationCall) -> String {
+    let mut code = String::new();
+    match &call.words {
+        Some(words) => {
+            let words: Vec<String> = words
+                .iter()
+                .map(|word| literal_code(params, &canonical_string_literal(word)))
+                .collect();
+            let _ = write!(&mut code, ",[{}]", words.join(","));
+        }
+        None if call.category.is_some() => code.push_str(",0"),
+        None => {}
+    }
+    if let Some(category) = call.category {
+        let _ = write!(&mut code, ",'{}'", category);
+    }
+    code
+}
+
+/// Returns the code for a string or template literal in the header.
+fn literal_code(params: &TemplateParameters, literal: &str) -> String {
+    if params.escape_script_end_tags {
+        escape_script_end_tags(literal)
+    } else {
+        literal.to_string()
+    }
+}
+
 pub fn build_dictionary_declaration(
     pos: BytePos,
     params: &TemplateParameters,
@@ -87,66 +215,124 @@ pub fn build_dictionary_declaration(
         return declaration;
     }
 
+    let dictionary = &params.dictionary_identifier;
+    let helper = &params.add_to_dictionary_helper_identifier;
+    // The code around the expression that registers the dictionary's entries.
+    let (prefix, suffix) = match params.dictionary_declaration {
+        // In a classic script, the helper is passed to an immediately-invoked function so
+        // that it doesn't become a global binding.
+        DictionaryDeclaration::Const if params.module_kind == ModuleKind::Script => (
+            format!("const {}=({}=>", dictionary, helper),
+            format!(")({});", helper_value_code(params).unwrap_or_default()),
+        ),
+        DictionaryDeclaration::Const => (format!("const {}=", dictionary), ";".to_string()),
+        // A function declaration is hoisted, so unlike a `const`, it can be called before the
+        // module body runs (e.g. from another module in an import cycle). The dictionary is
+        // built on the first call and cached as a property of the function.
+        DictionaryDeclaration::Function => {
+            let helper_declaration = if !helper_declared_at_top_level(params) {
+                helper_declaration_code(params)
+            } else {
+                String::new()
+            };
+            (
+                format!(
+                    "function {}(){{if(!{}.d){{{}{}.d=",
+                    dictionary, dictionary, helper_declaration, dictionary
+                ),
+                format!(";}}return {}.d}}", dictionary),
+            )
+        }
+    };
+    // When the dictionary is registered one category at a time, the helper returns each
+    // category's entries, and they're spread into a single array.
+    let (array_opener, array_closer) = if params.dictionary.grouped_by_category {
+        ("[...", "]")
+    } else {
+        ("", "")
+    };
+
     declaration.push(Rewrite::Insert {
         content: PrivacyRewriteContent::DictionaryDeclarationOpener(format!(
-            "const {}={}([",
-            params.dictionary_identifier, params.add_to_dictionary_helper_identifier
+            "{}{}{}{}([",
+            export_keyword(params),
+            prefix,
+            array_opener,
+            helper
         )),
         pos,
     });
 
-    let mut follows_another_entry = false;
-    for index in &params.dictionary.indices {
-        match params.dictionary.strings.get_index(*index) {
-            Some((atom, stats)) => {
-                let mut output = String::new();
+    let mut previous_call: Option<&RegistrationCall> = None;
+    for call in &params.registration_calls {
+        if let Some(previous_call) = previous_call {
+            declaration.push(Rewrite::Insert {
+                content: PrivacyRewriteContent::DictionaryDeclarationSeparator(format!(
+                    "]{}),...{}([",
+                    registration_arguments_code(params, previous_call),
+                    helper
+                )),
+                pos,
+            });
+        }
+        previous_call = Some(call);
 
-                if follows_another_entry {
-                    let _ = write!(&mut output, "{}", ",");
-                }
+        let mut follows_another_entry = false;
+        for index in &call.indices {
+            match params.dictionary.strings.get_index(*index) {
+                Some((atom, stats)) => {
+                    let mut output = String::new();
 
-                match atom {
-                    DictionaryEntry::String(string) => {
-                        let _ = write!(&mut output, "{}", string);
+                    if follows_another_entry {
+                        let _ = write!(&mut output, "{}", ",");
                     }
-                    DictionaryEntry::TaggedTemplate(quasis) => {
-                        let _ = write!(
-                            &mut output,
-                            "{}`",
-                            params.add_to_dictionary_helper_identifier
-                        );
-                        let mut need_separator = false;
-                        for quasi in quasis {
-                            if need_separator {
-                                let _ = write!(&mut output, "{}", "${0}");
-                            } else {
-                                need_separator = true;
+
+                    match atom {
+                        DictionaryEntry::String(string) => {
+                            let _ = write!(&mut output, "{}", literal_code(params, string));
+                        }
+                        DictionaryEntry::TaggedTemplate(quasis) => {
+                            let _ = write!(&mut output, "{}`", helper);
+                            let mut need_separator = false;
+                            for quasi in quasis {
+                                if need_separator {
+                                    let _ = write!(&mut output, "{}", "${0}");
+                                } else {
+                                    need_separator = true;
+                                }
+                                let _ = write!(&mut output, "{}", quasi.as_str());
                             }
-                            let _ = write!(&mut output, "{}", quasi.as_str());
+                            let _ = write!(&mut output, "{}", "`");
+                        }
+                        DictionaryEntry::TemplateQuasi(quasi) => {
+                            let literal = format!("`{}`", quasi.as_str());
+                            let _ = write!(&mut output, "{}", literal_code(params, &literal));
                         }
-                        let _ = write!(&mut output, "{}", "`");
-                    }
-                    DictionaryEntry::TemplateQuasi(quasi) => {
-                        let _ = write!(&mut output, "`{}`", quasi.as_str());
                     }
-                }
 
-                follows_another_entry = true;
+                    follows_another_entry = true;
 
-                declaration.push(Rewrite::Insert {
-                    content: PrivacyRewriteContent::DictionaryDeclarationEntry(
-                        output,
-                        stats.first_pos,
-                    ),
-                    pos,
-                });
+                    declaration.push(Rewrite::Insert {
+                        content: PrivacyRewriteContent::DictionaryDeclarationEntry(
+                            output,
+                            stats.first_pos,
+                        ),
+                        pos,
+                    });
+                }
+                None => {}
             }
-            None => {}
         }
     }
 
+    let arguments = previous_call
+        .map(|call| registration_arguments_code(params, call))
+        .unwrap_or_default();
     declaration.push(Rewrite::Insert {
-        content: PrivacyRewriteContent::DictionaryDeclarationCloser("]);".to_string()),
+        content: PrivacyRewriteContent::DictionaryDeclarationCloser(format!(
+            "]{}){}{}",
+            arguments, array_closer, suffix
+        )),
         pos,
     });
 
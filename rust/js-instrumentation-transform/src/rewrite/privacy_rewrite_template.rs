@@ -1,10 +1,15 @@
+use std::collections::BTreeSet;
 use std::fmt::Display;
 
 use js_instrumentation_rewrite::rewrite_content::RewriteContent;
-use js_instrumentation_shared::{instrumentation_options::HelperFunctionSource, ModuleKind};
+use js_instrumentation_shared::{
+    instrumentation_options::{DictionaryDeclaration, HelperFunctionSource},
+    instrumentation_output::ContextCategory,
+    ModuleKind,
+};
 use swc_common::BytePos;
 
-use crate::dictionary::{DictionaryError, OptimizedDictionary};
+use crate::dictionary::{DictionaryError, EntryReference, OptimizedDictionary, WordTokenizer};
 
 use super::PrivacyRewriteContent;
 
@@ -24,7 +29,16 @@ pub enum PrivacyRewriteTemplate {
     TaggedTemplateAfterExpr,
     TaggedTemplateTerminator,
     TemplateQuasiDictionaryReference(usize),
+    /// Text in a Vue template.
+    MustacheDictionaryReference(usize),
+    /// Text that makes up a whole line of MDX prose. An empty expression follows the
+    /// reference; otherwise, MDX would treat the reference as a block of its own, taking the
+    /// text out of its paragraph.
+    FlowTextDictionaryReference(usize),
+    /// A static attribute in a Vue template, with the attribute's name.
+    BoundAttributeDictionaryReference(String, usize),
     DeleteSourceMapComment,
+    ReplaceSourceMapComment(String),
 }
 
 impl RewriteContent for PrivacyRewriteTemplate {
@@ -36,30 +50,201 @@ impl RewriteContent for PrivacyRewriteTemplate {
 pub struct TemplateParameters<'a> {
     pub dictionary: OptimizedDictionary,
     pub dictionary_identifier: String,
+    /// The expression used to refer to the dictionary; see `dictionary_reference()`.
+    pub dictionary_reference: String,
+    pub dictionary_declaration: DictionaryDeclaration,
     pub add_to_dictionary_helper_source: &'a HelperFunctionSource,
     pub add_to_dictionary_helper_identifier: String,
     pub module_kind: ModuleKind,
+    /// True if the header's declarations must be exported; see `HeaderPosition`.
+    pub export_declarations: bool,
+    /// True if `</` must be escaped in the header's string literals; see `HeaderPosition`.
+    pub escape_script_end_tags: bool,
+    /// The calls to the helper that register the dictionary's entries.
+    pub registration_calls: Vec<RegistrationCall>,
+}
+
+/// A call to the add-to-dictionary helper that registers some of the dictionary's entries.
+pub struct RegistrationCall {
+    /// The category of the entries, if the dictionary is registered one category at a time.
+    pub category: Option<ContextCategory>,
+    /// The entries to register, as indices into the dictionary's strings, in dictionary order.
+    pub indices: Vec<usize>,
+    /// Some: the words in the entries, split at build time, to pass to the helper.
+    pub words: Option<Vec<String>>,
 }
 
 impl<'a> TemplateParameters<'a> {
     pub fn new(
         dictionary: OptimizedDictionary,
         dictionary_identifier: String,
-        add_to_dictionary_helper_source: &HelperFunctionSource,
+        dictionary_declaration: DictionaryDeclaration,
+        add_to_dictionary_helper_source: &'a HelperFunctionSource,
         add_to_dictionary_helper_identifier: String,
         module_kind: ModuleKind,
-    ) -> TemplateParameters<'_> {
+        word_tokenizer: Option<&WordTokenizer>,
+    ) -> TemplateParameters<'a> {
         TemplateParameters {
+            registration_calls: registration_calls(&dictionary, word_tokenizer),
             dictionary,
+            dictionary_reference: dictionary_reference(
+                &dictionary_identifier,
+                &dictionary_declaration,
+            ),
             dictionary_identifier,
+            dictionary_declaration,
             add_to_dictionary_helper_source,
             add_to_dictionary_helper_identifier,
             module_kind,
+            export_declarations: false,
+            escape_script_end_tags: false,
         }
     }
 }
 
+/// Returns the calls to the helper that register the dictionary's entries: a single call, or,
+/// if the dictionary is grouped by category, one call per category.
+fn registration_calls(
+    dictionary: &OptimizedDictionary,
+    word_tokenizer: Option<&WordTokenizer>,
+) -> Vec<RegistrationCall> {
+    if !dictionary.grouped_by_category {
+        return vec![RegistrationCall {
+            category: None,
+            indices: dictionary.indices.clone(),
+            words: word_tokenizer.map(|tokenizer| tokenizer.dictionary_words(dictionary)),
+        }];
+    }
+
+    // Entries left out of the dictionary still contribute their words, so a category may
+    // need a call even if none of its entries are in the dictionary.
+    let categories: BTreeSet<ContextCategory> = dictionary
+        .strings
+        .values()
+        .map(|stats| stats.category())
+        .collect();
+
+    categories
+        .into_iter()
+        .map(|category| {
+            let in_category = |index: &usize| {
+                dictionary
+                    .strings
+                    .get_index(*index)
+                    .is_some_and(|(_, stats)| stats.category() == category)
+            };
+            let indices: Vec<usize> = dictionary
+                .indices
+                .iter()
+                .copied()
+                .filter(in_category)
+                .collect();
+            let words = word_tokenizer.map(|tokenizer| {
+                let omitted = dictionary.omitted_indices().filter(in_category);
+                tokenizer.entry_words(dictionary, indices.iter().copied().chain(omitted))
+            });
+            RegistrationCall {
+                category: Some(category),
+                indices,
+                words,
+            }
+        })
+        .collect()
+}
+
+/// Returns the expression that dictionary references should use to access the dictionary.
+/// When the dictionary is declared as a function, it must be called to obtain the array.
+pub fn dictionary_reference(
+    dictionary_identifier: &str,
+    dictionary_declaration: &DictionaryDeclaration,
+) -> String {
+    match dictionary_declaration {
+        DictionaryDeclaration::Const => dictionary_identifier.to_string(),
+        DictionaryDeclaration::Function => format!("{}()", dictionary_identifier),
+    }
+}
+
 impl PrivacyRewriteTemplate {
+    /// Describes the dictionary reference that this template produces, if any, so that the
+    /// dictionary can be ordered to minimize the size of the output. The fixed lengths here
+    /// must match the templates in `evaluate()`.
+    pub fn entry_reference(self: &Self, replaced_len: usize) -> Option<EntryReference> {
+        let (index, fixed_len, only_if_smaller) = match self {
+            // {D[0]}
+            PrivacyRewriteTemplate::JSXStringDictionaryReference(index) => (index, 4, true),
+            // [D[0]]
+            PrivacyRewriteTemplate::PropertyKeyDictionaryReference(index) => (index, 4, true),
+            // ␣D[0]
+            PrivacyRewriteTemplate::StringDictionaryReference(index, LeftContext::MaybeKeyword) => {
+                (index, 3, true)
+            }
+            // D[0]
+            PrivacyRewriteTemplate::StringDictionaryReference(index, LeftContext::NonKeyword) => {
+                (index, 2, true)
+            }
+            // (D[0]
+            PrivacyRewriteTemplate::TaggedTemplateOpenerDictionaryReference(index) => {
+                (index, 3, false)
+            }
+            // ${D[0]}
+            PrivacyRewriteTemplate::TemplateQuasiDictionaryReference(index) => (index, 5, true),
+            // {{D[0]}}
+            PrivacyRewriteTemplate::MustacheDictionaryReference(index) => (index, 6, true),
+            // {D[0]}{}
+            PrivacyRewriteTemplate::FlowTextDictionaryReference(index) => (index, 6, true),
+            // :name="D[0]"
+            PrivacyRewriteTemplate::BoundAttributeDictionaryReference(name, index) => {
+                (index, name.len() + 6, true)
+            }
+            _ => return None,
+        };
+        Some(EntryReference {
+            index: *index,
+            replaced_len,
+            fixed_len,
+            only_if_smaller,
+        })
+    }
+
+    /// Returns this template with its dictionary index replaced according to `index_map`, or
+    /// None if the entry it refers to was removed from the dictionary.
+    pub fn remap_index(self: &Self, index_map: &[Option<usize>]) -> Option<PrivacyRewriteTemplate> {
+        let remap = |index: &usize| index_map.get(*index).copied().flatten();
+        Some(match self {
+            PrivacyRewriteTemplate::JSXStringDictionaryReference(index) => {
+                PrivacyRewriteTemplate::JSXStringDictionaryReference(remap(index)?)
+            }
+            PrivacyRewriteTemplate::PropertyKeyDictionaryReference(index) => {
+                PrivacyRewriteTemplate::PropertyKeyDictionaryReference(remap(index)?)
+            }
+            PrivacyRewriteTemplate::StringDictionaryReference(index, left_context) => {
+                PrivacyRewriteTemplate::StringDictionaryReference(
+                    remap(index)?,
+                    left_context.clone(),
+                )
+            }
+            PrivacyRewriteTemplate::TaggedTemplateOpenerDictionaryReference(index) => {
+                PrivacyRewriteTemplate::TaggedTemplateOpenerDictionaryReference(remap(index)?)
+            }
+            PrivacyRewriteTemplate::TemplateQuasiDictionaryReference(index) => {
+                PrivacyRewriteTemplate::TemplateQuasiDictionaryReference(remap(index)?)
+            }
+            PrivacyRewriteTemplate::MustacheDictionaryReference(index) => {
+                PrivacyRewriteTemplate::MustacheDictionaryReference(remap(index)?)
+            }
+            PrivacyRewriteTemplate::FlowTextDictionaryReference(index) => {
+                PrivacyRewriteTemplate::FlowTextDictionaryReference(remap(index)?)
+            }
+            PrivacyRewriteTemplate::BoundAttributeDictionaryReference(name, index) => {
+                PrivacyRewriteTemplate::BoundAttributeDictionaryReference(
+                    name.clone(),
+                    remap(index)?,
+                )
+            }
+            other => other.clone(),
+        })
+    }
+
     pub fn evaluate(
         self: &Self,
         params: &TemplateParameters,
@@ -68,14 +253,14 @@ impl PrivacyRewriteTemplate {
             PrivacyRewriteTemplate::JSXStringDictionaryReference(index) => Ok(
                 PrivacyRewriteContent::JSXStringDictionaryReference(format!(
                     "{{{}[{}]}}",
-                    params.dictionary_identifier,
+                    params.dictionary_reference,
                     params.dictionary.entry_for_index(*index)?
                 )),
             ),
             PrivacyRewriteTemplate::PropertyKeyDictionaryReference(index) => Ok(
                 PrivacyRewriteContent::PropertyKeyDictionaryReference(format!(
                     "[{}[{}]]",
-                    params.dictionary_identifier,
+                    params.dictionary_reference,
                     params.dictionary.entry_for_index(*index)?
                 )),
             ),
@@ -88,14 +273,14 @@ impl PrivacyRewriteTemplate {
                     LeftContext::MaybeKeyword => {
                         PrivacyRewriteContent::StringDictionaryReference(format!(
                             " {}[{}]",
-                            params.dictionary_identifier,
+                            params.dictionary_reference,
                             params.dictionary.entry_for_index(*index)?
                         ))
                     }
                     LeftContext::NonKeyword => {
                         PrivacyRewriteContent::StringDictionaryReference(format!(
                             "{}[{}]",
-                            params.dictionary_identifier,
+                            params.dictionary_reference,
                             params.dictionary.entry_for_index(*index)?
                         ))
                     }
@@ -104,7 +289,7 @@ impl PrivacyRewriteTemplate {
             PrivacyRewriteTemplate::TaggedTemplateOpenerDictionaryReference(index) => Ok(
                 PrivacyRewriteContent::TaggedTemplateOpenerDictionaryReference(format!(
                     "({}[{}]",
-                    params.dictionary_identifier,
+                    params.dictionary_reference,
                     params.dictionary.entry_for_index(*index)?
                 )),
             ),
@@ -120,13 +305,41 @@ impl PrivacyRewriteTemplate {
             PrivacyRewriteTemplate::TemplateQuasiDictionaryReference(index) => Ok(
                 PrivacyRewriteContent::TemplateQuasiDictionaryReference(format!(
                     "${{{}[{}]}}",
-                    params.dictionary_identifier,
+                    params.dictionary_reference,
+                    params.dictionary.entry_for_index(*index)?
+                )),
+            ),
+            PrivacyRewriteTemplate::MustacheDictionaryReference(index) => {
+                Ok(PrivacyRewriteContent::MustacheDictionaryReference(format!(
+                    "{{{{{}[{}]}}}}",
+                    params.dictionary_reference,
+                    params.dictionary.entry_for_index(*index)?
+                )))
+            }
+            PrivacyRewriteTemplate::FlowTextDictionaryReference(index) => {
+                Ok(PrivacyRewriteContent::FlowTextDictionaryReference(format!(
+                    "{{{}[{}]}}{{}}",
+                    params.dictionary_reference,
+                    params.dictionary.entry_for_index(*index)?
+                )))
+            }
+            PrivacyRewriteTemplate::BoundAttributeDictionaryReference(name, index) => Ok(
+                PrivacyRewriteContent::BoundAttributeDictionaryReference(format!(
+                    ":{}=\"{}[{}]\"",
+                    name,
+                    params.dictionary_reference,
                     params.dictionary.entry_for_index(*index)?
                 )),
             ),
             PrivacyRewriteTemplate::DeleteSourceMapComment => Ok(
                 PrivacyRewriteContent::DeleteSourceMapComment("".to_string()),
             ),
+            PrivacyRewriteTemplate::ReplaceSourceMapComment(url) => {
+                Ok(PrivacyRewriteContent::ReplaceSourceMapComment(format!(
+                    "//# sourceMappingURL={}",
+                    url
+                )))
+            }
         }
     }
 }
@@ -44,6 +44,40 @@ pub fn replace_jsx_string_with_dictionary_ref(
     }
 }
 
+pub fn replace_mustache_text_with_dictionary_ref(
+    dictionary_index: usize,
+    span: Span,
+) -> Rewrite<PrivacyRewriteTemplate> {
+    Rewrite::Replace {
+        content: PrivacyRewriteTemplate::MustacheDictionaryReference(dictionary_index),
+        span,
+    }
+}
+
+pub fn replace_flow_text_with_dictionary_ref(
+    dictionary_index: usize,
+    span: Span,
+) -> Rewrite<PrivacyRewriteTemplate> {
+    Rewrite::Replace {
+        content: PrivacyRewriteTemplate::FlowTextDictionaryReference(dictionary_index),
+        span,
+    }
+}
+
+pub fn replace_attribute_with_bound_dictionary_ref(
+    name: &str,
+    dictionary_index: usize,
+    span: Span,
+) -> Rewrite<PrivacyRewriteTemplate> {
+    Rewrite::Replace {
+        content: PrivacyRewriteTemplate::BoundAttributeDictionaryReference(
+            name.to_string(),
+            dictionary_index,
+        ),
+        span,
+    }
+}
+
 pub fn replace_property_key_with_dictionary_ref(
     dictionary_index: usize,
     span: Span,
@@ -88,3 +122,10 @@ pub fn delete_source_map_comment(span: Span) -> Rewrite<PrivacyRewriteTemplate>
         span,
     }
 }
+
+pub fn replace_source_map_comment(span: Span, url: &str) -> Rewrite<PrivacyRewriteTemplate> {
+    Rewrite::Replace {
+        content: PrivacyRewriteTemplate::ReplaceSourceMapComment(url.to_string()),
+        span,
+    }
+}
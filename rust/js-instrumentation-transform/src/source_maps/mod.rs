@@ -1,18 +1,82 @@
 use std::io::BufWriter;
 
 use anyhow::Result;
-use js_instrumentation_shared::debug_log;
-use swc_common::Span;
-use swc_core::base::sourcemap::SourceMap;
+use js_instrumentation_rewrite::rewrite::Rewrite;
+use js_instrumentation_shared::instrumentation_options::{
+    ExternalSourceMapComment, InputOptions, OutputOptions,
+};
+use js_instrumentation_shared::{Diagnostic, DiagnosticCode, InputFile};
+use swc_core::base::sourcemap::{
+    decode_slice, DecodedMap, SourceMap, SourceMapIndex, SourceMapSection,
+};
 
 use crate::comments::SourceMapComment;
+use crate::rewrite::{
+    delete_source_map_comment, replace_source_map_comment, PrivacyRewriteTemplate,
+};
 
-pub fn parse_source_map(unparsed_map: &[u8]) -> Result<SourceMap> {
-    SourceMap::from_reader(unparsed_map)
-        .map_err(|err| anyhow::anyhow!("Parsing input source map failed: {}", err))
+/// A source map for the instrumented code.
+pub struct OutputSourceMap {
+    pub map: SourceMap,
+    /// True if we chained with an index source map. The chained map is always flattened.
+    pub from_index_map: bool,
 }
 
-pub fn serialize_source_map(map: SourceMap) -> Result<String> {
+/// Parses a source map. Index source maps are flattened into a regular source map; their
+/// sections must embed their maps, since we can't load maps referenced by URL.
+pub fn parse_source_map(unparsed_map: &[u8]) -> Result<(SourceMap, bool)> {
+    let decoded = decode_slice(unparsed_map)
+        .map_err(|err| anyhow::anyhow!("Parsing input source map failed: {}", err))?;
+    match decoded {
+        DecodedMap::Regular(map) => Ok((map, false)),
+        DecodedMap::Index(index) => {
+            let map = index.flatten().map_err(|err| {
+                anyhow::anyhow!("Flattening input index source map failed: {}", err)
+            })?;
+            Ok((map, true))
+        }
+        DecodedMap::Hermes(hermes) => Ok(((*hermes).clone(), false)),
+    }
+}
+
+fn chain_source_map(
+    unparsed_map: &[u8],
+    transform_source_map: &SourceMap,
+) -> Result<OutputSourceMap> {
+    Ok(chain_parsed_source_map(
+        parse_source_map(unparsed_map)?,
+        transform_source_map,
+    ))
+}
+
+fn chain_parsed_source_map(
+    (mut map, from_index_map): (SourceMap, bool),
+    transform_source_map: &SourceMap,
+) -> OutputSourceMap {
+    map.adjust_mappings(transform_source_map);
+    OutputSourceMap {
+        map,
+        from_index_map,
+    }
+}
+
+/// Serializes the output source map. If `as_index_map` is true and we chained with an index
+/// source map, the output is an index source map with a single section, for the benefit of
+/// tools that expect an index map back.
+pub fn serialize_source_map(output_map: OutputSourceMap, as_index_map: bool) -> Result<String> {
+    let map = if as_index_map && output_map.from_index_map {
+        DecodedMap::Index(SourceMapIndex::new(
+            output_map.map.get_file().cloned(),
+            vec![SourceMapSection::new(
+                (0, 0),
+                None,
+                Some(DecodedMap::Regular(output_map.map)),
+            )],
+        ))
+    } else {
+        DecodedMap::Regular(output_map.map)
+    };
+
     let mut source_map_buffer = BufWriter::new(Vec::new());
     map.to_writer(&mut source_map_buffer)
         .map_err(|err| anyhow::anyhow!("Serializing output source map failed: {}", err))?;
@@ -23,59 +87,253 @@ pub fn serialize_source_map(map: SourceMap) -> Result<String> {
         .map_err(|err| anyhow::anyhow!("Converting output source map to string failed: {}", err))
 }
 
+/// Loads and parses the external source map referenced by the input's `sourceMappingURL`
+/// comment, if there is one. We don't need it if an input source map was provided, since that
+/// takes precedence. If it can't be loaded or parsed, we warn that we won't generate a source
+/// map; a bad third-party source map shouldn't break the build.
+pub fn resolve_external_source_map(
+    source_map_comment: &Option<SourceMapComment>,
+    input_source_map: &Option<String>,
+    input_file: &InputFile,
+    options: &InputOptions,
+    warnings: &mut Vec<Diagnostic>,
+) -> Option<(SourceMap, bool)> {
+    let (url, span) = match (source_map_comment, input_source_map) {
+        (Some(SourceMapComment::External(url, span)), None) => (url, span),
+        _ => return None,
+    };
+    let contents = match options.external_source_maps.get(url) {
+        Some(contents) => Some(contents.clone()),
+        None => options
+            .source_map_resolver
+            .as_ref()
+            .and_then(|resolver| resolver.resolve(url, input_file.name)),
+    };
+    let problem = match contents.map(|contents| parse_source_map(contents.as_bytes())) {
+        Some(Ok(parsed_map)) => return Some(parsed_map),
+        Some(Err(err)) => format!("couldn't be parsed ({})", err),
+        None => "couldn't be loaded".to_string(),
+    };
+    warnings.push(Diagnostic::at(
+        DiagnosticCode::ExternalSourceMap,
+        format!(
+            "Detected an external source map that {}. Will not generate a source map.",
+            problem
+        ),
+        input_file,
+        span.lo,
+    ));
+    None
+}
+
 pub fn chain_source_map_if_needed(
     source_map_comment: &Option<SourceMapComment>,
     input_source_map: &Option<String>,
+    external_source_map: Option<(SourceMap, bool)>,
     transform_source_map: SourceMap,
-) -> Result<Option<SourceMap>> {
+    input_file: &InputFile,
+    warnings: &mut Vec<Diagnostic>,
+) -> Result<Option<OutputSourceMap>> {
     match (source_map_comment, input_source_map) {
-        // If there's an external source map, we have no way of chaining with it. Generate no
-        // source map in this case.
-        (Some(SourceMapComment::External()), _) => {
-            debug_log("Detected external source map. Will not generate a source map.");
-            Ok(None)
-        }
+        // If an input source map was provided, trust it over the external source map, and
+        // chain with it.
+        (Some(SourceMapComment::External(_, _)), Some(unparsed_map)) => Ok(Some(chain_source_map(
+            unparsed_map.as_bytes(),
+            &transform_source_map,
+        )?)),
+
+        // If we were able to load the external source map, chain with it. Otherwise, we have
+        // no way of chaining with it, so generate no source map.
+        (Some(SourceMapComment::External(_, _)), None) => Ok(external_source_map
+            .map(|parsed_map| chain_parsed_source_map(parsed_map, &transform_source_map))),
 
         // We got source maps from two different sources! Arbitrarily choose to trust the
         // input source map and chain with it.
-        (Some(SourceMapComment::Inline(_, _)), Some(unparsed_map)) => {
-            debug_log("Detected an inline source map, but an input source map was provided. Ignoring inline source map.");
-            let mut map = parse_source_map(&unparsed_map.as_bytes())?;
-            map.adjust_mappings(&transform_source_map);
-            Ok(Some(map))
+        (Some(SourceMapComment::Inline(_, span)), Some(unparsed_map)) => {
+            warnings.push(Diagnostic::at(
+                DiagnosticCode::InlineSourceMapIgnored,
+                "Detected an inline source map, but an input source map was provided. Ignoring inline source map.".to_string(),
+                input_file,
+                span.lo,
+            ));
+            Ok(Some(chain_source_map(
+                unparsed_map.as_bytes(),
+                &transform_source_map,
+            )?))
         }
 
         // We got an inline source map; chain with it.
         (Some(SourceMapComment::Inline(unparsed_map, _)), None) => {
-            let mut map = parse_source_map(&unparsed_map)?;
-            map.adjust_mappings(&transform_source_map);
-            Ok(Some(map))
+            Ok(Some(chain_source_map(unparsed_map, &transform_source_map)?))
         }
 
         // We got an input source map; chain with it.
-        (None, Some(unparsed_map)) => {
-            let mut map = parse_source_map(&unparsed_map.as_bytes())?;
-            map.adjust_mappings(&transform_source_map);
-            Ok(Some(map))
-        }
+        (None, Some(unparsed_map)) => Ok(Some(chain_source_map(
+            unparsed_map.as_bytes(),
+            &transform_source_map,
+        )?)),
 
         // There's nothing to chain, so just use the transform source map as-is.
-        (None, None) => Ok(Some(transform_source_map)),
+        (None, None) => Ok(Some(OutputSourceMap {
+            map: transform_source_map,
+            from_index_map: false,
+        })),
     }
 }
 
-pub fn source_map_comment_span_to_delete(
+pub fn source_map_comment_rewrite(
     source_map_comment: &Option<SourceMapComment>,
-) -> Option<Span> {
+    input_source_map: &Option<String>,
+    external_source_map: &Option<(SourceMap, bool)>,
+    options: &OutputOptions,
+) -> Option<Rewrite<PrivacyRewriteTemplate>> {
     match source_map_comment {
-        // Don't delete external source map comments.
-        Some(SourceMapComment::External()) => None,
+        // Leave external source map comments alone unless we chained with the source map they
+        // reference, in which case they're handled as configured.
+        Some(SourceMapComment::External(_, span))
+            if input_source_map.is_some() || external_source_map.is_some() =>
+        {
+            match options.external_source_map_comment {
+                ExternalSourceMapComment::Remove => Some(delete_source_map_comment(*span)),
+                ExternalSourceMapComment::Keep => None,
+                ExternalSourceMapComment::Replace { ref url } => {
+                    Some(replace_source_map_comment(*span, url))
+                }
+            }
+        }
+        Some(SourceMapComment::External(_, _)) => None,
 
         // Always delete inline source map comments, because after the transform runs, the inline
         // source map is wrong.
-        Some(SourceMapComment::Inline(_, span)) => Some(*span),
+        Some(SourceMapComment::Inline(_, span)) => Some(delete_source_map_comment(*span)),
 
         // If there was no source map comment, we don't need to take any action.
         None => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use js_instrumentation_shared::{
+        InstrumentationInput, InstrumentationOptions, SourceMapResolver,
+    };
+
+    use crate::apply_transform;
+
+    use super::*;
+
+    const EXTERNAL_MAP: &str =
+        r#"{"version":3,"sources":["original.js"],"names":[],"mappings":"AAAA"}"#;
+
+    fn transform_with_external_map(options: &InstrumentationOptions) -> (String, Option<String>) {
+        let output = apply_transform(
+            &InstrumentationInput {
+                id: "input.js".into(),
+                code: "const a = 'Hello there';\n//# sourceMappingURL=input.js.map\n".into(),
+                map: None,
+            },
+            options,
+        )
+        .unwrap();
+        (output.code, output.map)
+    }
+
+    #[test]
+    fn chains_with_resolved_external_source_maps() {
+        let mut options = InstrumentationOptions::default();
+        options
+            .input
+            .external_source_maps
+            .insert("input.js.map".into(), EXTERNAL_MAP.into());
+
+        let (code, map) = transform_with_external_map(&options);
+        assert!(!code.contains("sourceMappingURL"));
+        let (map, _) = parse_source_map(map.unwrap().as_bytes()).unwrap();
+        assert_eq!(&*map.get_source(0).unwrap(), "original.js");
+
+        options.output.external_source_map_comment = ExternalSourceMapComment::Replace {
+            url: "output.js.map".into(),
+        };
+        let (code, _) = transform_with_external_map(&options);
+        assert!(code.ends_with("//# sourceMappingURL=output.js.map\n"));
+    }
+
+    #[test]
+    fn uses_the_resolver_for_unlisted_external_source_maps() {
+        struct Resolver;
+        impl SourceMapResolver for Resolver {
+            fn resolve(self: &Self, url: &str, id: &str) -> Option<String> {
+                assert_eq!((url, id), ("input.js.map", "input.js"));
+                Some(EXTERNAL_MAP.into())
+            }
+        }
+
+        let mut options = InstrumentationOptions::default();
+        options.input.source_map_resolver = Some(std::rc::Rc::new(Resolver));
+        let (_, map) = transform_with_external_map(&options);
+        assert!(map.is_some());
+    }
+
+    #[test]
+    fn generates_no_source_map_for_unresolved_external_source_maps() {
+        let (code, map) = transform_with_external_map(&Default::default());
+        assert!(code.ends_with("//# sourceMappingURL=input.js.map\n"));
+        assert!(map.is_none());
+    }
+
+    #[test]
+    fn warns_about_unparseable_external_source_maps() {
+        let mut options = InstrumentationOptions::default();
+        options
+            .input
+            .external_source_maps
+            .insert("input.js.map".into(), "not a source map".into());
+
+        let output = apply_transform(
+            &InstrumentationInput {
+                id: "input.js".into(),
+                code: "const a = 'Hello there';\n//# sourceMappingURL=input.js.map\n".into(),
+                map: None,
+            },
+            &options,
+        )
+        .unwrap();
+        assert!(output.code.contains("const a = D[0];"));
+        assert!(output.code.ends_with("//# sourceMappingURL=input.js.map\n"));
+        assert!(output.map.is_none());
+        assert_eq!(
+            output
+                .warnings
+                .iter()
+                .map(|warning| warning.code.clone())
+                .collect::<Vec<_>>(),
+            vec![DiagnosticCode::ExternalSourceMap]
+        );
+    }
+
+    #[test]
+    fn flattens_index_source_maps() {
+        let index_map = r#"{"version":3,"sections":[
+            {"offset":{"line":0,"column":0},"map":{"version":3,"sources":["a.js"],"names":[],"mappings":"AAAA"}},
+            {"offset":{"line":1,"column":0},"map":{"version":3,"sources":["b.js"],"names":[],"mappings":"AAAA"}}
+        ]}"#;
+        let input = InstrumentationInput {
+            id: "input.js".into(),
+            code: "const a = 'Hello there';\nconst b = 'Hello again';\n".into(),
+            map: Some(index_map.into()),
+        };
+
+        let mut options = InstrumentationOptions::default();
+        let output = apply_transform(&input, &options).unwrap();
+        let (map, was_index_map) = parse_source_map(output.map.unwrap().as_bytes()).unwrap();
+        assert!(!was_index_map);
+        let sources: Vec<&str> = map.sources().map(|source| &**source).collect();
+        assert_eq!(sources, vec!["a.js", "b.js"]);
+
+        options.output.index_source_map = true;
+        let output = apply_transform(&input, &options).unwrap();
+        let (map, was_index_map) = parse_source_map(output.map.unwrap().as_bytes()).unwrap();
+        assert!(was_index_map);
+        assert_eq!(map.get_source_count(), 2);
+    }
+}
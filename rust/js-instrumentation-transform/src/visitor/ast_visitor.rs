@@ -1,11 +1,14 @@
+use js_instrumentation_shared::instrumentation_output::ContextCategory;
+use js_instrumentation_shared::module_kind::ModuleSignal;
 use js_instrumentation_shared::InputFile;
-use swc_atoms::Atom;
 use swc_common::{BytePos, Span, Spanned};
 use swc_ecma_ast::{
-    CallExpr, Callee, ExportAll, Expr, Ident, IdentName, ImportDecl, JSXAttr, JSXAttrName,
-    JSXAttrValue, JSXElement, JSXElementChild, JSXElementName, JSXText, Lit, MemberExpr,
-    MemberProp, NamedExport, Program, PropName, PropOrSpread, Stmt, Str, TaggedTpl, Tpl,
-    TsEnumDecl, TsInterfaceDecl, TsModuleName, TsType,
+    ArrowExpr, AwaitExpr, CallExpr, Callee, ExportAll, Expr, ExprOrSpread, ForOfStmt, Function,
+    Ident, IdentName, ImportDecl, JSXAttr, JSXAttrName, JSXAttrValue, JSXElement, JSXElementChild,
+    JSXElementName, JSXObject, JSXText, Lit, MemberExpr, MemberProp, MetaPropExpr, MetaPropKind,
+    ModuleDecl, NamedExport, NewExpr, Program, PropName, PropOrSpread, Stmt, Str, SwitchCase,
+    TaggedTpl, ThrowStmt, Tpl, TsEnumDecl, TsInterfaceDecl, TsModuleName, TsModuleRef, TsType,
+    UnaryExpr, UnaryOp,
 };
 use swc_ecma_visit::{Visit, VisitWith};
 
@@ -22,6 +25,12 @@ use crate::{
     },
 };
 
+use js_instrumentation_shared::instrumentation_options::ElementFactory;
+
+use super::{
+    call_filter::UncollectedArgs, CallFilter, ElementFactories, ImportBindings, JSXFilter,
+};
+
 pub fn visit<'a, 'b>(
     program: &Program,
     input_file: &'a mut InputFile<'b>,
@@ -29,23 +38,35 @@ pub fn visit<'a, 'b>(
     feature_tracker: &'a mut FeatureTracker,
     identifier_tracker: &'a mut IdentifierTracker,
     rewrite_tracker: &'a mut RewriteTracker,
+    jsx_filter: &'a JSXFilter,
+    call_filter: &'a CallFilter,
+    element_factories: &'a ElementFactories,
 ) {
     let mut visitor = ASTVisitor {
         input_file,
         dictionary_tracker,
+        import_bindings: ImportBindings::collect(program),
         feature_tracker,
         identifier_tracker,
         rewrite_tracker,
+        jsx_filter,
+        call_filter,
+        element_factories,
     };
     program.visit_with(&mut visitor);
+    visitor.feature_tracker.observed_program(program);
 }
 
 struct ASTVisitor<'a, 'b> {
     input_file: &'a mut InputFile<'b>,
     dictionary_tracker: &'a mut DictionaryTracker,
+    import_bindings: ImportBindings,
     feature_tracker: &'a mut FeatureTracker,
     identifier_tracker: &'a mut IdentifierTracker,
     rewrite_tracker: &'a mut RewriteTracker,
+    jsx_filter: &'a JSXFilter,
+    call_filter: &'a CallFilter,
+    element_factories: &'a ElementFactories,
 }
 
 impl<'a, 'b> ASTVisitor<'a, 'b> {
@@ -56,6 +77,16 @@ impl<'a, 'b> ASTVisitor<'a, 'b> {
         self.dictionary_tracker.exit_uncollected_scope();
     }
 
+    /// Returns true if we never collect strings from the JSX element with the given tag. As in
+    /// JSX itself, lowercase tags are intrinsic elements and others are components.
+    fn is_uncollected_jsx_ident(self: &Self, name: &str) -> bool {
+        if name.starts_with(|c: char| c.is_ascii_lowercase()) {
+            self.jsx_filter.is_uncollected_element(name)
+        } else {
+            self.jsx_filter.is_uncollected_component(name)
+        }
+    }
+
     /// Perform an action in a context in which we won't rewrite code.
     fn in_unrewritten_scope<F: FnOnce(&mut Self)>(self: &mut Self, action: F) {
         self.rewrite_tracker.enter_unrewritten_scope();
@@ -63,21 +94,37 @@ impl<'a, 'b> ASTVisitor<'a, 'b> {
         self.rewrite_tracker.exit_unrewritten_scope();
     }
 
-    /// Visit a React.createElement call. We have a special visitor for React.createElement to
-    /// avoid collecting the first argument, which is an element name that isn't relevant for
-    /// the privacy dictionary.
-    fn visit_react_create_element_call(&mut self, node: &CallExpr) {
-        if let Some(true) = first_arg_if_literal_string(node).map(is_uncollected_jsx_element) {
-            // This is a React.createElement() call for an uncollected JSX element.
-            // Don't collect anything in this subtree.
+    /// Perform an action in a context in which the strings we collect belong to the given
+    /// category.
+    fn in_category<F: FnOnce(&mut Self)>(self: &mut Self, category: ContextCategory, action: F) {
+        let previous = self.dictionary_tracker.enter_category(category);
+        action(self);
+        self.dictionary_tracker.exit_category(previous);
+    }
+
+    /// Visit a call to an element factory, like React.createElement() or a jsx() call emitted
+    /// by the automatic JSX runtime. We have a special visitor for these calls to avoid
+    /// collecting the element's tag, which isn't relevant for the privacy dictionary, and to
+    /// collect the props and children like JSX attributes and children.
+    fn visit_element_factory_call(&mut self, node: &CallExpr, factory: &ElementFactory) {
+        if let Some(true) = factory
+            .tag_argument
+            .and_then(|index| arg_element_name(node, index))
+            .map(|element_name| match element_name {
+                ElementName::Intrinsic(name) => self.jsx_filter.is_uncollected_element(&name),
+                ElementName::Component(name) => self.jsx_filter.is_uncollected_component(&name),
+            })
+        {
+            // This call creates an uncollected JSX element. Don't collect anything in this
+            // subtree.
             self.in_uncollected_scope(|this| node.visit_children_with(this));
             return;
         }
 
-        // This is a React.createElement() for a collected JSX element. We generally want
-        // to collect things in this subtree, but we want to exclude certain strings,
-        // including the name of the element (which isn't relevant for privacy) and any
-        // uncollect JSX attributes.
+        // This call creates a collected JSX element. We generally want to collect things in
+        // this subtree, but we want to exclude certain strings, including the tag (which isn't
+        // relevant for privacy), any uncollected JSX attributes, and any arguments that the
+        // factory uses for bookkeeping.
         match node {
             CallExpr {
                 span,
@@ -97,29 +144,34 @@ impl<'a, 'b> ASTVisitor<'a, 'b> {
                 };
                 {
                     for (i, arg) in args.iter().enumerate() {
-                        match i {
-                            0 => {
-                                // This is the tag name; don't collect it.
-                                self.in_uncollected_scope(|this| arg.visit_with(this));
-                            }
-                            1 => match *arg.expr {
+                        if factory.tag_argument == Some(i)
+                            || factory.uncollected_arguments.contains(&i)
+                        {
+                            self.in_uncollected_scope(|this| arg.visit_with(this));
+                        } else if factory.props_argument == Some(i) {
+                            match *arg.expr {
                                 Expr::Object(ref obj) => {
                                     obj.span.visit_with(self);
                                     for prop in &obj.props {
-                                        if is_uncollected_jsx_attr_prop(&prop) {
+                                        if self.is_uncollected_jsx_attr_prop(&prop) {
                                             self.in_uncollected_scope(|this| prop.visit_with(this));
                                         } else {
-                                            prop.visit_with(self);
+                                            self.in_category(
+                                                ContextCategory::JsxAttribute,
+                                                |this| prop.visit_with(this),
+                                            );
                                         }
                                     }
                                 }
                                 _ => {
-                                    arg.visit_with(self);
+                                    self.in_category(ContextCategory::JsxAttribute, |this| {
+                                        arg.visit_with(this)
+                                    });
                                 }
-                            },
-                            _ => {
-                                arg.visit_with(self);
                             }
+                        } else {
+                            // The remaining arguments are the element's children.
+                            self.in_category(ContextCategory::JsxText, |this| arg.visit_with(this));
                         }
                     }
                 };
@@ -129,6 +181,78 @@ impl<'a, 'b> ASTVisitor<'a, 'b> {
             }
         }
     }
+
+    /// Visit the arguments of a call or constructor expression, without collecting the
+    /// arguments that the call filter excludes.
+    fn visit_args_with_uncollected(
+        self: &mut Self,
+        args: &[ExprOrSpread],
+        uncollected_args: &UncollectedArgs,
+    ) {
+        for (i, arg) in args.iter().enumerate() {
+            if uncollected_args.includes(i) {
+                self.in_uncollected_scope(|this| arg.visit_with(this));
+            } else {
+                arg.visit_with(self);
+            }
+        }
+    }
+
+    /// Visit a call expression, without collecting the arguments that the call filter
+    /// excludes.
+    fn visit_filtered_call_expr(self: &mut Self, node: &CallExpr) {
+        if let Callee::Expr(ref callee) = node.callee {
+            // Don't collect strings inside calls that the user has asked us to ignore.
+            let call_filter = self.call_filter;
+            if let Some(uncollected_args) = call_filter.uncollected_args_for_callee(callee) {
+                node.span.visit_with(self);
+                node.ctxt.visit_with(self);
+                callee.visit_with(self);
+                self.visit_args_with_uncollected(&node.args, uncollected_args);
+                node.type_args.visit_with(self);
+                return;
+            }
+        }
+
+        node.visit_children_with(self);
+    }
+
+    /// Visit a constructor expression, without collecting the arguments that the call filter
+    /// excludes.
+    fn visit_filtered_new_expr(self: &mut Self, node: &NewExpr) {
+        // Don't collect strings inside constructor calls that the user has asked us to ignore.
+        let call_filter = self.call_filter;
+        if let Some(uncollected_args) = call_filter.uncollected_args_for_callee(&node.callee) {
+            node.span.visit_with(self);
+            node.ctxt.visit_with(self);
+            node.callee.visit_with(self);
+            if let Some(ref args) = node.args {
+                self.visit_args_with_uncollected(args, uncollected_args);
+            }
+            node.type_args.visit_with(self);
+            return;
+        }
+
+        node.visit_children_with(self);
+    }
+
+    fn is_uncollected_jsx_attr_prop(self: &Self, prop: &PropOrSpread) -> bool {
+        match prop {
+            PropOrSpread::Prop(ref prop) => match **prop {
+                swc_ecma_ast::Prop::KeyValue(ref key_value_prop) => match key_value_prop.key {
+                    PropName::Ident(ref ident_name) => {
+                        self.jsx_filter.is_uncollected_attr(&ident_name.sym)
+                    }
+                    PropName::Str(ref str_name) => {
+                        self.jsx_filter.is_uncollected_attr(&str_name.value)
+                    }
+                    _ => false,
+                },
+                _ => false,
+            },
+            _ => false,
+        }
+    }
 }
 
 impl<'a, 'b> Visit for ASTVisitor<'a, 'b> {
@@ -149,7 +273,7 @@ impl<'a, 'b> Visit for ASTVisitor<'a, 'b> {
         // be, if this is really an ES module, but in that case we'll generally observe an ESM
         // "import" or "export" and correctly treat the module as ESM anyway.)
         if node.sym.as_str() == "exports" {
-            self.feature_tracker.observed_cjs_exports_or_require();
+            self.feature_tracker.observed(ModuleSignal::Exports);
         }
 
         // Track both the starting and ending position of all identifiers, so that we generate
@@ -164,13 +288,6 @@ impl<'a, 'b> Visit for ASTVisitor<'a, 'b> {
         // identifiers later.
         self.identifier_tracker.add_ident_name(node);
 
-        // If we see an identifier named "exports", assume it's the CommonJS "exports". (It may not
-        // be, if this is really an ES module, but in that case we'll generally observe an ESM
-        // "import" or "export" and correctly treat the module as ESM anyway.)
-        if node.sym.as_str() == "exports" {
-            self.feature_tracker.observed_cjs_exports_or_require();
-        }
-
         // Track both the starting and ending position of all identifiers, so that we generate
         // source maps that can handle identifier renaming well. (The TypeScript compiler does this
         // as well.)
@@ -206,7 +323,7 @@ impl<'a, 'b> Visit for ASTVisitor<'a, 'b> {
             PropName::Str(Str { raw, span, value }) => {
                 if let Some(index) = self
                     .dictionary_tracker
-                    .maybe_add_string(&raw, &value, &span)
+                    .maybe_add_property_key(&raw, &value, &span)
                 {
                     self.rewrite_tracker
                         .emit(replace_property_key_with_dictionary_ref(index, *span));
@@ -228,10 +345,11 @@ impl<'a, 'b> Visit for ASTVisitor<'a, 'b> {
         loop {
             let next_quasi = quasi_iter.next();
             if let Some(quasi) = next_quasi {
-                if let Some(index) = self
-                    .dictionary_tracker
-                    .maybe_add_template_quasi(&quasi.raw, &quasi.span)
-                {
+                if let Some(index) = self.dictionary_tracker.maybe_add_template_quasi(
+                    &quasi.raw,
+                    &quasi.cooked,
+                    &quasi.span,
+                ) {
                     self.rewrite_tracker
                         .emit(replace_template_quasi_with_dictionary_ref(
                             index, quasi.span,
@@ -271,7 +389,12 @@ impl<'a, 'b> Visit for ASTVisitor<'a, 'b> {
             .dictionary_tracker
             .maybe_add_tagged_template(&quasis, &node.span);
         if let None = index {
-            node.visit_children_with(self);
+            // The tag function receives the quasis as they're written, so they can't be
+            // rewritten individually; leave them alone.
+            node.tag.visit_with(self);
+            for expr in &node.tpl.exprs {
+                expr.visit_with(self);
+            }
             return;
         }
 
@@ -323,14 +446,21 @@ impl<'a, 'b> Visit for ASTVisitor<'a, 'b> {
 
     fn visit_jsx_attr(&mut self, node: &JSXAttr) {
         match &node.name {
-            JSXAttrName::Ident(ident) if is_uncollected_jsx_attr(&ident.sym) => {
+            JSXAttrName::Ident(ident) if self.jsx_filter.is_uncollected_attr(&ident.sym) => {
                 self.in_uncollected_scope(|this| node.visit_children_with(this));
             }
-            JSXAttrName::JSXNamespacedName(name) if is_uncollected_jsx_attr(&name.name.sym) => {
+            JSXAttrName::JSXNamespacedName(name)
+                if self.jsx_filter.is_uncollected_attr(&name.name.sym)
+                    || self
+                        .jsx_filter
+                        .is_uncollected_attr(&format!("{}:{}", name.ns.sym, name.name.sym)) =>
+            {
                 self.in_uncollected_scope(|this| node.visit_children_with(this));
             }
             _ => {
-                node.visit_children_with(self);
+                self.in_category(ContextCategory::JsxAttribute, |this| {
+                    node.visit_children_with(this)
+                });
             }
         }
     }
@@ -348,10 +478,11 @@ impl<'a, 'b> Visit for ASTVisitor<'a, 'b> {
                     // Track both the start and ending position since we rewrite this kind of token.
                     self.rewrite_tracker.add_token_position(span.lo);
                     self.rewrite_tracker.add_token_position(span.hi);
-
-                    // Don't recurse; we don't want to treat this as an ordinary string.
-                    return;
                 }
+
+                // Don't recurse, even if the string was skipped; we don't want to treat this
+                // as an ordinary string.
+                return;
             }
             _ => {}
         }
@@ -361,11 +492,18 @@ impl<'a, 'b> Visit for ASTVisitor<'a, 'b> {
 
     fn visit_jsx_element(&mut self, node: &JSXElement) {
         match &node.opening.name {
-            JSXElementName::Ident(ident) if is_uncollected_jsx_element(&ident.sym) => {
+            JSXElementName::Ident(ident) if self.is_uncollected_jsx_ident(&ident.sym) => {
                 self.in_uncollected_scope(|this| node.visit_children_with(this));
             }
             JSXElementName::JSXNamespacedName(name)
-                if is_uncollected_jsx_element(&name.name.sym) =>
+                if self.jsx_filter.is_uncollected_element(&name.name.sym) =>
+            {
+                self.in_uncollected_scope(|this| node.visit_children_with(this));
+            }
+            JSXElementName::JSXMemberExpr(member)
+                if self
+                    .jsx_filter
+                    .is_uncollected_component(&jsx_member_expr_name(&member.obj, &member.prop)) =>
             {
                 self.in_uncollected_scope(|this| node.visit_children_with(this));
             }
@@ -377,8 +515,8 @@ impl<'a, 'b> Visit for ASTVisitor<'a, 'b> {
 
     fn visit_jsx_element_child(&mut self, node: &JSXElementChild) {
         match &node {
-            JSXElementChild::JSXText(JSXText { raw, span, value }) => {
-                if let Some(index) = self.dictionary_tracker.maybe_add_jsx_text(raw, value, span) {
+            JSXElementChild::JSXText(JSXText { raw, span, .. }) => {
+                if let Some(index) = self.dictionary_tracker.maybe_add_jsx_text(raw, span) {
                     self.rewrite_tracker
                         .emit(replace_jsx_string_with_dictionary_ref(index, *span));
                 }
@@ -387,6 +525,13 @@ impl<'a, 'b> Visit for ASTVisitor<'a, 'b> {
                 self.rewrite_tracker.add_token_position(span.lo);
                 self.rewrite_tracker.add_token_position(span.hi);
             }
+            JSXElementChild::JSXExprContainer(_) => {
+                // Strings in an expression inside an element are displayed like its text.
+                self.in_category(ContextCategory::JsxText, |this| {
+                    node.visit_children_with(this)
+                });
+                return;
+            }
             _ => {}
         }
 
@@ -399,21 +544,99 @@ impl<'a, 'b> Visit for ASTVisitor<'a, 'b> {
         self.in_unrewritten_scope(|this| node.visit_children_with(this));
     }
 
+    fn visit_module_decl(&mut self, node: &ModuleDecl) {
+        let signal = match node {
+            ModuleDecl::Import(_) => Some(ModuleSignal::ImportDeclaration),
+            ModuleDecl::ExportDecl(_)
+            | ModuleDecl::ExportNamed(_)
+            | ModuleDecl::ExportDefaultDecl(_)
+            | ModuleDecl::ExportDefaultExpr(_)
+            | ModuleDecl::ExportAll(_) => Some(ModuleSignal::ExportDeclaration),
+            ModuleDecl::TsImportEquals(decl) => match decl.module_ref {
+                TsModuleRef::TsExternalModuleRef(_) => Some(ModuleSignal::TsImportEquals),
+                // `import x = Namespace.Member` is just an alias.
+                TsModuleRef::TsEntityName(_) => None,
+            },
+            ModuleDecl::TsExportAssignment(_) => Some(ModuleSignal::TsExportAssignment),
+            ModuleDecl::TsNamespaceExport(_) => None,
+        };
+        if let Some(signal) = signal {
+            self.feature_tracker.observed(signal);
+        }
+
+        node.visit_children_with(self);
+    }
+
+    fn visit_member_expr(&mut self, node: &MemberExpr) {
+        if let (Expr::Ident(obj), MemberProp::Ident(prop)) = (&*node.obj, &node.prop) {
+            match (obj.sym.as_str(), prop.sym.as_str()) {
+                ("module", "exports") => self.feature_tracker.observed(ModuleSignal::ModuleExports),
+                // UMD wrappers check `define.amd` before registering an AMD module.
+                ("define", "amd") => self.feature_tracker.observed(ModuleSignal::UmdWrapper),
+                _ => {}
+            }
+        }
+
+        node.visit_children_with(self);
+    }
+
+    fn visit_unary_expr(&mut self, node: &UnaryExpr) {
+        // UMD wrappers check `typeof define` before registering an AMD module.
+        if let (UnaryOp::TypeOf, Expr::Ident(arg)) = (node.op, &*node.arg) {
+            if arg.sym.as_str() == "define" {
+                self.feature_tracker.observed(ModuleSignal::UmdWrapper);
+            }
+        }
+
+        node.visit_children_with(self);
+    }
+
+    fn visit_meta_prop_expr(&mut self, node: &MetaPropExpr) {
+        if node.kind == MetaPropKind::ImportMeta {
+            self.feature_tracker.observed(ModuleSignal::ImportMeta);
+        }
+
+        node.visit_children_with(self);
+    }
+
+    fn visit_function(&mut self, node: &Function) {
+        // Track function nesting, so we can recognize top-level await.
+        self.feature_tracker.enter_function();
+        node.visit_children_with(self);
+        self.feature_tracker.exit_function();
+    }
+
+    fn visit_arrow_expr(&mut self, node: &ArrowExpr) {
+        // Track function nesting, so we can recognize top-level await.
+        self.feature_tracker.enter_function();
+        node.visit_children_with(self);
+        self.feature_tracker.exit_function();
+    }
+
+    fn visit_await_expr(&mut self, node: &AwaitExpr) {
+        self.feature_tracker.observed_await();
+        node.visit_children_with(self);
+    }
+
+    fn visit_for_of_stmt(&mut self, node: &ForOfStmt) {
+        if node.is_await {
+            self.feature_tracker.observed_await();
+        }
+        node.visit_children_with(self);
+    }
+
     fn visit_import_decl(&mut self, node: &ImportDecl) {
         // Don't collect string literals inside import declarations.
-        self.feature_tracker.observed_esm_export_or_import();
         self.in_uncollected_scope(|this| node.visit_children_with(this));
     }
 
     fn visit_named_export(&mut self, node: &NamedExport) {
         // Don't collect string literals inside named exports.
-        self.feature_tracker.observed_esm_export_or_import();
         self.in_uncollected_scope(|this| node.visit_children_with(this));
     }
 
     fn visit_export_all(&mut self, node: &ExportAll) {
         // Don't collect string literals inside export all declarations.
-        self.feature_tracker.observed_esm_export_or_import();
         self.in_uncollected_scope(|this| node.visit_children_with(this));
     }
 
@@ -461,7 +684,32 @@ impl<'a, 'b> Visit for ASTVisitor<'a, 'b> {
         node.visit_children_with(self);
     }
 
+    fn visit_throw_stmt(&mut self, node: &ThrowStmt) {
+        self.in_category(ContextCategory::ErrorMessage, |this| {
+            node.visit_children_with(this)
+        });
+    }
+
+    fn visit_switch_case(&mut self, node: &SwitchCase) {
+        node.span.visit_with(self);
+        self.in_category(ContextCategory::SwitchCase, |this| {
+            node.test.visit_with(this)
+        });
+        node.cons.visit_with(self);
+    }
+
     fn visit_call_expr(&mut self, node: &CallExpr) {
+        if let Callee::Expr(ref callee) = node.callee {
+            let element_factories = self.element_factories;
+            if let Some(factory) =
+                element_factories.factory_for_callee(callee, &self.import_bindings)
+            {
+                // This is compiled JSX, e.g. a jsx() or React.createElement() call.
+                self.visit_element_factory_call(node, factory);
+                return;
+            }
+        }
+
         match &node.callee {
             Callee::Expr(expr) => {
                 match **expr {
@@ -473,40 +721,41 @@ impl<'a, 'b> Visit for ASTVisitor<'a, 'b> {
                                 return;
                             }
                             "require" => {
-                                self.feature_tracker.observed_cjs_exports_or_require();
+                                self.feature_tracker.observed(ModuleSignal::Require);
                                 self.in_uncollected_scope(|this| node.visit_children_with(this));
                                 return;
                             }
-                            "jsx" | "_jsx" | "jsxs" | "_jsxs" | "jsxDEV" | "_jsxDEV" => {
-                                // This is a "modern" React.createElement call, using the newer
-                                // JSX transform factory functions.
-                                self.visit_react_create_element_call(node);
-                                return;
+                            "define" if node.args.iter().any(is_amd_define_arg) => {
+                                self.feature_tracker.observed(ModuleSignal::AmdDefine);
                             }
                             _ => {}
                         }
                     }
-                    Expr::Member(ref member) if is_react_create_element(member) => {
-                        // This is a "classic" React.createElement call.
-                        self.visit_react_create_element_call(node);
-                        return;
-                    }
                     _ => {}
                 }
             }
             Callee::Import(_) => {
-                // Don't collect strings inside import() expressions.
-                self.feature_tracker.observed_esm_export_or_import();
+                // Don't collect strings inside import() expressions. Note that import() is
+                // available in CommonJS too, so it says nothing about the module format.
                 self.in_uncollected_scope(|this| node.visit_children_with(this));
                 return;
             }
             _ => {}
         }
 
-        node.visit_children_with(self);
+        let category = match node.callee {
+            Callee::Expr(ref callee) => callee_category(callee),
+            _ => None,
+        };
+        match category {
+            Some(category) => {
+                self.in_category(category, |this| this.visit_filtered_call_expr(node));
+            }
+            None => self.visit_filtered_call_expr(node),
+        }
     }
 
-    fn visit_new_expr(&mut self, node: &swc_ecma_ast::NewExpr) {
+    fn visit_new_expr(&mut self, node: &NewExpr) {
         match *node.callee {
             Expr::Ident(ref ident) => {
                 // Don't collect strings inside certain constructor calls.
@@ -521,97 +770,123 @@ impl<'a, 'b> Visit for ASTVisitor<'a, 'b> {
             _ => {}
         }
 
-        node.visit_children_with(self);
+        match callee_category(&node.callee) {
+            Some(category) => {
+                self.in_category(category, |this| this.visit_filtered_new_expr(node));
+            }
+            None => self.visit_filtered_new_expr(node),
+        }
     }
 }
 
-fn is_uncollected_jsx_element(atom: &Atom) -> bool {
-    match atom.as_str() {
-        "g" | "path" => true,
-        _ => false,
+/// Returns the category of the strings passed to a call or constructor with the given
+/// callee, if it's more specific than the surrounding context.
+fn callee_category(callee: &Expr) -> Option<ContextCategory> {
+    match callee {
+        // Error constructors, like Error() or new TypeError().
+        Expr::Ident(ident)
+            if ident.sym.ends_with("Error")
+                && ident.sym.starts_with(|c: char| c.is_ascii_uppercase()) =>
+        {
+            Some(ContextCategory::ErrorMessage)
+        }
+        Expr::Member(MemberExpr { obj, .. }) => match **obj {
+            Expr::Ident(ref ident) if ident.sym.as_str() == "console" => {
+                Some(ContextCategory::ConsoleArgument)
+            }
+            _ => None,
+        },
+        _ => None,
     }
 }
 
-fn is_uncollected_jsx_attr(atom: &Atom) -> bool {
-    match atom.as_str() {
-        "class" | "className" | "d" | "id" | "src" | "srcset" | "style" => true,
-        _ => false,
+/// Returns the element name passed as the argument at `index` to an element factory call
+/// (e.g. the first argument to React.createElement()), if it's a literal string (e.g. "div")
+/// or a component reference (e.g. `Icon` or `Icons.Close`).
+/// Component references are named the same way they'd be named in JSX syntax.
+fn arg_element_name(node: &CallExpr, index: usize) -> Option<ElementName> {
+    let expr = &node.args.get(index)?.expr;
+    match **expr {
+        Expr::Lit(Lit::Str(ref s)) => Some(ElementName::Intrinsic(s.value.to_string())),
+        _ => expr_component_name(expr).map(ElementName::Component),
     }
 }
 
-fn is_uncollected_jsx_attr_prop(prop: &PropOrSpread) -> bool {
-    match prop {
-        PropOrSpread::Prop(ref prop) => match **prop {
-            swc_ecma_ast::Prop::KeyValue(ref key_value_prop) => match key_value_prop.key {
-                PropName::Ident(ref ident_name) => is_uncollected_jsx_attr(&ident_name.sym),
-                PropName::Str(ref str_name) => is_uncollected_jsx_attr(&str_name.value),
-                _ => false,
-            },
-            _ => false,
-        },
-        _ => false,
-    }
+/// The name of the element created by an element factory call.
+enum ElementName {
+    /// An intrinsic element, passed as a string (e.g. "div").
+    Intrinsic(String),
+    /// A component, passed by reference (e.g. `Icon` or `Icons.Close`).
+    Component(String),
 }
 
-fn is_react_create_element(member: &MemberExpr) -> bool {
-    match *member.obj {
-        Expr::Ident(ref ident) => {
-            if ident.sym.as_str() != "React" {
-                return false;
-            }
-        }
-        _ => {
-            return false;
-        }
+fn expr_component_name(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Ident(ref ident) => Some(ident.sym.to_string()),
+        Expr::Member(MemberExpr {
+            ref obj,
+            prop: MemberProp::Ident(ref prop),
+            ..
+        }) => Some(format!("{}.{}", expr_component_name(obj)?, prop.sym)),
+        _ => None,
     }
+}
 
-    match member.prop {
-        MemberProp::Ident(ref ident) => {
-            if ident.sym.as_str() != "createElement" {
-                return false;
-            }
-        }
-        _ => {
-            return false;
-        }
+fn jsx_member_expr_name(obj: &JSXObject, prop: &IdentName) -> String {
+    match obj {
+        JSXObject::Ident(ref ident) => format!("{}.{}", ident.sym, prop.sym),
+        JSXObject::JSXMemberExpr(ref member) => format!(
+            "{}.{}",
+            jsx_member_expr_name(&member.obj, &member.prop),
+            prop.sym
+        ),
     }
-
-    return true;
 }
 
-fn first_arg_if_literal_string(node: &CallExpr) -> Option<&Atom> {
-    match node.args.get(0) {
-        Some(expr_or_spread) => match *expr_or_spread.expr {
-            Expr::Lit(Lit::Str(ref s)) => Some(&s.value),
-            _ => None,
-        },
-        None => None,
-    }
+/// True if the argument looks like an AMD dependency list or module factory.
+fn is_amd_define_arg(arg: &ExprOrSpread) -> bool {
+    matches!(*arg.expr, Expr::Array(_) | Expr::Fn(_) | Expr::Arrow(_))
 }
 
 #[cfg(test)]
 mod tests {
-    use js_instrumentation_shared::{build_parser, InputFile};
+    use js_instrumentation_shared::instrumentation_options::{
+        ElementFactoryPreset, UncollectedCall,
+    };
+    use js_instrumentation_shared::{build_parser, InputFile, InstrumentationOptions};
     use swc_common::comments::SingleThreadedComments;
     use swc_common::source_map::SmallPos;
     use swc_common::BytePos;
 
     use crate::comments::process_comments;
     use crate::dictionary::DictionaryEntry;
+    use crate::visitor::resolve_scopes;
 
     use super::*;
 
     fn walk_code(code: &str) -> (DictionaryTracker, RewriteTracker) {
+        walk_code_with_options(code, &Default::default())
+    }
+
+    fn walk_code_with_options(
+        code: &str,
+        options: &InstrumentationOptions,
+    ) -> (DictionaryTracker, RewriteTracker) {
         let mut input_file = InputFile::new("test.jsx", code);
         let comments: SingleThreadedComments = Default::default();
-        let mut parser = build_parser(&input_file, &comments, &Default::default());
-        let program = parser.parse_program().unwrap();
-        let (directive_set, _) = process_comments(&input_file, &comments);
+        let mut parser = build_parser(&input_file, &comments, options);
+        let mut program = parser.parse_program().unwrap();
+        resolve_scopes(&mut program);
+        let (directive_set, _, _) = process_comments(&input_file, &comments);
 
-        let mut dictionary_tracker = DictionaryTracker::new(directive_set);
+        let mut dictionary_tracker =
+            DictionaryTracker::new(directive_set, Default::default(), Default::default());
         let mut feature_tracker = FeatureTracker::new();
         let mut identifier_tracker = IdentifierTracker::new(vec![]);
         let mut rewrite_tracker = RewriteTracker::new();
+        let jsx_filter = JSXFilter::new(&options.privacy).unwrap();
+        let call_filter = CallFilter::new(&options.privacy).unwrap();
+        let element_factories = ElementFactories::new(&options.privacy).unwrap();
 
         visit(
             &program,
@@ -620,6 +895,9 @@ mod tests {
             &mut feature_tracker,
             &mut identifier_tracker,
             &mut rewrite_tracker,
+            &jsx_filter,
+            &call_filter,
+            &element_factories,
         );
 
         (dictionary_tracker, rewrite_tracker)
@@ -655,4 +933,257 @@ mod tests {
             )]
         );
     }
+
+    #[test]
+    fn applies_uncollected_jsx_options_to_jsx_syntax_and_compiled_calls() {
+        let mut options = InstrumentationOptions::default();
+        options.privacy.uncollected_jsx_elements = vec!["Icon*".into()];
+        options.privacy.uncollected_jsx_attributes = vec!["variant".into(), "aria-*".into()];
+
+        let (dictionary_tracker, _) = walk_code_with_options(
+            r#"
+              const a = <IconClose title="Close icon">Icon text</IconClose>;
+              const b = <Button variant="primary" aria-label="Label" title="Title">Text</Button>;
+              const c = jsx(IconOpen, { title: "Open icon" });
+              const d = React.createElement(Button, { variant: "secondary", "aria-hidden": "true" }, "Child");
+            "#,
+            &options,
+        );
+        assert_eq!(
+            dictionary_tracker
+                .strings
+                .keys()
+                .cloned()
+                .collect::<Vec<DictionaryEntry>>(),
+            vec![
+                DictionaryEntry::String("'Title'".into()),
+                DictionaryEntry::String("'Text'".into()),
+                DictionaryEntry::String("'Child'".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn applies_built_in_uncollected_elements_to_intrinsic_elements_only() {
+        let (dictionary_tracker, _) = walk_code(
+            r#"
+              const a = <svg><g><text>Inside a group</text></g></svg>;
+              const b = jsx("path", { children: "Inside a path" });
+              const c = jsx(g, { title: "Minified component" });
+              const d = React.createElement(path, null, "Component child");
+            "#,
+        );
+        assert_eq!(
+            dictionary_tracker
+                .strings
+                .keys()
+                .cloned()
+                .collect::<Vec<DictionaryEntry>>(),
+            vec![
+                DictionaryEntry::String("'Minified component'".into()),
+                DictionaryEntry::String("'Component child'".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn tags_strings_with_their_context() {
+        let (dictionary_tracker, _) = walk_code(
+            r#"
+              const a = <Label title="Attribute">Text {'Expression'}</Label>;
+              const b = React.createElement(Label, { title: "Prop" }, "Child");
+              const c = { 'key': 'value' };
+              console.warn('Logged');
+              switch (action) { case 'close': break; }
+              throw new TypeError(`Failed`);
+            "#,
+        );
+        assert_eq!(
+            dictionary_tracker
+                .strings
+                .iter()
+                .map(|(entry, stats)| (entry.clone(), stats.category()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    DictionaryEntry::String("'Attribute'".into()),
+                    ContextCategory::JsxAttribute
+                ),
+                (
+                    DictionaryEntry::String("'Text '".into()),
+                    ContextCategory::JsxText
+                ),
+                (
+                    DictionaryEntry::String("'Expression'".into()),
+                    ContextCategory::JsxText
+                ),
+                (
+                    DictionaryEntry::String("'Prop'".into()),
+                    ContextCategory::JsxAttribute
+                ),
+                (
+                    DictionaryEntry::String("'Child'".into()),
+                    ContextCategory::JsxText
+                ),
+                (
+                    DictionaryEntry::String("'key'".into()),
+                    ContextCategory::PropertyKey
+                ),
+                (
+                    DictionaryEntry::String("'value'".into()),
+                    ContextCategory::Other
+                ),
+                (
+                    DictionaryEntry::String("'Logged'".into()),
+                    ContextCategory::ConsoleArgument
+                ),
+                (
+                    DictionaryEntry::String("'close'".into()),
+                    ContextCategory::SwitchCase
+                ),
+                (
+                    DictionaryEntry::String("'Failed'".into()),
+                    ContextCategory::ErrorMessage
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn recognizes_compiled_jsx_through_runtime_bindings() {
+        let (dictionary_tracker, _) = walk_code(
+            r#"
+              import { jsx as _j } from "react/jsx-runtime";
+              var _jsxRuntime = require("react/jsx-runtime");
+              var _react = _interopRequireDefault(require("react"));
+              var r = require("react/jsx-runtime");
+              const a = (0, _jsxRuntime.jsx)("div", { className: "card", children: "First" });
+              const b = _react.default.createElement("span", { style: { color: "red" } }, "Second");
+              const c = r.jsx("p", { className: "text", children: "Third" });
+              const d = _j("h1", { className: "title", children: "Fourth" });
+            "#,
+        );
+        assert_eq!(
+            dictionary_tracker
+                .strings
+                .keys()
+                .cloned()
+                .collect::<Vec<DictionaryEntry>>(),
+            vec![
+                DictionaryEntry::String("'First'".into()),
+                DictionaryEntry::String("'Second'".into()),
+                DictionaryEntry::String("'Third'".into()),
+                DictionaryEntry::String("'Fourth'".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_parameters_that_shadow_runtime_bindings() {
+        let (dictionary_tracker, _) = walk_code(
+            r#"
+              import { jsx as _j } from "react/jsx-runtime";
+              function f(_j) {
+                return _j("shadowed", { title: "Some title" });
+              }
+              const a = _j("div", { children: "Outside" });
+            "#,
+        );
+        assert_eq!(
+            dictionary_tracker
+                .strings
+                .keys()
+                .cloned()
+                .collect::<Vec<DictionaryEntry>>(),
+            vec![
+                DictionaryEntry::String("'shadowed'".into()),
+                DictionaryEntry::String("'Some title'".into()),
+                DictionaryEntry::String("'Outside'".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn recognizes_element_factories_from_selected_presets() {
+        let mut options = InstrumentationOptions::default();
+        options.privacy.element_factory_presets = vec![
+            ElementFactoryPreset::Vue,
+            ElementFactoryPreset::Solid,
+            ElementFactoryPreset::Inferno,
+        ];
+        let (dictionary_tracker, _) = walk_code_with_options(
+            r#"
+              import { createElementVNode as _cev, createTextVNode as _ctv } from "vue";
+              import { template as _$template } from "solid-js/web";
+              import { createVNode, createComponentVNode } from "inferno";
+              const a = _cev("p", { title: "First" }, "Second", 8, ["title"]);
+              const b = _ctv("Third", 1);
+              const c = _$template(`<div>Markup</div>`);
+              const d = createVNode(1, "div", "card", "Fourth", 16, { title: "Fifth" }, "key");
+              const e = createComponentVNode(2, Button, { label: "Sixth" }, "key");
+            "#,
+            &options,
+        );
+        assert_eq!(
+            dictionary_tracker
+                .strings
+                .keys()
+                .cloned()
+                .collect::<Vec<DictionaryEntry>>(),
+            vec![
+                DictionaryEntry::String("'First'".into()),
+                DictionaryEntry::String("'Second'".into()),
+                DictionaryEntry::String("'Third'".into()),
+                DictionaryEntry::String("'Fourth'".into()),
+                DictionaryEntry::String("'Fifth'".into()),
+                DictionaryEntry::String("'Sixth'".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn does_not_collect_arguments_to_uncollected_calls() {
+        let mut options = InstrumentationOptions::default();
+        options.privacy.uncollected_calls = vec![
+            UncollectedCall {
+                callee: "t".into(),
+                arguments: None,
+            },
+            UncollectedCall {
+                callee: "logger.*".into(),
+                arguments: None,
+            },
+            UncollectedCall {
+                callee: "el.addEventListener".into(),
+                arguments: Some(vec![0]),
+            },
+            UncollectedCall {
+                callee: "CustomEvent".into(),
+                arguments: Some(vec![0]),
+            },
+        ];
+
+        let (dictionary_tracker, _) = walk_code_with_options(
+            r#"
+              t('translation.key');
+              logger.debug('Debug message');
+              el.addEventListener('click', () => alert('Clicked'));
+              new CustomEvent('custom', 'Detail');
+              other('Collected');
+            "#,
+            &options,
+        );
+        assert_eq!(
+            dictionary_tracker
+                .strings
+                .keys()
+                .cloned()
+                .collect::<Vec<DictionaryEntry>>(),
+            vec![
+                DictionaryEntry::String("'Clicked'".into()),
+                DictionaryEntry::String("'Detail'".into()),
+                DictionaryEntry::String("'Collected'".into()),
+            ]
+        );
+    }
 }
@@ -0,0 +1,81 @@
+use js_instrumentation_shared::instrumentation_options::PrivacyOptions;
+use swc_ecma_ast::{Expr, MemberExpr, MemberProp};
+
+use super::name_pattern_set::NamePatternSet;
+
+/// Describes which arguments of a call we should not collect strings from.
+pub enum UncollectedArgs {
+    All,
+    Indices(Vec<usize>),
+}
+
+impl UncollectedArgs {
+    pub fn includes(self: &Self, index: usize) -> bool {
+        match self {
+            UncollectedArgs::All => true,
+            UncollectedArgs::Indices(indices) => indices.contains(&index),
+        }
+    }
+}
+
+struct UncollectedCallRule {
+    callee: NamePatternSet,
+    args: UncollectedArgs,
+}
+
+/// Decides which call and constructor expressions we never collect strings from, beyond the
+/// built-in ones (e.g. `eval()` or `new RegExp()`), which the visitor handles itself.
+#[derive(Default)]
+pub struct CallFilter {
+    rules: Vec<UncollectedCallRule>,
+}
+
+impl CallFilter {
+    pub fn new(options: &PrivacyOptions) -> Result<CallFilter, regex::Error> {
+        let rules = options
+            .uncollected_calls
+            .iter()
+            .map(|call| {
+                Ok(UncollectedCallRule {
+                    callee: NamePatternSet::new(&[call.callee.clone()])?,
+                    args: match call.arguments {
+                        Some(ref indices) => UncollectedArgs::Indices(indices.clone()),
+                        None => UncollectedArgs::All,
+                    },
+                })
+            })
+            .collect::<Result<Vec<_>, regex::Error>>()?;
+
+        Ok(CallFilter { rules })
+    }
+
+    /// Returns the arguments we shouldn't collect for a call to the given callee, or None if
+    /// the callee doesn't match any rule. If several rules match, the first one wins.
+    pub fn uncollected_args_for_callee(self: &Self, callee: &Expr) -> Option<&UncollectedArgs> {
+        if self.rules.is_empty() {
+            return None;
+        }
+
+        let path = callee_path(callee)?;
+        self.rules
+            .iter()
+            .find(|rule| rule.callee.matches(&path))
+            .map(|rule| &rule.args)
+    }
+}
+
+/// Returns a dotted path for callees like `t` or `document.querySelector`, or None if the
+/// callee is too complex to describe that way.
+pub fn callee_path(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Ident(ref ident) => Some(ident.sym.to_string()),
+        Expr::This(_) => Some("this".into()),
+        Expr::Paren(ref paren) => callee_path(&paren.expr),
+        Expr::Member(MemberExpr {
+            ref obj,
+            prop: MemberProp::Ident(ref prop),
+            ..
+        }) => Some(format!("{}.{}", callee_path(obj)?, prop.sym)),
+        _ => None,
+    }
+}
@@ -0,0 +1,336 @@
+use js_instrumentation_shared::instrumentation_options::{
+    ElementFactory, ElementFactoryImport, ElementFactoryPreset, PrivacyOptions,
+};
+use swc_ecma_ast::Expr;
+
+use super::{call_filter::callee_path, name_pattern_set::NamePatternSet, ImportBindings};
+
+struct ElementFactoryRule {
+    callees: NamePatternSet,
+    factory: ElementFactory,
+}
+
+/// Decides which calls create UI elements, like `React.createElement()` or Preact's `h()`.
+/// Calls to element factories are compiled JSX (or templates), and the visitor collects them
+/// like JSX.
+#[derive(Default)]
+pub struct ElementFactories {
+    rules: Vec<ElementFactoryRule>,
+}
+
+impl ElementFactories {
+    pub fn new(options: &PrivacyOptions) -> Result<ElementFactories, regex::Error> {
+        let rules = options
+            .element_factories
+            .iter()
+            .cloned()
+            .chain(
+                options
+                    .element_factory_presets
+                    .iter()
+                    .flat_map(|preset| preset_factories(*preset)),
+            )
+            .map(|factory| {
+                Ok(ElementFactoryRule {
+                    callees: NamePatternSet::new(&factory.callees)?,
+                    factory,
+                })
+            })
+            .collect::<Result<Vec<_>, regex::Error>>()?;
+
+        Ok(ElementFactories { rules })
+    }
+
+    /// Returns the element factory that a call to the given callee invokes, or None if the
+    /// callee isn't an element factory. The callee matches a factory if it refers to the
+    /// factory's import through one of the program's bindings, or if its path matches one of
+    /// the factory's callee patterns. If several factories match, the first one wins.
+    pub fn factory_for_callee(
+        self: &Self,
+        callee: &Expr,
+        bindings: &ImportBindings,
+    ) -> Option<&ElementFactory> {
+        if self.rules.is_empty() {
+            return None;
+        }
+
+        let imported = bindings.imported_export(callee);
+        let path = callee_path(without_this_value_wrapper(callee));
+        self.rules
+            .iter()
+            .find(|rule| {
+                let matches_import = match (&rule.factory.import, imported) {
+                    (Some(import), Some((module, name))) => {
+                        import.module == module && import.name == name
+                    }
+                    _ => false,
+                };
+                matches_import || path.as_ref().is_some_and(|path| rule.callees.matches(path))
+            })
+            .map(|rule| &rule.factory)
+    }
+}
+
+/// Strips the `(0, f)` wrapper that compilers emit to call a function without a `this` value.
+fn without_this_value_wrapper(callee: &Expr) -> &Expr {
+    match callee {
+        Expr::Paren(paren) => without_this_value_wrapper(&paren.expr),
+        Expr::Seq(seq) => match seq.exprs.last() {
+            Some(last) => without_this_value_wrapper(last),
+            None => callee,
+        },
+        _ => callee,
+    }
+}
+
+fn factory(
+    callees: &[&str],
+    import: Option<(&str, &str)>,
+    tag_argument: Option<usize>,
+    props_argument: Option<usize>,
+    uncollected_arguments: &[usize],
+) -> ElementFactory {
+    ElementFactory {
+        callees: callees.iter().map(|callee| callee.to_string()).collect(),
+        import: import.map(|(module, name)| ElementFactoryImport {
+            module: module.to_string(),
+            name: name.to_string(),
+        }),
+        tag_argument,
+        props_argument,
+        uncollected_arguments: uncollected_arguments.to_vec(),
+    }
+}
+
+/// Returns the built-in element factories for the given framework.
+fn preset_factories(preset: ElementFactoryPreset) -> Vec<ElementFactory> {
+    match preset {
+        // jsx(type, props, key), createElement(type, props, ...children). Compilers that
+        // assume the automatic runtime is in scope call it by its usual names.
+        ElementFactoryPreset::React => vec![
+            factory(
+                &["jsx", "_jsx", "jsxs", "_jsxs", "jsxDEV", "_jsxDEV"],
+                None,
+                Some(0),
+                Some(1),
+                &[],
+            ),
+            factory(
+                &["React.createElement"],
+                Some(("react", "createElement")),
+                Some(0),
+                Some(1),
+                &[],
+            ),
+            factory(
+                &[],
+                Some(("react/jsx-runtime", "jsx")),
+                Some(0),
+                Some(1),
+                &[],
+            ),
+            factory(
+                &[],
+                Some(("react/jsx-runtime", "jsxs")),
+                Some(0),
+                Some(1),
+                &[],
+            ),
+            factory(
+                &[],
+                Some(("react/jsx-dev-runtime", "jsxDEV")),
+                Some(0),
+                Some(1),
+                &[],
+            ),
+        ],
+        // h(type, props, ...children), and an automatic JSX runtime like React's.
+        ElementFactoryPreset::Preact => vec![
+            factory(&["h"], Some(("preact", "h")), Some(0), Some(1), &[]),
+            factory(
+                &[],
+                Some(("preact", "createElement")),
+                Some(0),
+                Some(1),
+                &[],
+            ),
+            factory(
+                &[],
+                Some(("preact/jsx-runtime", "jsx")),
+                Some(0),
+                Some(1),
+                &[],
+            ),
+            factory(
+                &[],
+                Some(("preact/jsx-runtime", "jsxs")),
+                Some(0),
+                Some(1),
+                &[],
+            ),
+            factory(
+                &[],
+                Some(("preact/jsx-dev-runtime", "jsxDEV")),
+                Some(0),
+                Some(1),
+                &[],
+            ),
+        ],
+        // h(type, props, children), and the functions that compiled templates import under
+        // underscored aliases: createElementVNode(type, props, children, patchFlag,
+        // dynamicProps) and friends, and createTextVNode(text, patchFlag).
+        ElementFactoryPreset::Vue => {
+            let mut factories = vec![factory(&["h"], Some(("vue", "h")), Some(0), Some(1), &[])];
+            for name in [
+                "createElementVNode",
+                "createElementBlock",
+                "createVNode",
+                "createBlock",
+            ] {
+                factories.push(factory(
+                    &[&format!("_{}", name)],
+                    Some(("vue", name)),
+                    Some(0),
+                    Some(1),
+                    &[3, 4],
+                ));
+            }
+            factories.push(factory(
+                &["_createTextVNode"],
+                Some(("vue", "createTextVNode")),
+                None,
+                None,
+                &[1],
+            ));
+            factories
+        }
+        // template(html), which compiled JSX calls with the element's markup.
+        ElementFactoryPreset::Solid => vec![factory(
+            &["_$template"],
+            Some(("solid-js/web", "template")),
+            Some(0),
+            None,
+            &[],
+        )],
+        // createVNode(flags, type, className, children, childFlags, props, key, ref),
+        // createComponentVNode(flags, type, props, key, ref), and createTextVNode(text, key).
+        ElementFactoryPreset::Inferno => vec![
+            factory(
+                &["createVNode"],
+                Some(("inferno", "createVNode")),
+                Some(1),
+                Some(5),
+                &[0, 2, 4, 6, 7],
+            ),
+            factory(
+                &["createComponentVNode"],
+                Some(("inferno", "createComponentVNode")),
+                Some(1),
+                Some(2),
+                &[0, 3, 4],
+            ),
+            factory(
+                &["createTextVNode"],
+                Some(("inferno", "createTextVNode")),
+                None,
+                None,
+                &[1],
+            ),
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use js_instrumentation_shared::{build_parser, InputFile, InstrumentationOptions};
+    use swc_common::comments::SingleThreadedComments;
+    use swc_ecma_ast::{Callee, ExprStmt, ModuleItem, Program, Stmt};
+
+    use super::*;
+
+    /// Parses the given program, and returns the tag argument of the element factory that
+    /// each of its expression statements calls, or None if it doesn't call one.
+    fn factory_tag_arguments(code: &str, options: &PrivacyOptions) -> Vec<Option<Option<usize>>> {
+        let input_file = InputFile::new("test.js", code);
+        let comments: SingleThreadedComments = Default::default();
+        let program = build_parser(&input_file, &comments, &InstrumentationOptions::default())
+            .parse_program()
+            .unwrap();
+        let bindings = ImportBindings::collect(&program);
+        let factories = ElementFactories::new(options).unwrap();
+
+        let Program::Module(module) = program else {
+            panic!("Expected a module");
+        };
+        module
+            .body
+            .iter()
+            .filter_map(|item| match item {
+                ModuleItem::Stmt(Stmt::Expr(ExprStmt { expr, .. })) => match **expr {
+                    Expr::Call(ref call) => match call.callee {
+                        Callee::Expr(ref callee) => Some(
+                            factories
+                                .factory_for_callee(callee, &bindings)
+                                .map(|factory| factory.tag_argument),
+                        ),
+                        _ => Some(None),
+                    },
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn recognizes_react_factories_by_default() {
+        let options = InstrumentationOptions::default().privacy;
+        assert_eq!(
+            factory_tag_arguments(
+                r#"
+                  import { jsx as _j } from "react/jsx-runtime";
+                  import { h } from "preact";
+                  _j("div", {});
+                  (0, _jsx)("div", {});
+                  React.createElement("div");
+                  h("div");
+                "#,
+                &options
+            ),
+            vec![Some(Some(0)), Some(Some(0)), Some(Some(0)), None]
+        );
+    }
+
+    #[test]
+    fn recognizes_selected_presets_and_custom_factories() {
+        let mut options = InstrumentationOptions::default().privacy;
+        options.element_factory_presets =
+            vec![ElementFactoryPreset::Preact, ElementFactoryPreset::Inferno];
+        options.element_factories = vec![ElementFactory {
+            callees: vec!["m".into()],
+            tag_argument: Some(2),
+            ..Default::default()
+        }];
+        assert_eq!(
+            factory_tag_arguments(
+                r#"
+                  import { h as preactH } from "preact";
+                  import { createVNode as cv } from "inferno";
+                  preactH("div");
+                  h("div");
+                  cv(1, "div");
+                  m(0, 0, "div");
+                  React.createElement("div");
+                "#,
+                &options
+            ),
+            vec![
+                Some(Some(0)),
+                Some(Some(0)),
+                Some(Some(1)),
+                Some(Some(2)),
+                None
+            ]
+        );
+    }
+}
@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+
+use swc_common::{Globals, Mark, GLOBALS};
+use swc_ecma_ast::{
+    AssignExpr, AssignTarget, Callee, Expr, Id, Ident, ImportDecl, ImportSpecifier, Lit,
+    MemberProp, ModuleExportName, ObjectPatProp, Pat, Program, PropName, SimpleAssignTarget,
+    VarDeclarator,
+};
+use swc_ecma_transforms_base::resolver;
+use swc_ecma_visit::{Visit, VisitMutWith, VisitWith};
+
+/// Marks each identifier in the program with the scope of the binding it refers to, so that
+/// bindings can be told apart from the local variables that shadow them.
+pub fn resolve_scopes(program: &mut Program) {
+    GLOBALS.set(&Globals::new(), || {
+        program.visit_mut_with(&mut resolver(Mark::new(), Mark::new(), true));
+    });
+}
+
+/// The local bindings in a program that refer to other modules or to their exports. Compiled
+/// code calls imported functions through these bindings, e.g. `(0, _jsxRuntime.jsx)(...)`.
+#[derive(Default)]
+pub struct ImportBindings {
+    /// Bindings that refer to a module's export, with the module and the export's name, e.g.
+    /// `_j` in `import { jsx as _j } from "react/jsx-runtime"`.
+    exports: HashMap<Id, (String, String)>,
+    /// Bindings that refer to a whole module, e.g. `r` in `const r = require("react")`.
+    namespaces: HashMap<Id, String>,
+}
+
+impl ImportBindings {
+    /// Finds the bindings in the given program. Bindings are tracked by name and scope, so the
+    /// program's scopes must already have been resolved with `resolve_scopes()`; otherwise,
+    /// a local variable that shadows a binding is mistaken for it.
+    pub fn collect(program: &Program) -> ImportBindings {
+        let mut bindings = ImportBindings::default();
+        program.visit_with(&mut bindings);
+        bindings
+    }
+
+    /// Returns the module and the export name that the given expression refers to, or None if
+    /// it doesn't refer to another module's export.
+    pub fn imported_export<'a>(self: &'a Self, expr: &'a Expr) -> Option<(&'a str, &'a str)> {
+        match expr {
+            Expr::Paren(paren) => self.imported_export(&paren.expr),
+            // `(0, _jsxRuntime.jsx)(...)`, which compilers emit to call an imported function
+            // without a `this` value.
+            Expr::Seq(seq) => self.imported_export(seq.exprs.last()?),
+            Expr::Ident(ident) => self
+                .exports
+                .get(&ident.to_id())
+                .map(|(module, name)| (module.as_str(), name.as_str())),
+            Expr::Member(member) => match member.prop {
+                MemberProp::Ident(ref prop) => self
+                    .namespace_module(&member.obj)
+                    .map(|module| (module, prop.sym.as_str())),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the module that the given expression refers to.
+    fn namespace_module(self: &Self, expr: &Expr) -> Option<&str> {
+        match expr {
+            Expr::Paren(paren) => self.namespace_module(&paren.expr),
+            Expr::Ident(ident) => self
+                .namespaces
+                .get(&ident.to_id())
+                .map(|module| module.as_str()),
+            // `_react.default.createElement(...)`, where `_react` is the result of an interop
+            // helper like `_interopRequireDefault(require("react"))`.
+            Expr::Member(member) => match member.prop {
+                MemberProp::Ident(ref prop) if prop.sym.as_str() == "default" => {
+                    self.namespace_module(&member.obj)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn add_namespace(self: &mut Self, local: &Ident, module: &str) {
+        self.namespaces.insert(local.to_id(), module.to_string());
+    }
+
+    fn add_export(self: &mut Self, local: &Ident, imported: &str, module: &str) {
+        self.exports
+            .insert(local.to_id(), (module.to_string(), imported.to_string()));
+    }
+}
+
+impl Visit for ImportBindings {
+    fn visit_import_decl(&mut self, node: &ImportDecl) {
+        let module = node.src.value.as_str();
+        for specifier in &node.specifiers {
+            match specifier {
+                ImportSpecifier::Named(named) => {
+                    let imported = match named.imported {
+                        Some(ModuleExportName::Ident(ref ident)) => ident.sym.as_str(),
+                        Some(ModuleExportName::Str(ref str)) => str.value.as_str(),
+                        None => named.local.sym.as_str(),
+                    };
+                    self.add_export(&named.local, imported, module);
+                }
+                // Default exports are treated like namespaces, since that's how interop
+                // helpers expose CommonJS modules like React.
+                ImportSpecifier::Default(default) => self.add_namespace(&default.local, module),
+                ImportSpecifier::Namespace(namespace) => {
+                    self.add_namespace(&namespace.local, module)
+                }
+            }
+        }
+    }
+
+    fn visit_var_declarator(&mut self, node: &VarDeclarator) {
+        let Some(module) = node.init.as_deref().and_then(required_module) else {
+            node.visit_children_with(self);
+            return;
+        };
+
+        match node.name {
+            // const _jsxRuntime = require("react/jsx-runtime");
+            Pat::Ident(ref ident) => self.add_namespace(&ident.id, module),
+            // const { jsx: _jsx } = require("react/jsx-runtime");
+            Pat::Object(ref object) => {
+                for prop in &object.props {
+                    match prop {
+                        ObjectPatProp::KeyValue(key_value) => {
+                            let imported = match key_value.key {
+                                PropName::Ident(ref ident) => ident.sym.as_str(),
+                                PropName::Str(ref str) => str.value.as_str(),
+                                _ => continue,
+                            };
+                            if let Pat::Ident(ref local) = *key_value.value {
+                                self.add_export(&local.id, imported, module);
+                            }
+                        }
+                        ObjectPatProp::Assign(assign) => {
+                            self.add_export(&assign.key.id, assign.key.id.sym.as_str(), module)
+                        }
+                        ObjectPatProp::Rest(_) => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_assign_expr(&mut self, node: &AssignExpr) {
+        // Minifiers often turn declarations into assignments, e.g. `r = require("react")`.
+        if let AssignTarget::Simple(SimpleAssignTarget::Ident(ref ident)) = node.left {
+            if let Some(module) = required_module(&node.right) {
+                self.add_namespace(&ident.id, module);
+                return;
+            }
+        }
+
+        node.visit_children_with(self);
+    }
+}
+
+/// Returns the module that the given expression requires, if it's a `require()` call, possibly
+/// wrapped in interop helpers, e.g. `_interopRequireDefault(require("react"))`.
+fn required_module(expr: &Expr) -> Option<&str> {
+    match expr {
+        Expr::Paren(paren) => required_module(&paren.expr),
+        Expr::Call(call) => match call.callee {
+            Callee::Expr(ref callee) if matches!(**callee, Expr::Ident(ref ident) if ident.sym.as_str() == "require") => {
+                match *call.args.first()?.expr {
+                    Expr::Lit(Lit::Str(ref str)) => Some(str.value.as_str()),
+                    _ => None,
+                }
+            }
+            _ => call.args.iter().find_map(|arg| required_module(&arg.expr)),
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use js_instrumentation_shared::{build_parser, InputFile, InstrumentationOptions};
+    use swc_common::comments::SingleThreadedComments;
+    use swc_ecma_ast::{ExprStmt, ModuleItem, Stmt};
+
+    use super::*;
+
+    /// Parses the given program, and returns the export that each of its expression statements
+    /// calls, formatted as `module:name`.
+    fn called_exports(code: &str) -> Vec<Option<String>> {
+        let input_file = InputFile::new("test.js", code);
+        let comments: SingleThreadedComments = Default::default();
+        let options = InstrumentationOptions::default();
+        let program = build_parser(&input_file, &comments, &options)
+            .parse_program()
+            .unwrap();
+        let bindings = ImportBindings::collect(&program);
+
+        let items: Vec<ModuleItem> = match program {
+            Program::Module(module) => module.body,
+            Program::Script(script) => script.body.into_iter().map(ModuleItem::Stmt).collect(),
+        };
+        items
+            .iter()
+            .filter_map(|item| match item {
+                ModuleItem::Stmt(Stmt::Expr(ExprStmt { expr, .. })) => match **expr {
+                    Expr::Call(ref call) => match call.callee {
+                        Callee::Expr(ref callee) => Some(
+                            bindings
+                                .imported_export(callee)
+                                .map(|(module, name)| format!("{}:{}", module, name)),
+                        ),
+                        _ => Some(None),
+                    },
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn resolves_imported_bindings() {
+        assert_eq!(
+            called_exports(
+                r#"
+                  import { jsx as _j, "jsxs" as jsxs } from "react/jsx-runtime";
+                  import * as dev from "react/jsx-dev-runtime";
+                  import R, { createElement as h } from "react";
+                  _j("div", {});
+                  jsxs("div", {});
+                  dev.jsxDEV("div", {});
+                  R.createElement("div");
+                  h("div");
+                  local("div");
+                "#
+            ),
+            vec![
+                Some("react/jsx-runtime:jsx".into()),
+                Some("react/jsx-runtime:jsxs".into()),
+                Some("react/jsx-dev-runtime:jsxDEV".into()),
+                Some("react:createElement".into()),
+                Some("react:createElement".into()),
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn resolves_required_bindings() {
+        assert_eq!(
+            called_exports(
+                r#"
+                  var _jsxRuntime = require("react/jsx-runtime");
+                  var _react = _interopRequireDefault(require("react"));
+                  const { jsx: _jsx2, h } = require("preact");
+                  var r;
+                  r = require("vue");
+                  (0, _jsxRuntime.jsx)("div", {});
+                  _react.default.createElement("div");
+                  _jsx2("div", {});
+                  h("div");
+                  r.h("div");
+                  other.jsx("div", {});
+                  (0, other)("div");
+                "#
+            ),
+            vec![
+                Some("react/jsx-runtime:jsx".into()),
+                Some("react:createElement".into()),
+                Some("preact:jsx".into()),
+                Some("preact:h".into()),
+                Some("vue:h".into()),
+                None,
+                None,
+            ]
+        );
+    }
+}
@@ -0,0 +1,43 @@
+use js_instrumentation_shared::instrumentation_options::PrivacyOptions;
+
+use super::name_pattern_set::NamePatternSet;
+
+/// Decides which JSX elements and attributes we never collect strings from. The same rules
+/// apply to JSX syntax and to compiled JSX (e.g. `jsx()` or `React.createElement()` calls).
+#[derive(Default)]
+pub struct JSXFilter {
+    uncollected_elements: NamePatternSet,
+    uncollected_attrs: NamePatternSet,
+}
+
+impl JSXFilter {
+    pub fn new(options: &PrivacyOptions) -> Result<JSXFilter, regex::Error> {
+        Ok(JSXFilter {
+            uncollected_elements: NamePatternSet::new(&options.uncollected_jsx_elements)?,
+            uncollected_attrs: NamePatternSet::new(&options.uncollected_jsx_attributes)?,
+        })
+    }
+
+    /// Returns true if we never collect strings from the intrinsic element with the given tag
+    /// (e.g. `<path>` in JSX or "path" in compiled JSX).
+    pub fn is_uncollected_element(self: &Self, name: &str) -> bool {
+        match name {
+            "g" | "path" => true,
+            _ => self.uncollected_elements.matches(name),
+        }
+    }
+
+    /// Returns true if we never collect strings from the component with the given name (e.g.
+    /// `Icon` or `Icons.Close`). The built-in SVG elements don't apply to components, since a
+    /// minifier may well have renamed one to `g`.
+    pub fn is_uncollected_component(self: &Self, name: &str) -> bool {
+        self.uncollected_elements.matches(name)
+    }
+
+    pub fn is_uncollected_attr(self: &Self, name: &str) -> bool {
+        match name {
+            "class" | "className" | "d" | "id" | "src" | "srcset" | "style" => true,
+            _ => self.uncollected_attrs.matches(name),
+        }
+    }
+}
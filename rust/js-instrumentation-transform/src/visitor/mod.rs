@@ -1,2 +1,16 @@
 mod ast_visitor;
 pub use ast_visitor::visit;
+
+mod call_filter;
+pub use call_filter::CallFilter;
+
+mod element_factories;
+pub use element_factories::ElementFactories;
+
+mod import_bindings;
+pub use import_bindings::{resolve_scopes, ImportBindings};
+
+mod jsx_filter;
+pub use jsx_filter::JSXFilter;
+
+mod name_pattern_set;
@@ -0,0 +1,38 @@
+use regex::Regex;
+
+/// A set of name patterns, compiled into a single regular expression. Patterns are matched
+/// against the entire name, and may contain `*` wildcards, which match any sequence of
+/// characters; this makes it possible to express prefix matches like `aria-*`.
+#[derive(Default)]
+pub struct NamePatternSet {
+    regex: Option<Regex>,
+}
+
+impl NamePatternSet {
+    pub fn new(patterns: &[String]) -> Result<NamePatternSet, regex::Error> {
+        if patterns.is_empty() {
+            return Ok(NamePatternSet { regex: None });
+        }
+
+        let alternatives: Vec<String> = patterns
+            .iter()
+            .map(|pattern| {
+                pattern
+                    .split('*')
+                    .map(|literal| regex::escape(literal))
+                    .collect::<Vec<String>>()
+                    .join(".*")
+            })
+            .collect();
+        let regex = Regex::new(&format!("^(?:{})$", alternatives.join("|")))?;
+
+        Ok(NamePatternSet { regex: Some(regex) })
+    }
+
+    pub fn matches(self: &Self, name: &str) -> bool {
+        match self.regex {
+            Some(ref regex) => regex.is_match(name),
+            None => false,
+        }
+    }
+}
@@ -1,6 +1,7 @@
 import {
   initSync,
-  transform
+  transform,
+  transform_batch
 } from '../rust/datadog-js-instrumentation/pkg/datadog_js_instrumentation.js';
 import {
   default as plugin
@@ -15,6 +16,19 @@ export interface InstrumentationInput {
   map?: string;
 }
 
+export interface InstrumentationBatchInput {
+  /**
+   * The files to instrument. They'll share a single privacy dictionary, so the files that
+   * collect any strings must all be the same kind of module as the shared dictionary module.
+   */
+  inputs: InstrumentationInput[];
+  /**
+   * The filename or id of the shared dictionary module. Instrumented files import the
+   * dictionary using this id as the module specifier.
+   */
+  sharedDictionaryId: string;
+}
+
 export interface InstrumentationOutput {
   /** The filename or id of the code that was instrumented. */
   id: string;
@@ -30,18 +44,172 @@ export interface InstrumentationOutput {
   map?: string;
   /** The number of items in the privacy dictionary generated for this file. */
   privacyDictionarySize: number;
+  /**
+   * A report listing the entries in the privacy dictionary generated for this file.
+   * Only generated if `privacyDictionaryReport` is enabled in the output options.
+   */
+  report?: PrivacyDictionaryReport;
+  /** The module kind the instrumented code was generated for, and why it was chosen. */
+  moduleKind: ModuleKindDetection;
+  /** Problems that didn't prevent instrumentation, but may be worth reporting. */
+  warnings: Diagnostic[];
+}
+
+export interface ModuleKindDetection {
+  kind: 'cjs' | 'esm' | 'script';
+  /**
+   * What decided the module kind: the `module` input option ('configured'), the file
+   * extension ('filename'), signals found in the code ('signals'), or nothing, in which
+   * case ESM is assumed ('default').
+   */
+  reason: 'configured' | 'filename' | 'signals' | 'default';
+  /** The signals that decided the module kind. Empty unless `reason` is 'signals'. */
+  signals: ModuleSignal[];
+}
+
+/** A piece of evidence about a file's module format, found in its source code. */
+export type ModuleSignal =
+  | 'importDeclaration'
+  | 'exportDeclaration'
+  | 'importMeta'
+  | 'topLevelAwait'
+  | 'moduleSyntax'
+  | 'require'
+  | 'exports'
+  | 'moduleExports'
+  | 'tsImportEquals'
+  | 'tsExportAssignment'
+  | 'amdDefine'
+  | 'umdWrapper';
+
+export interface Diagnostic {
+  /** A stable identifier for the kind of problem. */
+  code:
+    | 'dictionaryBudgetExceeded'
+    | 'externalSourceMap'
+    | 'inlineSourceMapIgnored'
+    | 'recoveredParseError'
+    | 'rewriteOverlap'
+    | 'sensitiveStringRejected'
+    | 'syntaxFallback'
+    | 'templateEvaluationFailed'
+    | 'unbalancedExcludeDirective'
+    | 'unsupportedMarkup';
+  /** A human-readable description of the problem. */
+  message: string;
+  /** The line (1-based) where the problem occurred, if it has a location. */
+  line?: number;
+  /** The column (0-based) where the problem occurred, if it has a location. */
+  column?: number;
+}
+
+export interface SharedDictionaryOutput {
+  /** The filename or id of the shared dictionary module. */
+  id: string;
+  /** The source code of the shared dictionary module. */
+  code: string;
+  /** The number of items in the shared privacy dictionary. */
+  privacyDictionarySize: number;
+}
+
+export interface InstrumentationBatchOutput {
+  /** The instrumented files, in the same order as the inputs. */
+  outputs: InstrumentationOutput[];
+  /** The module containing the privacy dictionary shared by the instrumented files. */
+  sharedDictionary: SharedDictionaryOutput;
+}
+
+/**
+ * A value that was added to the privacy dictionary. Strings include their surrounding
+ * quotes. Tagged templates are represented by their quasis.
+ */
+export type PrivacyDictionaryReportValue =
+  | { string: string }
+  | { taggedTemplate: string[] }
+  | { templateQuasi: string };
+
+/** The syntactic context that a privacy dictionary value was collected from. */
+export type ContextCategory =
+  | 'jsxText'
+  | 'jsxAttribute'
+  | 'propertyKey'
+  | 'errorMessage'
+  | 'consoleArgument'
+  | 'switchCase'
+  | 'other';
+
+export interface PrivacyDictionaryReportEntry {
+  /** The value that was added to the dictionary. */
+  value: PrivacyDictionaryReportValue;
+  /** The number of times the value appears in the file. */
+  count: number;
+  /** The line (1-based) where the value first appears in the file. */
+  line: number;
+  /** The column (0-based) where the value first appears in the file. */
+  column: number;
+  /**
+   * The index of the value in the privacy dictionary. Absent if the value was left out of
+   * the dictionary because nothing references it, and only its words were registered.
+   */
+  dictionaryEntry?: number;
+  /**
+   * The syntactic contexts the value appears in, in the order they first appear in the
+   * file. The first one is the category the value is registered under when the dictionary
+   * is registered one category at a time.
+   */
+  categories: ContextCategory[];
+}
+
+export interface PrivacyDictionaryReport {
+  /** The entries in the privacy dictionary, in dictionary order. */
+  entries: PrivacyDictionaryReportEntry[];
+  /**
+   * The entries that were dropped to keep the dictionary within its budget, in the order
+   * they first appear in the file.
+   */
+  dropped: PrivacyDictionaryReportEntry[];
 }
 
 export interface InputOptions {
   /**
    * If 'cjs', interpret the input as a CommonJS module. If 'esm', interpret the input as
-   * an ES module. If 'undefined' (the default), guess based on the module contents.
+   * an ES module. If 'script', interpret the input as a classic script (e.g. loaded with
+   * `<script>` or `importScripts()`); scripts can't import the helper, so use an
+   * expression or global helper source. The helper is kept out of the global scope, but the
+   * dictionary is still a global binding, since wrapping the script would change the scope
+   * of its own declarations. It's given a name unique to the file (e.g. `D_1e7p1md` for
+   * `app.js`) so that it doesn't collide with other scripts; with
+   * `dictionaryDeclaration: 'function'`, it also becomes a property of the global object.
+   * If 'undefined' (the default), guess based on the module contents.
    */
-  module?: 'cjs' | 'esm' | undefined;
+  module?: 'cjs' | 'esm' | 'script' | undefined;
   /** If true (the default), allow JSX syntax in the input. */
   jsx?: boolean;
   /** If true (the default), allow TypeScript syntax in the input. */
   typescript?: boolean;
+  /**
+   * If true (the default), retry parsing with other JSX and TypeScript settings if the
+   * input can't be parsed with the configured ones. This handles, for example, TypeScript
+   * type assertions like `<T>value` in files that allow JSX.
+   */
+  syntaxFallback?: boolean;
+  /**
+   * If true, fail on any parse error, even ones the parser can recover from. If false
+   * (the default), recoverable parse errors are reported as warnings.
+   */
+  strictParsing?: boolean;
+  /**
+   * The contents of external source maps, keyed by the URL that appears in the
+   * `sourceMappingURL` comment that references them.
+   */
+  externalSourceMaps?: Record<string, string>;
+  /**
+   * Called to load external source maps that don't appear in `externalSourceMaps`. Receives
+   * the URL from the `sourceMappingURL` comment and the id of the file that contains it, and
+   * should return the source map's contents, or undefined if it can't be loaded. If an
+   * external source map can't be loaded, no source map is generated.
+   */
+  resolveSourceMap?: (url: string, id: string) => string | undefined;
 }
 
 export interface OutputOptions {
@@ -49,6 +217,40 @@ export interface OutputOptions {
   inlineSourceMap?: boolean;
   /** If true, embed the code in the source map. The default is true. */
   embedCodeInSourceMap?: boolean;
+  /**
+   * If true, include a report listing the privacy dictionary's entries in the output.
+   * The default is false.
+   */
+  privacyDictionaryReport?: boolean;
+  /**
+   * What to do with an external `sourceMappingURL` comment once the source map it
+   * references has been loaded and chained. If 'remove' (the default), the comment is
+   * removed. If 'keep', it's left as-is. If `{ replace: { url } }`, it's replaced with a
+   * comment referencing `url`.
+   */
+  externalSourceMapComment?: 'remove' | 'keep' | { replace: { url: string } };
+  /**
+   * If true, and the input source map was an index source map (one with `sections`), the
+   * returned source map is also an index source map. Inline source maps are always regular
+   * source maps. The default is false.
+   */
+  indexSourceMap?: boolean;
+  /**
+   * If true, insert the privacy helper import and dictionary declaration after any license
+   * comments (e.g. `/*! ... *\/`) that precede the first statement. The header is always
+   * inserted after any shebang and directive prologue (e.g. "use strict" or "use client").
+   * The default is false.
+   */
+  insertHeaderAfterLicenseComments?: boolean;
+  /**
+   * How to declare the privacy dictionary. If 'const' (the default), it's declared as
+   * `const D=...`, which is the most compact option, but throws a ReferenceError if the
+   * dictionary is accessed before the module body runs, which can happen in an import
+   * cycle. If 'function', it's declared as a hoisted function that builds the dictionary
+   * on first use, and entries are referenced as `D()[0]`; this is slightly larger, but
+   * safe to access at any time.
+   */
+  dictionaryDeclaration?: 'const' | 'function';
 }
 
 /**
@@ -90,13 +292,215 @@ export type ImportPrivacyHelperSource = {
   }
 };
 
+/**
+ * Declare the helper by reading it from the given property path on `globalThis`.
+ *
+ * Example: `{ global: { path: 'DD_PRIVACY.$' } }` will produce output like:
+ * ```js
+ *   const $ = globalThis.DD_PRIVACY.$;
+ * ```
+ *
+ * Unlike an import, this also works in classic scripts.
+ */
+export type GlobalPrivacyHelperSource = {
+  global: { path: string };
+};
+
 export type PrivacyHelperSource =
   | ExpressionPrivacyHelperSource
-  | ImportPrivacyHelperSource;
+  | ImportPrivacyHelperSource
+  | GlobalPrivacyHelperSource;
+
+/**
+ * A rule that decides whether strings are added to the privacy dictionary. A rule
+ * matches a string if every condition it specifies matches.
+ *
+ * Example: `{ action: 'exclude', regex: '^ff_' }` will keep feature flag keys like
+ * `'ff_new_checkout'` out of the dictionary.
+ */
+export interface StringFilterRule {
+  /** Whether matching strings should be included in or excluded from the dictionary. */
+  action: 'include' | 'exclude';
+  /** If specified, the string must match this regular expression. */
+  regex?: string;
+  /** If specified, the string must be at least this many bytes long. */
+  minLength?: number;
+  /** If specified, the string must be at most this many bytes long. */
+  maxLength?: number;
+  /** If specified, the string must start with this prefix. */
+  prefix?: string;
+  /** If specified, the string must end with this suffix. */
+  suffix?: string;
+}
+
+/**
+ * A call or constructor expression whose arguments should never be added to the
+ * privacy dictionary.
+ *
+ * Example: `{ callee: 'el.addEventListener', arguments: [0] }` will keep event names
+ * like `'click'` out of the dictionary.
+ */
+export interface UncollectedCall {
+  /**
+   * The callee, as a bare identifier (e.g. `'t'`) or a member path (e.g.
+   * `'document.querySelector'`). May contain `*` wildcards, e.g. `'logger.*'`.
+   */
+  callee: string;
+  /**
+   * If specified, only the arguments at these indices are uncollected. Otherwise, no
+   * arguments are collected.
+   */
+  arguments?: number[];
+}
+
+/**
+ * A function that creates a UI element, like React's `createElement()` or Preact's `h()`.
+ * Calls to element factories are collected like JSX: the tag is never collected, the props
+ * are collected like JSX attributes, and the remaining arguments like JSX children.
+ */
+export interface ElementFactory {
+  /**
+   * Names that calls to the factory may use, as bare identifiers (e.g. `'h'`) or member
+   * paths (e.g. `'React.createElement'`). May contain `*` wildcards.
+   */
+  callees?: string[];
+  /**
+   * The module export that the factory is. Calls through any binding that imports or
+   * requires it are recognized too, whatever the binding is named.
+   */
+  import?: {
+    module: string;
+    name: string;
+  };
+  /** The index of the argument that holds the element's tag or component. */
+  tagArgument?: number;
+  /** The index of the argument that holds the element's props. */
+  propsArgument?: number;
+  /** The indices of other arguments that are never collected, e.g. flags. */
+  uncollectedArguments?: number[];
+}
+
+/** A built-in set of element factories for a UI framework. */
+export type ElementFactoryPreset = 'react' | 'preact' | 'vue' | 'solid' | 'inferno';
+
+/**
+ * Limits on the size of a file's privacy dictionary. When a limit is exceeded, the least
+ * valuable entries are dropped, and their strings are left untouched. An entry's value is
+ * the number of bytes that replacing its strings with dictionary references saves, minus
+ * the size of the entry itself. Tagged templates are never dropped.
+ */
+export interface DictionaryBudget {
+  /** If specified, the dictionary may contain at most this many entries. */
+  maxEntries?: number;
+  /** If specified, the dictionary's entries may total at most this many bytes. */
+  maxBytes?: number;
+  /**
+   * If specified, the dictionary may grow the file by at most this fraction of its original
+   * size (e.g. 0.1 for 10%), taking into account the bytes saved by dictionary references.
+   */
+  maxGrowthRatio?: number;
+}
+
+/**
+ * The rules used to split privacy dictionary strings into words. These must match the
+ * rules used by the version of the SDK that the instrumented code will run with.
+ */
+export interface WordTokenization {
+  /**
+   * A regular expression that matches a single word, in Rust regex syntax. The default is
+   * `'\\p{Letter}+|\\p{Symbol}+'`.
+   */
+  pattern?: string;
+  /** Words longer than this many UTF-16 code units are discarded. The default is 20. */
+  maxWordLength?: number;
+  /** If true (the default), words are converted to lowercase. */
+  lowercase?: boolean;
+}
+
+/** A kind of sensitive value. A string is rejected if it contains one anywhere. */
+export type SensitiveStringRule =
+  | 'email'
+  | 'jwt'
+  | 'apiKey'
+  | 'awsAccessKey'
+  | 'privateKey'
+  | 'creditCard'
+  | 'highEntropy';
+
+/**
+ * How strings that look like secrets or personal information are kept out of the privacy
+ * dictionary. Rejected strings are left untouched, and each rejection is reported as a
+ * warning.
+ */
+export interface SensitiveStringDetection {
+  /**
+   * How aggressively to look for sensitive strings. 'low' only looks for credentials, like
+   * API keys and private keys. 'medium' (the default) also looks for personal information,
+   * like email addresses and credit card numbers. 'high' also flags shorter and less random
+   * tokens as high-entropy. 'off' disables detection.
+   */
+  sensitivity?: 'off' | 'low' | 'medium' | 'high';
+  /** Rules that shouldn't be applied, e.g. 'email'. */
+  disabledRules?: SensitiveStringRule[];
+}
 
 export interface PrivacyOptions {
   /** The source for the helper function used to add strings to the dictionary. */
   addToDictionaryHelper?: PrivacyHelperSource;
+  /**
+   * Rules that decide whether strings are added to the dictionary. The rules are checked
+   * in order, and the first matching rule wins. If no rule matches, the preset decides.
+   */
+  stringFilters?: StringFilterRule[];
+  /**
+   * If 'default' (the default), exclude strings that look like URLs, file names, numbers,
+   * or code. If 'none', only `stringFilters` are applied. Either way, strings longer than
+   * 4096 bytes are never added to the dictionary, even if a rule includes them.
+   */
+  stringFilterPreset?: 'default' | 'none';
+  /**
+   * JSX elements whose contents should never be added to the dictionary, in addition
+   * to the built-in ones. Patterns may contain `*` wildcards, e.g. `'Icon*'`.
+   */
+  uncollectedJsxElements?: string[];
+  /**
+   * JSX attributes whose values should never be added to the dictionary, in addition
+   * to the built-in ones. Patterns may contain `*` wildcards, e.g. `'aria-*'`.
+   */
+  uncollectedJsxAttributes?: string[];
+  /**
+   * Calls whose arguments should never be added to the dictionary, in addition to
+   * the built-in ones (e.g. `eval()`). The first matching entry wins.
+   */
+  uncollectedCalls?: UncollectedCall[];
+  /** The built-in element factories to recognize. The default is `['react']`. */
+  elementFactoryPresets?: ElementFactoryPreset[];
+  /**
+   * Element factories to recognize in addition to the presets. If a call matches several
+   * factories, these take precedence.
+   */
+  elementFactories?: ElementFactory[];
+  /**
+   * If specified, split the dictionary's strings into words at build time, and pass the
+   * words to the add-to-dictionary helper as a second argument, so the helper doesn't need
+   * to do it at runtime. Strings that no dictionary reference uses are then left out of
+   * the dictionary, since their words are all the helper needs.
+   */
+  buildTimeTokenization?: WordTokenization;
+  /** Limits on the size of each file's privacy dictionary. By default, there are none. */
+  dictionaryBudget?: DictionaryBudget;
+  /**
+   * If true, register the dictionary with one call to the add-to-dictionary helper per
+   * context category (e.g. `'jsxText'` or `'errorMessage'`), passing the category as a third
+   * argument, so that the helper can apply a different policy to each category. Each entry
+   * is registered under the category it first appears in. The default is false.
+   */
+  registerPerCategory?: boolean;
+  /**
+   * How strings that look like secrets or personal information are kept out of the
+   * dictionary. By default, detection is enabled with 'medium' sensitivity.
+   */
+  sensitiveStringDetection?: SensitiveStringDetection;
 }
 
 export interface InstrumentationOptions {
@@ -110,6 +514,17 @@ export interface InstrumentationOptions {
 
 export interface RustPrivacyOptions {
   addToDictionaryHelper: PrivacyHelperSource;
+  stringFilters: StringFilterRule[];
+  stringFilterPreset: 'default' | 'none';
+  uncollectedJsxElements: string[];
+  uncollectedJsxAttributes: string[];
+  uncollectedCalls: UncollectedCall[];
+  elementFactoryPresets: ElementFactoryPreset[];
+  elementFactories: ElementFactory[];
+  buildTimeTokenization?: WordTokenization;
+  dictionaryBudget: DictionaryBudget;
+  registerPerCategory: boolean;
+  sensitiveStringDetection: SensitiveStringDetection;
 }
 
 type RustInstrumentationOptions = Required<InstrumentationOptions>;
@@ -122,10 +537,19 @@ function convertOptions(
       module: options?.input?.module ?? undefined,
       jsx: options?.input?.jsx ?? true,
       typescript: options?.input?.typescript ?? true,
+      syntaxFallback: options?.input?.syntaxFallback ?? true,
+      strictParsing: options?.input?.strictParsing ?? false,
+      externalSourceMaps: options?.input?.externalSourceMaps ?? {},
     },
     output: {
       inlineSourceMap: options?.output?.inlineSourceMap ?? false,
       embedCodeInSourceMap: options?.output?.embedCodeInSourceMap ?? true,
+      privacyDictionaryReport: options?.output?.privacyDictionaryReport ?? false,
+      externalSourceMapComment: options?.output?.externalSourceMapComment ?? 'remove',
+      indexSourceMap: options?.output?.indexSourceMap ?? false,
+      insertHeaderAfterLicenseComments:
+        options?.output?.insertHeaderAfterLicenseComments ?? false,
+      dictionaryDeclaration: options?.output?.dictionaryDeclaration ?? 'const',
     },
     privacy: {
       addToDictionaryHelper: options?.privacy?.addToDictionaryHelper ?? {
@@ -134,11 +558,60 @@ function convertOptions(
           esmModule: 'datadog:privacy-helpers.mjs',
           func: '$',
         }
-      }
+      },
+      stringFilters: options?.privacy?.stringFilters ?? [],
+      stringFilterPreset: options?.privacy?.stringFilterPreset ?? 'default',
+      uncollectedJsxElements: options?.privacy?.uncollectedJsxElements ?? [],
+      uncollectedJsxAttributes: options?.privacy?.uncollectedJsxAttributes ?? [],
+      uncollectedCalls: options?.privacy?.uncollectedCalls ?? [],
+      elementFactoryPresets: options?.privacy?.elementFactoryPresets ?? ['react'],
+      elementFactories: options?.privacy?.elementFactories ?? [],
+      buildTimeTokenization: options?.privacy?.buildTimeTokenization ?? undefined,
+      dictionaryBudget: options?.privacy?.dictionaryBudget ?? {},
+      registerPerCategory: options?.privacy?.registerPerCategory ?? false,
+      sensitiveStringDetection: options?.privacy?.sensitiveStringDetection ?? {},
     },
   };
 }
 
+/** A description of an error that occurred while instrumenting code. */
+export type InstrumentationErrorDetails =
+  | {
+    type: 'parse';
+    /** The filename or id of the code that failed to parse. */
+    id: string;
+    /** A human-readable description of the error. */
+    message: string;
+    /** The line (1-based) where the error occurred. */
+    line: number;
+    /** The column (0-based) where the error occurred. */
+    column: number;
+    /** The kind of syntax error reported by the parser. */
+    kind: string;
+    /** An excerpt of the input surrounding the error, with the error location marked. */
+    codeFrame: string;
+  }
+  | { type: 'sourceMap'; id: string; message: string }
+  | { type: 'rewrite'; id: string; message: string }
+  | { type: 'options'; id: string; message: string };
+
+/** Thrown by `instrument()` when the input can't be instrumented. */
+export class InstrumentationError extends Error {
+  readonly details: InstrumentationErrorDetails;
+
+  constructor(details: InstrumentationErrorDetails) {
+    super(details.type === 'parse'
+      ? `${details.message} (${details.id}:${details.line}:${details.column})\n${details.codeFrame}`
+      : `${details.message} (${details.id})`);
+    this.name = 'InstrumentationError';
+    this.details = details;
+  }
+}
+
+function isInstrumentationErrorDetails(e: unknown): e is InstrumentationErrorDetails {
+  return typeof e === 'object' && e !== null && 'type' in e && 'id' in e && 'message' in e;
+}
+
 let wasmPluginLoaded = false;
 async function ensureWasmPluginLoaded() {
   if (wasmPluginLoaded) {
@@ -155,6 +628,7 @@ async function ensureWasmPluginLoaded() {
   * Instrument the provided input, applying the provided options, if any.
   *
   * @returns the instrumented code.
+  * @throws {InstrumentationError} if the input can't be instrumented.
   */
 export function instrument(
   input: InstrumentationInput,
@@ -162,9 +636,32 @@ export function instrument(
 ): InstrumentationOutput {
   try {
     ensureWasmPluginLoaded();
-    return transform(input, convertOptions(options));
+    return transform(input, convertOptions(options), options?.input?.resolveSourceMap);
+  } catch (e) {
+    const error = isInstrumentationErrorDetails(e) ? new InstrumentationError(e) : e;
+    console.log(`Instrumentation threw error`, error);
+    throw error;
+  }
+}
+
+/**
+  * Instrument several files at once, applying the provided options, if any. Instead of
+  * declaring its own privacy dictionary, each file imports a dictionary shared by the whole
+  * batch, which is returned as a separate module.
+  *
+  * @returns the instrumented code and the shared dictionary module.
+  * @throws {InstrumentationError} if any of the inputs can't be instrumented.
+  */
+export function instrumentBatch(
+  input: InstrumentationBatchInput,
+  options?: InstrumentationOptions | undefined,
+): InstrumentationBatchOutput {
+  try {
+    ensureWasmPluginLoaded();
+    return transform_batch(input, convertOptions(options), options?.input?.resolveSourceMap);
   } catch (e) {
-    console.log(`Instrumentation threw error`, e);
-    throw e;
+    const error = isInstrumentationErrorDetails(e) ? new InstrumentationError(e) : e;
+    console.log(`Instrumentation threw error`, error);
+    throw error;
   }
 }
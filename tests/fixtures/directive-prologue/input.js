@@ -0,0 +1,10 @@
+// The header must follow the directive prologue, or "use strict" would no longer apply.
+'use strict'
+"use custom"
+
+const greeting = "Hello there";
+
+module.exports = function greet(name) {
+  "use strict";
+  return `${greeting}, ${name}`;
+};
@@ -0,0 +1,8 @@
+"use client";import{$}from'datadog:privacy-helpers.mjs';const D=$(['Clicked ',' times']);
+
+import { useState } from "react";
+
+export function Counter() {
+  const [count, setCount] = useState(0);
+  return <button onClick={() => setCount(count + 1)}>{D[0]}{count} times</button>;
+}
@@ -1,11 +1,11 @@
-import{$}from'datadog:privacy-helpers.mjs';const D=$(['bar','foo','baz']);"use strict";
+"use strict";import{$}from'datadog:privacy-helpers.mjs';const D=$(['foo','bar','baz']);
 function f(v) {
     switch (v) {
-        case D[1]: return 0;
-        case D[0]: return 1;
+        case D[0]: return 0;
+        case D[1]: return 1;
         case D[2]: return 2;
     }
 }
-const foo = D[0];
+const foo = D[1];
 console.log(f(foo));
 
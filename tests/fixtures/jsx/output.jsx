@@ -1,4 +1,4 @@
-import{$}from'datadog:privacy-helpers.mjs';const D=$(["Escape special characters with the \"\\\" character.","Text content. With more than one line. And another."," entity reference",'quotes"inside','with"\'quotes','--top-offset',"some content","something'",'error-info','value',`--is-stuck`,`px`]);import {
+import{$}from'datadog:privacy-helpers.mjs';const D=$(["something'",'quotes"inside','with"\'quotes','value','Text content. With more than one line. And another.','error-info','--top-offset','--is-stuck','Escape special characters with the "\\" character.','some content','px',' entity reference']);import {
   AnotherComponent,
   ErrorHandler,
   Flex,
@@ -13,34 +13,34 @@ import{$}from'datadog:privacy-helpers.mjs';const D=$(["Escape special characters
 
 export function MyComponent(props) {
   return (
-    <SomeComponent attr={D[7]} with={D[3]}>
-      <AnotherComponent attr={D[4]} />
+    <SomeComponent attr={D[0]} with={D[1]}>
+      <AnotherComponent attr={D[2]} />
       <SomethingElse stuff={{
-        key: D[9],
+        key: D[3],
         anotherKey: props.key2,
       }}
-      >{D[1]}</SomethingElse>
+      >{D[4]}</SomethingElse>
       <ErrorHandler
         onError={(info) => {
-          reportError({ [D[8]]: info });
+          reportError({ [D[5]]: info });
         }}
       />
       <Flex
         stuff={
           {
-            [D[5]]: `${topOffset}px`,
+            [D[6]]: `${topOffset}px`,
           }
         }
         clazzes={classNames(BLOCK, {
-          [`${BLOCK}${D[10]}`]: isStuck,
+          [`${BLOCK}${D[7]}`]: isStuck,
         })}
-      >{D[0]}</Flex>
+      >{D[8]}</Flex>
       <svg
         fill={props.fill}
-        content={(() => { return D[6]; })()}
+        content={(() => { return D[9]; })()}
         xmlns="http://www.w3.org/2000/svg"
         viewBox="0 0 192 192"
-        data={D[2]}
+        data={D[11]}
       >
         <g>
           <rect x='84' y='156' width='20' height='20' />
@@ -0,0 +1,16 @@
+// These strings are long, but each one is used only once, so they benefit about as much from
+// a two-digit dictionary reference as from a one-digit one.
+const long0 = "a long string, number 0";
+const long1 = "a long string, number 1";
+const long2 = "a long string, number 2";
+const long3 = "a long string, number 3";
+const long4 = "a long string, number 4";
+const long5 = "a long string, number 5";
+const long6 = "a long string, number 6";
+const long7 = "a long string, number 7";
+const long8 = "a long string, number 8";
+const long9 = "a long string, number 9";
+
+// This string is short, and is only worth replacing with a one-digit dictionary reference,
+// but it's used many times, so it should get one of the one-digit entries.
+const short = ["abc", "abc", "abc", "abc", "abc", "abc", "abc", "abc", "abc", "abc"];
@@ -0,0 +1,16 @@
+import{$}from'datadog:privacy-helpers.mjs';const D=$(['a long string, number 1','a long string, number 2','a long string, number 3','a long string, number 4','a long string, number 5','a long string, number 6','a long string, number 7','a long string, number 8','a long string, number 9','abc','a long string, number 0']);// These strings are long, but each one is used only once, so they benefit about as much from
+// a two-digit dictionary reference as from a one-digit one.
+const long0 = D[10];
+const long1 = D[0];
+const long2 = D[1];
+const long3 = D[2];
+const long4 = D[3];
+const long5 = D[4];
+const long6 = D[5];
+const long7 = D[6];
+const long8 = D[7];
+const long9 = D[8];
+
+// This string is short, and is only worth replacing with a one-digit dictionary reference,
+// but it's used many times, so it should get one of the one-digit entries.
+const short = [D[9], D[9], D[9], D[9], D[9], D[9], D[9], D[9], D[9], D[9]];
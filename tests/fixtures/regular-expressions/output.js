@@ -1,4 +1,4 @@
-import{$}from'datadog:privacy-helpers.mjs';const D=$([`"`]);import compact from 'lodash/compact';
+import{$}from'datadog:privacy-helpers.mjs';const D=$(['"']);import compact from 'lodash/compact';
 
 export const PATTERN = /[:()"\\]/g;
 
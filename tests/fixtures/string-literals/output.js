@@ -1,37 +1,37 @@
-import{$}from'datadog:privacy-helpers.mjs';const D=$(["appendix","gem`'\"\u{6F}","cat\r\n\tdog","observe",'quarrel','fizz"\'"',"karat",'bowling',"egg'\"'","macrame","nanobot","pacific","hammer","image","jewel","labor"]);const foo = () => {};
+import{$}from'datadog:privacy-helpers.mjs';const D=$(['appendix',"egg'\"'",'fizz"\'"','gem`\'"o','hammer','image','jewel','karat','observe','quarrel','bowling','cat\r\n\tdog','labor','macrame','nanobot','pacific']);const foo = () => {};
 
 // Trivial string literals.
 const trivial1 = "";
 const trivial2 = '';
 const trivial3 = D[0];
-const trivial4 = D[7];
+const trivial4 = D[10];
 
 // Escape sequences.
-const escape1 = D[2];
-const escape2 = D[8];
-const escape3 = D[5];
-const escape4 = D[1];
+const escape1 = D[11];
+const escape2 = D[1];
+const escape3 = D[2];
+const escape4 = D[3];
 
 // String literals used in expressions.
-const expression1 = D[12].toLowerCase();
-const expression2 = foo[D[13]];
-const expression3 = foo(D[14]);
-const expression4 = D[6][1];
+const expression1 = D[4].toLowerCase();
+const expression2 = foo[D[5]];
+const expression3 = foo(D[6]);
+const expression4 = D[7][1];
 
 // String literals used in function declarations.
-const func1 = (_a = D[15]) => {};
+const func1 = (_a = D[12]) => {};
 
 // String literals used in object literals.
-const object1 = { [D[9]]: 1 };
-const object2 = { [D[10]]: 2 };
-const object3 = { [D[3]](_a){} };
+const object1 = { [D[13]]: 1 };
+const object2 = { [D[14]]: 2 };
+const object3 = { [D[8]](_a){} };
 
 // String literals used in array literals.
-const array1 = [D[11]];
-const array2 = [...D[4]];
+const array1 = [D[15]];
+const array2 = [...D[9]];
 
 // Reuse of string literals we've already seen.
 const reuse1 = D[0];
-const reuse2 = D[6][1];
-const reuse3 = { [D[3]](_a){} };
-const reuse4 = [...D[4]];
+const reuse2 = D[7][1];
+const reuse3 = { [D[8]](_a){} };
+const reuse4 = [...D[9]];
@@ -1,13 +1,13 @@
-import{$}from'datadog:privacy-helpers.mjs';const D=$(['francis','result','emmett',"arnold",'betty',$`danielle`,`charles`]);const tag = () => D[1];
+import{$}from'datadog:privacy-helpers.mjs';const D=$(['result','emmett','arnold','betty','charles',$`danielle`,'francis']);const tag = () => D[0];
 
 export const foo = (value) => {
-  const emmett = D[2];
+  const emmett = D[1];
 
   switch (value) {
-    case D[3]:
+    case D[2]:
       return 1;
 
-    case D[4]:
+    case D[3]:
       return 2;
 
     case `charles`:
@@ -19,7 +19,7 @@ export const foo = (value) => {
     case emmett:
       return 5;
 
-    case D[0]:
+    case D[6]:
       return 6;
 
     default:
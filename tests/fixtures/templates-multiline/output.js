@@ -1,23 +1,17 @@
-import{$}from'datadog:privacy-helpers.mjs';const D=$([`This is
-  a multiline template literal
-with several embedded newlines
-    and inconsistent spacing.`,`This is
-  a multiline template literal
-with expressions `,` embedded here
-    and `,'foo','bar',$`This is
+import{$}from'datadog:privacy-helpers.mjs';const D=$(['foo','bar','This is\n  a multiline template literal\nwith several embedded newlines\n    and inconsistent spacing.','This is\n  a multiline template literal\nwith expressions ',' embedded here\n    and ',' there.',$`This is
   a multiline template literal
 with several embedded newlines
     and inconsistent spacing.`,$`This is
   a multiline template literal
 with expressions ${0} embedded here
-    and ${0} there.`,` there.`]);const foo = D[3];
-const bar = D[4];
+    and ${0} there.`]);const foo = D[0];
+const bar = D[1];
 const tag = (v) => v;
 
-const multilineTemplateLiteral = `${D[0]}`;
+const multilineTemplateLiteral = `${D[2]}`;
 
-const multilineTemplateLiteralWithExpressions = `${D[1]}${foo}${D[2]}${bar} there.`;
+const multilineTemplateLiteralWithExpressions = `${D[3]}${foo}${D[4]}${bar} there.`;
 
-const multilineTaggedTemplateLiteral = tag(D[5]);
+const multilineTaggedTemplateLiteral = tag(D[6]);
 
-const multilineTaggedTemplateLiteralWithExpressions = tag(D[6], foo, bar);
+const multilineTaggedTemplateLiteralWithExpressions = tag(D[7], foo, bar);
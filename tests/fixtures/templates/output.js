@@ -1,34 +1,34 @@
-import{$}from'datadog:privacy-helpers.mjs';const D=$([`\`'"\u{6F}`,`abc\r\n\t123`,`gallingly`,`hairbrush`,`idealists`,`absolute`,`backdrops`,`cacophony`,`dachshund`,`eagerness`,`fabricate`,`newlyweds`,`orthodoxy`,`paralyzed`,`quadrants`,`reflexive`,`waferlike`,`yardstick`,$`superior${0}vertical`,$`triangle${0}umbrella`,$``,$`judgment`,$`keyboard${0}laughter${0}material`,$`abstract`,$`doubtful${0}endeavor`,$`bacteria${0}contrast`]);const foo = () => {};
+import{$}from'datadog:privacy-helpers.mjs';const D=$(['absolute','eagerness','fabricate','gallingly','hairbrush','idealists','abc\r\n\t123','`\'"o',$`superior${0}vertical`,$`triangle${0}umbrella`,'backdrops','cacophony','dachshund',$``,$`judgment`,$`keyboard${0}laughter${0}material`,'newlyweds','orthodoxy','paralyzed','quadrants','reflexive','waferlike','yardstick',$`abstract`,$`doubtful${0}endeavor`,$`bacteria${0}contrast`]);const foo = () => {};
 const bar = 1;
 
 // Trivial template expressions.
 const trivial1 = ``;
-const trivial2 = `${D[5]}`;
-const trivial3 = `${D[6]}${bar}`;
-const trivial4 = `${bar}${D[7]}`;
-const trivial5 = `${D[8]}${bar}`;
-const trivial6 = `${D[9]}${bar}${D[10]}`;
-const trivial7 = `${D[2]}${bar}${D[3]}${bar}${D[4]}${bar}`;
+const trivial2 = `${D[0]}`;
+const trivial3 = `${D[10]}${bar}`;
+const trivial4 = `${bar}${D[11]}`;
+const trivial5 = `${D[12]}${bar}`;
+const trivial6 = `${D[1]}${bar}${D[2]}`;
+const trivial7 = `${D[3]}${bar}${D[4]}${bar}${D[5]}${bar}`;
 
 // Escape sequences.
-const escape1 = `${D[1]}`;
-const escape2 = `${D[0]}`;
+const escape1 = `${D[6]}`;
+const escape2 = `${D[7]}`;
 
 // Tagged template expressions.
-const tagged1 = foo(D[20]);
-const tagged2 = foo(D[21]);
-const tagged3 = foo(D[22], bar, bar);
+const tagged1 = foo(D[13]);
+const tagged2 = foo(D[14]);
+const tagged3 = foo(D[15], bar, bar);
 
 // Nested template expressions.
-const nested1 = `${D[11]}${`${D[12]}${`${D[13]}`}${D[14]}`}${D[15]}`;
-const nested2 = foo(D[18], foo(D[19], bar));
-const nested3 = `${D[16]}${bar}${D[17]}${foo(D[23])}`;
+const nested1 = `${D[16]}${`${D[17]}${`${D[18]}`}${D[19]}`}${D[20]}`;
+const nested2 = foo(D[8], foo(D[9], bar));
+const nested3 = `${D[21]}${bar}${D[22]}${foo(D[23])}`;
 
 // Using the results of template expressions as a tag.
 const resulttag1 = foo(D[25], bar)(D[24], bar);
 
 // Reuse of quasis and template expressions that we've already seen.
-const reuse1 = `${D[5]}`;
-const reuse2 = `${D[2]}${bar}${D[3]}${bar}${D[4]}${bar}`;
-const reuse3 = `${D[0]}`;
-const reuse4 = foo(D[18], foo(D[19], bar));
+const reuse1 = `${D[0]}`;
+const reuse2 = `${D[3]}${bar}${D[4]}${bar}${D[5]}${bar}`;
+const reuse3 = `${D[7]}`;
+const reuse4 = foo(D[8], foo(D[9], bar));
@@ -1,6 +1,6 @@
-import{$}from'datadog:privacy-helpers.mjs';const D=$(['katherine',"charles",'ophelia','richard','result',"arnold",'george','marcia','nathan',"betty",'harry','irina','peter','quinn','jack',$`danielle`,$`emmett`,$`francine`]);const tag = () => D[4];
+import{$}from'datadog:privacy-helpers.mjs';const D=$(['result','arnold','betty','charles',$`danielle`,$`emmett`,$`francine`,'george','harry','irina','jack','katherine','marcia','nathan','ophelia','peter','quinn','richard']);const tag = () => D[0];
 
-export const arnold = D[5] ? D[9] : D[1];
-export const danielle = tag(D[15]) ? tag(D[16]) : tag(D[17]);
-export const george = D[6] ? (D[10], D[11]) : (D[14], D[0]);
-export const leopold = { [D[7]]: D[8] } ? { [D[2]]: D[12] } : { 'quinn': D[3] };
+export const arnold = D[1] ? D[2] : D[3];
+export const danielle = tag(D[4]) ? tag(D[5]) : tag(D[6]);
+export const george = D[7] ? (D[8], D[9]) : (D[10], D[11]);
+export const leopold = { [D[12]]: D[13] } ? { [D[14]]: D[15] } : { 'quinn': D[17] };
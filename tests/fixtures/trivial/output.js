@@ -1,2 +1,2 @@
-import{$}from'datadog:privacy-helpers.mjs';const D=$(["test"]);console.log(/* (attached comment) */ D[0]);
+import{$}from'datadog:privacy-helpers.mjs';const D=$(['test']);console.log(/* (attached comment) */ D[0]);
 
@@ -1,4 +1,4 @@
-import{$}from'datadog:privacy-helpers.mjs';const D=$(['Number Three','Number One','Letter A',"Letter B","Number Two",'Number Two','Letter C','Alpha',"Beta",`Letter C`,`Gamma`]);enum GreekLetters {
+import{$}from'datadog:privacy-helpers.mjs';const D=$(['Alpha','Beta','Gamma','Letter A','Letter B','Letter C','Number One','Number Two','Number Three']);enum GreekLetters {
   Alpha = 'Alpha',
   Beta = "Beta",
   Gamma = `Gamma`,
@@ -1,9 +1,9 @@
-import{$}from'datadog:privacy-helpers.mjs';const D=$(['SpecificNode','pattern']);import { Variant } from './variant';
+import{$}from'datadog:privacy-helpers.mjs';const D=$(['pattern','SpecificNode']);import { Variant } from './variant';
 
 export class SpecificNode
-    extends Variant(D[0])<[string, {}]>
+    extends Variant(D[1])<[string, {}]>
 {
     pattern(): string {
-        return { escaped: D[1] };
+        return { escaped: D[0] };
     }
 }
@@ -1,11 +1,11 @@
-import{$}from'datadog:privacy-helpers.mjs';const D=$(['betelgeuse','cassiopeia','andromeda']);declare module SomeModule {
-  const variable = D[2];
+import{$}from'datadog:privacy-helpers.mjs';const D=$(['andromeda','betelgeuse','cassiopeia']);declare module SomeModule {
+  const variable = D[0];
 }
 
 declare module "SomeModule" {
-  const variable = D[0];
+  const variable = D[1];
 }
 
 declare namespace SomeNamespace {
-  const variable = D[1];
+  const variable = D[2];
 }
@@ -0,0 +1,10 @@
+// Type assertions and generic arrow functions are ambiguous with JSX, so
+// this file can only be parsed with JSX disabled.
+const identity = <T>(value: T) => value;
+
+export function describe(input: unknown): string {
+  const label = <string>input;
+  return identity(`Label: ${label}`);
+}
+
+export const greeting = identity('Hello there');
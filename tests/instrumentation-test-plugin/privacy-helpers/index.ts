@@ -79,7 +79,11 @@ function processDictionaryQueue(): void {
 }
 
 export function $(
-  items: DictionaryItem[] | TemplateStringsArray
+  items: DictionaryItem[] | TemplateStringsArray,
+  words?: string[] | 0,
+  // If the dictionary is registered one category at a time, each call is passed the category
+  // of its items. This helper applies the same policy to every category.
+  _category?: string
 ): (DictionaryItem[] | TemplateStringsArray) {
   if ((items as TemplateStringsArray).raw) {
     // We're being used as a template tag function. The invocation will look like this:
@@ -90,6 +94,13 @@ export function $(
     return items;
   }
 
+  // If the instrumentation split the dictionary into words at build time, the words are
+  // passed as a second argument, and there's nothing left to process.
+  if (words) {
+    addWordsToDictionary(words);
+    return items;
+  }
+
   // We're being used to construct a dictionary. Add our arguments to the queue and
   // schedule a callback to process them if one isn't already scheduled.
   queue.push(items as DictionaryItem[]);